@@ -11,8 +11,16 @@ use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use anyhow::{Context, Result};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 
+use crate::events::UserSignal;
+use crate::fuzzy;
+use crate::keys::{KeyAction, KeyMap};
 use crate::output::{sanitize_text, LogLine, StreamKind, TimelineBuffer, TimelineEntry};
+use crate::palette::{self, PaletteCommand, PaletteEntry};
+use crate::panes::{Pane, PaneLayout, SplitDirection, MAX_PANES};
 use crate::process::{ProcessSpec, ProcessState, ProcessStatus};
+use crate::reaper::TreeNode;
+use crate::stats::StatsSample;
+use crate::theme::Theme;
 
 /// Modes of user input interaction.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,6 +35,12 @@ pub enum InputMode {
     Input,
     /// Typing a group name to restart.
     Group,
+    /// Answering a yes/no prompt.
+    Confirm,
+    /// Picking a command from the command palette.
+    Palette,
+    /// Picking a process to jump to by name or tag.
+    GoTo,
 }
 
 /// The main application state container.
@@ -60,6 +74,8 @@ pub struct App {
     pub log_view_height: usize,
     /// Width of the process list area (for mouse clicks).
     pub process_list_width: u16,
+    /// Processes with a row on screen in the process list at the last draw, top to bottom.
+    pub visible_processes: Vec<usize>,
     /// Whether the timeline view is active.
     pub timeline_view: bool,
     /// Whether the timeline is automatically following new output.
@@ -74,13 +90,32 @@ pub struct App {
     pub use_symbols: bool,
     /// Whether to show the help modal/overlay.
     pub show_help: bool,
+    /// Whether to show the resource detail pane for the selected process.
+    pub show_details: bool,
+    /// Split log panes; empty when a single log view is shown.
+    pub panes: Vec<Pane>,
+    /// Index of the focused pane, which always shows the selected process.
+    pub focused_pane: usize,
+    /// How the split panes are arranged.
+    pub split_direction: SplitDirection,
+    /// Active key bindings for normal mode.
+    pub keys: KeyMap,
+    /// Colours the TUI draws with.
+    pub theme: Theme,
+    /// Highlighted entry of the command palette.
+    pub palette_index: usize,
+    /// Highlighted entry of the process switcher.
+    pub goto_index: usize,
+    palette_recent: Vec<String>,
     log_viewport: Option<LogViewport>,
+    pane_viewports: Vec<LogViewport>,
     visible_raw_lines: Vec<String>,
     selection_start: Option<usize>,
     selection_end: Option<usize>,
     selection_active: bool,
     selection_scope: Option<SelectionScope>,
     status_message: Option<StatusMessage>,
+    confirm: Option<(String, AppAction)>,
 }
 
 /// Actions resulting from user interaction.
@@ -96,6 +131,10 @@ pub enum AppAction {
     Restart(usize),
     /// Restart all processes in a group/tag.
     RestartGroup(String),
+    /// Stop all processes in a group/tag.
+    KillGroup(String),
+    /// Send a signal to a process without stopping it.
+    Signal(usize, UserSignal),
     /// Export logs to a file.
     Export(usize),
     /// Send text to a process's stdin.
@@ -104,6 +143,8 @@ pub enum AppAction {
     SendInputBytes(usize, Vec<u8>),
     /// Copy selected logs (or full buffer) to clipboard.
     CopySelection,
+    /// Kill the process holding a port, then restart the process that needed it.
+    KillPortOwner { id: usize, pid: u32, port: u16 },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -166,6 +207,7 @@ impl App {
             should_quit: false,
             log_view_height: 0,
             process_list_width: 0,
+            visible_processes: Vec::new(),
             timeline_view: false,
             timeline_follow: true,
             timeline_scroll: 0,
@@ -173,13 +215,24 @@ impl App {
             strip_ansi: false,
             use_symbols,
             show_help: false,
+            show_details: false,
+            panes: Vec::new(),
+            focused_pane: 0,
+            split_direction: SplitDirection::default(),
+            keys: KeyMap::default(),
+            theme: Theme::default(),
+            palette_index: 0,
+            goto_index: 0,
+            palette_recent: Vec::new(),
             log_viewport: None,
+            pane_viewports: Vec::new(),
             visible_raw_lines: Vec::new(),
             selection_start: None,
             selection_end: None,
             selection_active: false,
             selection_scope: None,
             status_message: None,
+            confirm: None,
         }
     }
 
@@ -191,6 +244,11 @@ impl App {
         self.processes.get_mut(self.selected)
     }
 
+    /// Looks up a process index by name.
+    pub fn process_id(&self, name: &str) -> Option<usize> {
+        self.processes.iter().position(|process| process.spec.name == name)
+    }
+
     pub fn on_process_starting(&mut self, id: usize) {
         if let Some(process) = self.processes.get_mut(id) {
             process.status = ProcessStatus::Starting;
@@ -202,6 +260,7 @@ impl App {
     pub fn on_process_ready(&mut self, id: usize) {
         if let Some(process) = self.processes.get_mut(id) {
             process.ready = true;
+            process.ready_at = Some(Instant::now());
         }
     }
 
@@ -211,14 +270,29 @@ impl App {
             process.pid = Some(pid);
             process.started_at = Some(Instant::now());
             process.exit_code = None;
+            process.starts += 1;
+            process.ready_at = None;
+            process.timed_out = false;
+            process.trigger_failure = None;
+        }
+    }
+
+    pub fn on_process_timed_out(&mut self, id: usize) {
+        if let Some(process) = self.processes.get_mut(id) {
+            process.timed_out = true;
+        }
+    }
+
+    pub fn on_process_trigger_failed(&mut self, id: usize, reason: String) {
+        if let Some(process) = self.processes.get_mut(id) {
+            process.trigger_failure = Some(reason);
         }
     }
 
     pub fn on_process_output(&mut self, id: usize, line: String, stream: StreamKind) {
         let selected = self.selected == id;
-        let selected_follow = selected
-            .then(|| self.processes.get(id).map(|p| p.follow).unwrap_or(true))
-            .unwrap_or(false);
+        let selected_follow =
+            selected && self.processes.get(id).map(|p| p.follow).unwrap_or(true);
         if let Some(process) = self.processes.get_mut(id) {
             let dropped = process.logs.push(LogLine {
                 text: line.clone(),
@@ -227,11 +301,23 @@ impl App {
             if dropped && !process.follow && process.scroll > 0 {
                 process.scroll -= 1;
             }
+            if dropped {
+                for (index, pane) in self.panes.iter_mut().enumerate() {
+                    if index != self.focused_pane
+                        && pane.process == id
+                        && !pane.follow
+                        && pane.scroll > 0
+                    {
+                        pane.scroll -= 1;
+                    }
+                }
+            }
         }
 
         let dropped_timeline = self.timeline.push(TimelineEntry {
             text: line,
             process_id: id,
+            stream,
         });
         if dropped_timeline && !self.timeline_follow && self.timeline_scroll > 0 {
             self.timeline_scroll -= 1;
@@ -250,16 +336,52 @@ impl App {
         }
     }
 
-    pub fn on_process_exited(&mut self, id: usize, code: Option<i32>) {
+    /// Records an exit; `stopped` is set when Piperack asked the process to stop.
+    pub fn on_process_exited(
+        &mut self,
+        id: usize,
+        code: Option<i32>,
+        signal: Option<i32>,
+        stopped: bool,
+    ) {
         if let Some(process) = self.processes.get_mut(id) {
             process.status = ProcessStatus::Exited { code };
             process.exit_code = code;
+            process.exit_signal = signal;
+            process.stopped = stopped;
+            process.succeeded = process.exit_succeeded(code);
+            process.stats.record(None);
         }
     }
 
     pub fn on_process_failed(&mut self, id: usize, error: String) {
         if let Some(process) = self.processes.get_mut(id) {
             process.status = ProcessStatus::Failed { error };
+            process.stopped = false;
+            process.stats.record(None);
+        }
+    }
+
+    /// PIDs of running processes, indexed by process ID (for stats sampling).
+    pub fn running_pids(&self) -> Vec<Option<u32>> {
+        self.processes
+            .iter()
+            .map(|process| match process.status {
+                ProcessStatus::Running => process.pid,
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn on_process_stats(&mut self, id: usize, sample: Option<StatsSample>) {
+        if let Some(process) = self.processes.get_mut(id) {
+            process.stats.record(sample);
+        }
+    }
+
+    pub fn on_process_tree(&mut self, id: usize, tree: Vec<TreeNode>) {
+        if let Some(process) = self.processes.get_mut(id) {
+            process.tree = tree;
         }
     }
 
@@ -272,6 +394,11 @@ impl App {
                         self.selected = index;
                         self.update_search_matches();
                     }
+                } else if let Some(index) = self
+                    .pane_at(mouse.row, mouse.column)
+                    .filter(|index| *index != self.focused_pane)
+                {
+                    self.focus_pane(index);
                 } else if let Some(row) = self.log_row_at(mouse.row, mouse.column) {
                     self.freeze_follow_for_selection();
                     self.selection_start = Some(row);
@@ -280,11 +407,9 @@ impl App {
                     self.selection_scope = Some(self.current_selection_scope());
                 }
             }
-            MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
-                if self.selection_active {
-                    if let Some(row) = self.log_row_at(mouse.row, mouse.column) {
-                        self.selection_end = Some(row);
-                    }
+            MouseEventKind::Drag(crossterm::event::MouseButton::Left) if self.selection_active => {
+                if let Some(row) = self.log_row_at(mouse.row, mouse.column) {
+                    self.selection_end = Some(row);
                 }
             }
             MouseEventKind::Up(crossterm::event::MouseButton::Left) => {
@@ -302,11 +427,48 @@ impl App {
             InputMode::Search => self.handle_search_input(key),
             InputMode::Filter => self.handle_filter_input(key),
             InputMode::Group => self.handle_group_input(key),
+            InputMode::Confirm => self.handle_confirm_input(key),
             InputMode::Input => self.handle_input_key(key),
+            InputMode::Palette => self.handle_palette_input(key),
+            InputMode::GoTo => self.handle_goto_input(key),
             InputMode::Normal => self.handle_normal_input(key),
         }
     }
 
+    /// Asks the user to confirm `action`; it is returned from `handle_key` on `y`.
+    ///
+    /// The prompt is only shown from normal mode so it never interrupts typing.
+    pub fn request_confirm(&mut self, prompt: impl Into<String>, action: AppAction) -> bool {
+        if self.input_mode != InputMode::Normal {
+            return false;
+        }
+        self.confirm = Some((prompt.into(), action));
+        self.input_mode = InputMode::Confirm;
+        true
+    }
+
+    pub fn confirm_prompt(&self) -> Option<&str> {
+        self.confirm.as_ref().map(|(prompt, _)| prompt.as_str())
+    }
+
+    fn handle_confirm_input(&mut self, key: KeyEvent) -> AppAction {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                self.input_mode = InputMode::Normal;
+                self.confirm
+                    .take()
+                    .map(|(_, action)| action)
+                    .unwrap_or(AppAction::None)
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.confirm = None;
+                AppAction::None
+            }
+            _ => AppAction::None,
+        }
+    }
+
     fn handle_group_input(&mut self, key: KeyEvent) -> AppAction {
         match key.code {
             KeyCode::Esc => {
@@ -435,71 +597,230 @@ impl App {
         }
     }
 
+    /// The palette entries matching the typed query, best first.
+    pub fn palette_matches(&self) -> Vec<PaletteEntry> {
+        palette::rank(palette::commands(self), &self.input, &self.palette_recent)
+    }
+
+    fn handle_palette_input(&mut self, key: KeyEvent) -> AppAction {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.input.clear();
+                AppAction::None
+            }
+            KeyCode::Enter => {
+                let entry = self.palette_matches().into_iter().nth(self.palette_index);
+                self.input_mode = InputMode::Normal;
+                self.input.clear();
+                match entry {
+                    Some(entry) => self.run_palette_entry(entry),
+                    None => AppAction::None,
+                }
+            }
+            KeyCode::Up => {
+                self.palette_index = self.palette_index.saturating_sub(1);
+                AppAction::None
+            }
+            KeyCode::Char('p') if ctrl => {
+                self.palette_index = self.palette_index.saturating_sub(1);
+                AppAction::None
+            }
+            KeyCode::Down => {
+                self.palette_move_down();
+                AppAction::None
+            }
+            KeyCode::Char('n') if ctrl => {
+                self.palette_move_down();
+                AppAction::None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.palette_index = 0;
+                AppAction::None
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.input.push(c);
+                self.palette_index = 0;
+                AppAction::None
+            }
+            _ => AppAction::None,
+        }
+    }
+
+    fn palette_move_down(&mut self) {
+        let len = self.palette_matches().len();
+        if self.palette_index + 1 < len {
+            self.palette_index += 1;
+        }
+    }
+
+    fn run_palette_entry(&mut self, entry: PaletteEntry) -> AppAction {
+        self.palette_recent.retain(|label| *label != entry.label);
+        self.palette_recent.insert(0, entry.label);
+        self.palette_recent.truncate(palette::RECENT_LIMIT);
+        match entry.command {
+            PaletteCommand::Key(action) => self.run_key_action(action),
+            PaletteCommand::Restart(id) => AppAction::Restart(id),
+            PaletteCommand::Kill(id) => AppAction::Kill(id),
+            PaletteCommand::Export(id) => AppAction::Export(id),
+            PaletteCommand::Signal(id, signal) => AppAction::Signal(id, signal),
+            PaletteCommand::RestartGroup(tag) => AppAction::RestartGroup(tag),
+            PaletteCommand::KillGroup(tag) => AppAction::KillGroup(tag),
+            PaletteCommand::ExportTimeline => {
+                if let Err(err) = self.export_timeline() {
+                    self.set_status_message(format!("Export failed: {}", err));
+                }
+                AppAction::None
+            }
+        }
+    }
+
     fn handle_normal_input(&mut self, key: KeyEvent) -> AppAction {
+        match (self.keys.action(&key), key.code) {
+            (Some(action), _) => self.run_key_action(action),
+            // Digits not bound to an action jump to the Nth process visible in the list.
+            (None, KeyCode::Char(c @ '1'..='9'))
+                if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                let nth = c as usize - '1' as usize;
+                let id = if self.visible_processes.is_empty() {
+                    Some(nth)
+                } else {
+                    self.visible_processes.get(nth).copied()
+                };
+                if let Some(id) = id {
+                    self.select_process(id);
+                }
+                AppAction::None
+            }
+            _ => AppAction::None,
+        }
+    }
+
+    /// Selects process `id` (ignored when out of range), as Up/Down would.
+    pub fn select_process(&mut self, id: usize) {
+        if id >= self.processes.len() || id == self.selected {
+            return;
+        }
+        self.exit_input_mode();
+        self.clear_selection();
+        self.selected = id;
+        self.update_search_matches();
+        if self.selected_following() {
+            self.ensure_follow();
+        }
+    }
+
+    /// Processes whose name or a tag matches the typed query, best first.
+    pub fn goto_matches(&self) -> Vec<usize> {
+        let mut scored: Vec<(u32, usize)> = self
+            .processes
+            .iter()
+            .enumerate()
+            .filter_map(|(id, process)| {
+                std::iter::once(&process.spec.name)
+                    .chain(process.spec.tags.iter())
+                    .filter_map(|text| fuzzy::score(&self.input, text))
+                    .max()
+                    .map(|score| (score, id))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().map(|(_, id)| id).collect()
+    }
+
+    fn handle_goto_input(&mut self, key: KeyEvent) -> AppAction {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
         match key.code {
-            KeyCode::Char('q') => {
+            KeyCode::Esc => {
+                self.input_mode = InputMode::Normal;
+                self.input.clear();
+            }
+            KeyCode::Enter => {
+                let target = self.goto_matches().get(self.goto_index).copied();
+                self.input_mode = InputMode::Normal;
+                self.input.clear();
+                if let Some(id) = target {
+                    self.select_process(id);
+                }
+            }
+            KeyCode::Up => self.goto_index = self.goto_index.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => self.goto_index = self.goto_index.saturating_sub(1),
+            KeyCode::Down => self.goto_move_down(),
+            KeyCode::Char('n') if ctrl => self.goto_move_down(),
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.goto_index = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.input.push(c);
+                self.goto_index = 0;
+            }
+            _ => {}
+        }
+        AppAction::None
+    }
+
+    fn goto_move_down(&mut self) {
+        if self.goto_index + 1 < self.goto_matches().len() {
+            self.goto_index += 1;
+        }
+    }
+
+    /// Runs a key-bound action, whether from its key or from the command palette.
+    fn run_key_action(&mut self, action: KeyAction) -> AppAction {
+        match action {
+            KeyAction::Quit => {
                 self.should_quit = true;
                 AppAction::Quit
             }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            KeyAction::Copy => {
                 AppAction::CopySelection
             }
-            KeyCode::Up => {
+            KeyAction::Up => {
                 if self.selected > 0 {
-                    self.exit_input_mode();
-                    self.clear_selection();
-                    self.selected -= 1;
-                    self.update_search_matches();
-                    if self.selected_following() {
-                        self.ensure_follow();
-                    }
+                    self.select_process(self.selected - 1);
                 }
                 AppAction::None
             }
-            KeyCode::Down => {
-                if self.selected + 1 < self.processes.len() {
-                    self.exit_input_mode();
-                    self.clear_selection();
-                    self.selected += 1;
-                    self.update_search_matches();
-                    if self.selected_following() {
-                        self.ensure_follow();
-                    }
-                }
+            KeyAction::Down => {
+                self.select_process(self.selected + 1);
                 AppAction::None
             }
-            KeyCode::Tab => {
+            KeyAction::Cycle => {
                 if !self.processes.is_empty() {
-                    self.exit_input_mode();
-                    self.clear_selection();
-                    self.selected = (self.selected + 1) % self.processes.len();
-                    self.update_search_matches();
-                    if self.selected_following() {
-                        self.ensure_follow();
-                    }
+                    self.select_process((self.selected + 1) % self.processes.len());
                 }
                 AppAction::None
             }
-            KeyCode::Char('f') => {
+            KeyAction::GoTo => {
+                self.input_mode = InputMode::GoTo;
+                self.input.clear();
+                self.goto_index = 0;
+                AppAction::None
+            }
+            KeyAction::Follow => {
                 self.toggle_follow();
                 AppAction::None
             }
-            KeyCode::Char('F') => {
+            KeyAction::Filter => {
                 self.input_mode = InputMode::Filter;
                 self.input = self.filter_query.clone().unwrap_or_default();
                 AppAction::None
             }
-            KeyCode::Char('j') => {
+            KeyAction::Json => {
                 self.json_formatting = !self.json_formatting;
                 AppAction::None
             }
-            KeyCode::Enter => {
+            KeyAction::Input => {
                 if self.input_enabled {
                     self.enter_input_mode();
                 }
                 AppAction::None
             }
-            KeyCode::Char('t') => {
+            KeyAction::Timeline => {
                 self.exit_input_mode();
                 self.clear_selection();
                 self.timeline_view = !self.timeline_view;
@@ -509,27 +830,27 @@ impl App {
                 }
                 AppAction::None
             }
-            KeyCode::Char('a') => {
+            KeyAction::Ansi => {
                 self.strip_ansi = !self.strip_ansi;
                 AppAction::None
             }
-            KeyCode::Char('e') => AppAction::Export(self.selected),
-            KeyCode::Char('/') => {
+            KeyAction::Export => AppAction::Export(self.selected),
+            KeyAction::Search => {
                 self.input_mode = InputMode::Search;
                 self.input = self.search_query.clone().unwrap_or_default();
                 AppAction::None
             }
-            KeyCode::Char('n') => {
+            KeyAction::NextMatch => {
                 self.jump_search(true);
                 AppAction::None
             }
-            KeyCode::Char('N') => {
+            KeyAction::PrevMatch => {
                 self.jump_search(false);
                 AppAction::None
             }
-            KeyCode::Char('r') => AppAction::Restart(self.selected),
-            KeyCode::Char('R') => AppAction::RestartGroup("all".to_string()),
-            KeyCode::Char('g') => {
+            KeyAction::Restart => AppAction::Restart(self.selected),
+            KeyAction::RestartAll => AppAction::RestartGroup("all".to_string()),
+            KeyAction::RestartGroup => {
                 self.input_mode = InputMode::Group;
                 self.input.clear();
                 // Pre-fill with current process tag if available?
@@ -543,24 +864,67 @@ impl App {
                 }
                 AppAction::None
             }
-            KeyCode::Char('?') => {
+            KeyAction::Help => {
                 self.show_help = !self.show_help;
                 AppAction::None
             }
-            KeyCode::Char('k') => AppAction::Kill(self.selected),
-            KeyCode::PageUp => {
+            KeyAction::Details => {
+                self.show_details = !self.show_details;
+                AppAction::None
+            }
+            KeyAction::Split => {
+                self.add_pane();
+                AppAction::None
+            }
+            KeyAction::ClosePane => {
+                self.close_pane();
+                AppAction::None
+            }
+            KeyAction::NextPane => {
+                if !self.panes.is_empty() {
+                    self.focus_pane((self.focused_pane + 1) % self.panes.len());
+                }
+                AppAction::None
+            }
+            KeyAction::PrevPane => {
+                if !self.panes.is_empty() {
+                    let len = self.panes.len();
+                    self.focus_pane((self.focused_pane + len - 1) % len);
+                }
+                AppAction::None
+            }
+            KeyAction::SplitDirection => {
+                self.split_direction = self.split_direction.toggle();
+                AppAction::None
+            }
+            KeyAction::Palette => {
+                self.input_mode = InputMode::Palette;
+                self.input.clear();
+                self.palette_index = 0;
+                AppAction::None
+            }
+            KeyAction::Kill => AppAction::Kill(self.selected),
+            KeyAction::PageUp => {
                 self.scroll_up(self.log_view_height.max(1));
                 AppAction::None
             }
-            KeyCode::PageDown => {
+            KeyAction::PageDown => {
                 self.scroll_down(self.log_view_height.max(1));
                 AppAction::None
             }
-            KeyCode::Home => {
+            KeyAction::ScrollUp => {
+                self.scroll_up(1);
+                AppAction::None
+            }
+            KeyAction::ScrollDown => {
+                self.scroll_down(1);
+                AppAction::None
+            }
+            KeyAction::Top => {
                 self.scroll_to_top();
                 AppAction::None
             }
-            KeyCode::End => {
+            KeyAction::Bottom => {
                 self.ensure_follow();
                 if self.timeline_view {
                     self.timeline_follow = true;
@@ -569,7 +933,6 @@ impl App {
                 }
                 AppAction::None
             }
-            _ => AppAction::None,
         }
     }
 
@@ -774,8 +1137,13 @@ impl App {
             .started_at
             .map(|t| format_duration(t.elapsed()))
             .unwrap_or_else(|| "-".into());
+        let pane = if self.panes.is_empty() {
+            String::new()
+        } else {
+            format!(" | pane: {}/{}", self.focused_pane + 1, self.panes.len())
+        };
         format!(
-            "{} | status: {} | pid: {} | lines: {} | elapsed: {} | follow: {} | ansi: {} | input: {}",
+            "{} | status: {} | pid: {} | lines: {} | elapsed: {} | follow: {} | ansi: {} | input: {}{}",
             process.spec.name,
             status,
             pid,
@@ -783,7 +1151,8 @@ impl App {
             elapsed,
             if process.follow { "on" } else { "off" },
             if self.strip_ansi { "off" } else { "on" },
-            if process.input_active { "on" } else { "off" }
+            if process.input_active { "on" } else { "off" },
+            pane
         )
     }
 
@@ -928,22 +1297,154 @@ impl App {
         }
     }
 
+    pub fn set_pane_viewports(&mut self, viewports: Vec<LogViewport>) {
+        self.pane_viewports = viewports;
+    }
+
+    /// Splits the log panel, opening a pane on the process after the selected one.
+    pub fn add_pane(&mut self) {
+        if self.processes.is_empty() {
+            return;
+        }
+        if self.timeline_view {
+            self.set_status_message("Leave the timeline (t) to split panes");
+            return;
+        }
+        if self.panes.len() >= MAX_PANES {
+            self.set_status_message(format!("At most {} panes can be open", MAX_PANES));
+            return;
+        }
+        if self.panes.is_empty() {
+            self.panes.push(self.pane_for(self.selected));
+            self.focused_pane = 0;
+        }
+        let pane = self.pane_for((self.selected + 1) % self.processes.len());
+        self.panes.insert(self.focused_pane + 1, pane);
+        self.focus_pane(self.focused_pane + 1);
+    }
+
+    /// Closes the focused pane; closing the second-to-last one ends the split.
+    pub fn close_pane(&mut self) {
+        if self.panes.is_empty() {
+            return;
+        }
+        let closing = self.focused_pane;
+        let next = if closing + 1 < self.panes.len() {
+            closing + 1
+        } else {
+            closing.saturating_sub(1)
+        };
+        self.focus_pane(next);
+        self.panes.remove(closing);
+        if self.focused_pane > closing {
+            self.focused_pane -= 1;
+        }
+        if self.panes.len() < 2 {
+            self.panes.clear();
+            self.focused_pane = 0;
+        }
+    }
+
+    /// Moves focus to pane `index`, swapping its scroll, follow and search state in.
+    pub fn focus_pane(&mut self, index: usize) {
+        if index >= self.panes.len() {
+            return;
+        }
+        self.exit_input_mode();
+        self.clear_selection();
+        let selected = self.selected;
+        let current = self.pane_for(selected);
+        if let Some(pane) = self.panes.get_mut(self.focused_pane) {
+            *pane = Pane {
+                search_query: self.search_query.clone(),
+                ..current
+            };
+        }
+        self.focused_pane = index;
+        let pane = self.panes[index].clone();
+        self.selected = pane.process;
+        if let Some(process) = self.processes.get_mut(pane.process) {
+            process.scroll = pane.scroll;
+            process.follow = pane.follow;
+        }
+        self.search_query = pane.search_query;
+        self.update_search_matches();
+    }
+
+    /// The pane arrangement to persist, with processes by name.
+    pub fn pane_layout(&self) -> PaneLayout {
+        let panes = self
+            .panes
+            .iter()
+            .enumerate()
+            .map(|(index, pane)| {
+                let id = if index == self.focused_pane {
+                    self.selected
+                } else {
+                    pane.process
+                };
+                self.processes[id].spec.name.clone()
+            })
+            .collect();
+        PaneLayout {
+            direction: self.split_direction,
+            panes,
+            focused: self.focused_pane,
+        }
+    }
+
+    /// Restores a saved arrangement; panes for processes that no longer exist are dropped.
+    pub fn apply_pane_layout(&mut self, layout: &PaneLayout) {
+        self.split_direction = layout.direction;
+        let ids: Vec<usize> = layout
+            .panes
+            .iter()
+            .filter_map(|name| self.process_id(name))
+            .take(MAX_PANES)
+            .collect();
+        if ids.len() < 2 {
+            return;
+        }
+        self.panes = ids.into_iter().map(|id| self.pane_for(id)).collect();
+        self.focused_pane = layout.focused.min(self.panes.len() - 1);
+        self.selected = self.panes[self.focused_pane].process;
+    }
+
+    fn pane_for(&self, id: usize) -> Pane {
+        let (scroll, follow) = self
+            .processes
+            .get(id)
+            .map(|process| (process.scroll, process.follow))
+            .unwrap_or((0, true));
+        Pane {
+            process: id,
+            scroll,
+            follow,
+            search_query: None,
+        }
+    }
+
+    fn pane_at(&self, row: u16, col: u16) -> Option<usize> {
+        if self.panes.len() != self.pane_viewports.len() {
+            return None;
+        }
+        self.pane_viewports.iter().position(|viewport| {
+            row >= viewport.y
+                && row < viewport.y + viewport.height
+                && col >= viewport.x
+                && col < viewport.x + viewport.width
+        })
+    }
+
     pub fn input_line(&self) -> &str {
         &self.input_buffer
     }
 
-    pub fn export_selected_logs(&mut self) -> Result<PathBuf> {
-        let Some(process) = self.selected_process() else {
+    /// Writes the logs of process `id` to `piperack-logs/<name>-<epoch>.log`.
+    pub fn export_logs(&mut self, id: usize) -> Result<PathBuf> {
+        let Some(process) = self.processes.get(id) else {
             anyhow::bail!("no process selected");
         };
-        let dir = PathBuf::from("piperack-logs");
-        fs::create_dir_all(&dir).context("failed to create piperack-logs directory")?;
-        let epoch = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let name = sanitize_name(&process.spec.name);
-        let path = dir.join(format!("{}-{}.log", name, epoch));
         let mut output = String::new();
         for line in process.logs.iter() {
             if line.stream == StreamKind::Stderr {
@@ -953,11 +1454,29 @@ impl App {
             output.push_str(&text);
             output.push('\n');
         }
-        fs::write(&path, output).with_context(|| format!("failed to write {}", path.display()))?;
+        let path = write_export(&sanitize_name(&process.spec.name), &output)?;
         self.set_status_message(format!("Exported logs to {}", path.display()));
         Ok(path)
     }
 
+    /// Writes the timeline to `piperack-logs/timeline-<epoch>.log`, one `[name] line` per entry.
+    pub fn export_timeline(&mut self) -> Result<PathBuf> {
+        let mut output = String::new();
+        for entry in self.timeline.iter() {
+            let name = self
+                .processes
+                .get(entry.process_id)
+                .map(|process| process.spec.name.as_str())
+                .unwrap_or("process");
+            output.push_str(&format!("[{}] ", name));
+            output.push_str(&sanitize_text(&entry.text, self.strip_ansi));
+            output.push('\n');
+        }
+        let path = write_export("timeline", &output)?;
+        self.set_status_message(format!("Exported timeline to {}", path.display()));
+        Ok(path)
+    }
+
     fn selected_following(&self) -> bool {
         self.selected_process().map(|p| p.follow).unwrap_or(true)
     }
@@ -1035,40 +1554,77 @@ impl App {
     }
 }
 
+fn write_export(stem: &str, output: &str) -> Result<PathBuf> {
+    let dir = PathBuf::from("piperack-logs");
+    fs::create_dir_all(&dir).context("failed to create piperack-logs directory")?;
+    let epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = dir.join(format!("{}-{}.log", stem, epoch));
+    fs::write(&path, output).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
 fn strip_carriage(text: &str) -> String {
     text.rsplit('\r').next().unwrap_or("").to_string()
 }
 
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let minutes = secs / 60;
+    let seconds = secs % 60;
+    format!("{:02}:{:02}", minutes, seconds)
+}
+
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn control_byte(c: char) -> Option<u8> {
+    if !c.is_ascii_alphabetic() {
+        return None;
+    }
+    let upper = c.to_ascii_uppercase() as u8;
+    Some(upper.saturating_sub(b'@'))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use crate::output::LogLine;
+    use crate::process::test_spec;
     use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
-    fn make_spec(name: &str) -> ProcessSpec {
-        ProcessSpec {
-            name: name.to_string(),
-            cmd: "echo".to_string(),
-            args: Vec::new(),
-            cwd: None,
-            color: None,
-            env: HashMap::new(),
-            restart_on_fail: false,
-            follow: true,
-            pre_cmd: None,
-            watch_paths: Vec::new(),
-            watch_ignore: Vec::new(),
-            watch_ignore_gitignore: false,
-            watch_debounce_ms: 200,
-            depends_on: Vec::new(),
-            ready_check: None,
-            tags: Vec::new(),
-        }
+    fn make_app() -> App {
+        App::new(vec![test_spec("api")], 100, false, true)
     }
 
-    fn make_app() -> App {
-        App::new(vec![make_spec("api")], 100, false, true)
+    #[test]
+    fn confirm_prompt_returns_action_on_yes() {
+        let mut app = make_app();
+        let action = AppAction::KillPortOwner { id: 0, pid: 42, port: 3000 };
+        assert!(app.request_confirm("kill?", action.clone()));
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        assert_eq!(app.confirm_prompt(), Some("kill?"));
+        assert_eq!(app.handle_key(KeyEvent::from(KeyCode::Char('x'))), AppAction::None);
+        assert_eq!(app.handle_key(KeyEvent::from(KeyCode::Char('y'))), action);
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        app.request_confirm("kill?", action);
+        assert_eq!(app.handle_key(KeyEvent::from(KeyCode::Esc)), AppAction::None);
+        assert!(app.confirm_prompt().is_none());
+
+        app.input_mode = InputMode::Search;
+        assert!(!app.request_confirm("kill?", AppAction::Quit));
     }
 
     #[test]
@@ -1153,31 +1709,127 @@ mod tests {
         assert!(app.selection_end.is_none());
         assert!(!app.selection_active);
     }
-}
 
-fn format_duration(duration: Duration) -> String {
-    let secs = duration.as_secs();
-    let minutes = secs / 60;
-    let seconds = secs % 60;
-    format!("{:02}:{:02}", minutes, seconds)
-}
+    #[test]
+    fn panes_keep_their_own_scroll_follow_and_search() {
+        let mut app = App::new(vec![test_spec("api"), test_spec("web")], 100, false, true);
+        app.processes[0].follow = false;
+        app.processes[0].scroll = 7;
+        app.search_query = Some("GET".to_string());
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('s')));
+        assert_eq!(app.panes.len(), 2);
+        assert_eq!((app.focused_pane, app.selected), (1, 1));
+        assert!(app.search_query.is_none());
+        app.search_query = Some("POST".to_string());
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('w')));
+        assert_eq!((app.focused_pane, app.selected), (0, 0));
+        assert_eq!(app.search_query.as_deref(), Some("GET"));
+        assert!(!app.processes[0].follow);
+        assert_eq!(app.processes[0].scroll, 7);
+        assert_eq!(app.panes[1].search_query.as_deref(), Some("POST"));
 
-fn sanitize_name(name: &str) -> String {
-    name.chars()
-        .map(|c| {
-            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
-                c
-            } else {
-                '_'
-            }
-        })
-        .collect()
-}
+        let layout = app.pane_layout();
+        assert_eq!(layout.panes, vec!["api".to_string(), "web".to_string()]);
+        assert_eq!(layout.focused, 0);
 
-fn control_byte(c: char) -> Option<u8> {
-    if !c.is_ascii_alphabetic() {
-        return None;
+        app.handle_key(KeyEvent::from(KeyCode::Char('x')));
+        assert!(app.panes.is_empty());
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.search_query.as_deref(), Some("POST"));
+    }
+
+    #[test]
+    fn apply_pane_layout_skips_unknown_processes() {
+        let mut app = App::new(vec![test_spec("api"), test_spec("web")], 100, false, true);
+        app.apply_pane_layout(&PaneLayout {
+            direction: SplitDirection::Horizontal,
+            panes: vec!["web".to_string(), "gone".to_string(), "api".to_string()],
+            focused: 1,
+        });
+        assert_eq!(app.split_direction, SplitDirection::Horizontal);
+        assert_eq!(app.panes.iter().map(|p| p.process).collect::<Vec<_>>(), vec![1, 0]);
+        assert_eq!((app.focused_pane, app.selected), (1, 0));
+
+        let mut single = make_app();
+        single.apply_pane_layout(&PaneLayout {
+            direction: SplitDirection::Vertical,
+            panes: vec!["api".to_string(), "gone".to_string()],
+            focused: 0,
+        });
+        assert!(single.panes.is_empty());
+    }
+
+    #[test]
+    fn palette_runs_commands_and_lists_recent_first() {
+        let mut app = App::new(vec![test_spec("api"), test_spec("web")], 100, false, true);
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        assert_eq!(app.handle_key(ctrl_p), AppAction::None);
+        assert_eq!(app.input_mode, InputMode::Palette);
+        for c in "hup web".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(
+            app.handle_key(KeyEvent::from(KeyCode::Enter)),
+            AppAction::Signal(1, UserSignal::Hup)
+        );
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char(':')));
+        assert_eq!(app.palette_matches()[0].label, "Send SIGHUP to web");
+        for c in "json".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(app.palette_matches()[0].shortcut, "j");
+        assert_eq!(app.handle_key(KeyEvent::from(KeyCode::Enter)), AppAction::None);
+        assert!(app.json_formatting);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char(':')));
+        for c in "kill web".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.input.is_empty());
+    }
+
+    #[test]
+    fn goto_and_digits_jump_to_processes() {
+        let mut web = test_spec("web");
+        web.tags = vec!["frontend".to_string()];
+        let mut app = App::new(vec![test_spec("api"), test_spec("db"), web], 100, false, true);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('3')));
+        assert_eq!(app.selected, 2);
+        app.handle_key(KeyEvent::from(KeyCode::Char('9')));
+        assert_eq!(app.selected, 2);
+        app.handle_key(KeyEvent::from(KeyCode::Char('1')));
+        assert_eq!(app.selected, 0);
+
+        // Once drawn, digits count the rows on screen, not the whole list.
+        app.visible_processes = vec![1, 2];
+        app.handle_key(KeyEvent::from(KeyCode::Char('1')));
+        assert_eq!(app.selected, 1);
+        app.handle_key(KeyEvent::from(KeyCode::Char('3')));
+        assert_eq!(app.selected, 1);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('p')));
+        assert_eq!(app.input_mode, InputMode::GoTo);
+        assert_eq!(app.goto_matches(), vec![0, 1, 2]);
+        for c in "front".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(app.goto_matches(), vec![2]);
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!((app.selected, app.input_mode), (2, InputMode::Normal));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('p')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('d')));
+        app.handle_key(KeyEvent::from(KeyCode::Char('b')));
+        app.handle_key(KeyEvent::from(KeyCode::Down));
+        assert_eq!(app.goto_index, 0);
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.selected, 1);
     }
-    let upper = c.to_ascii_uppercase() as u8;
-    Some(upper.saturating_sub(b'@'))
 }
@@ -0,0 +1,194 @@
+//! CI log folding and annotations.
+//!
+//! `--output ci` prints each process's output as one collapsible block, using GitHub
+//! Actions workflow commands (`::group::`) or GitLab CI section markers, depending on where
+//! Piperack runs. On GitHub, stderr lines that look like errors also become `::error`
+//! annotations (`::warning` for located warnings), with the file and line when the line
+//! starts with a `path:line` location.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// The CI system the output is formatted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiProvider {
+    GitHub,
+    GitLab,
+}
+
+impl CiProvider {
+    /// Detects the CI system from its environment variables.
+    pub fn detect() -> Option<Self> {
+        Self::from_env(|key| std::env::var_os(key).is_some())
+    }
+
+    fn from_env(is_set: impl Fn(&str) -> bool) -> Option<Self> {
+        if is_set("GITHUB_ACTIONS") {
+            Some(CiProvider::GitHub)
+        } else if is_set("GITLAB_CI") {
+            Some(CiProvider::GitLab)
+        } else {
+            None
+        }
+    }
+
+    /// The line that opens a folded block for process `name`.
+    pub fn group_start(self, name: &str) -> String {
+        match self {
+            CiProvider::GitHub => format!("::group::{}", name),
+            CiProvider::GitLab => format!(
+                "\x1b[0Ksection_start:{}:{}[collapsed=true]\r\x1b[0K{}",
+                unix_time(),
+                section_name(name),
+                name
+            ),
+        }
+    }
+
+    /// The line that closes the block opened by `group_start`.
+    pub fn group_end(self, name: &str) -> String {
+        match self {
+            CiProvider::GitHub => "::endgroup::".to_string(),
+            CiProvider::GitLab => format!(
+                "\x1b[0Ksection_end:{}:{}\r\x1b[0K",
+                unix_time(),
+                section_name(name)
+            ),
+        }
+    }
+
+    /// An annotation for a stderr line, if the line looks like an error or a located warning.
+    ///
+    /// GitLab has no equivalent of workflow annotations, so this is GitHub only.
+    pub fn annotation(self, line: &str) -> Option<String> {
+        if self != CiProvider::GitHub {
+            return None;
+        }
+        if let Some(caps) = location_regex().captures(line) {
+            let number = caps.name("line").or_else(|| caps.name("pline"));
+            let col = caps.name("col").or_else(|| caps.name("pcol"));
+            let mut properties = format!("file={}", escape_property(&caps["file"]));
+            if let Some(number) = number {
+                properties.push_str(&format!(",line={}", number.as_str()));
+            }
+            if let Some(col) = col {
+                properties.push_str(&format!(",col={}", col.as_str()));
+            }
+            // Located lines are errors unless they only say "warning" (e.g. lint output).
+            let level = if !error_regex().is_match(line) && warning_regex().is_match(line) {
+                "warning"
+            } else {
+                "error"
+            };
+            return Some(format!("::{} {}::{}", level, properties, escape_data(line)));
+        }
+        if error_regex().is_match(line) {
+            return Some(format!("::error::{}", escape_data(line)));
+        }
+        None
+    }
+}
+
+// `src/app.ts:12:5: msg`, `main.c:3: error: msg` or `src/app.ts(12,5): error TS2322: msg`.
+fn location_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"^\s*(?P<file>[\w./\\@-]+\.\w+)(?::(?P<line>\d+)(?::(?P<col>\d+))?:|\((?P<pline>\d+)(?:,(?P<pcol>\d+))?\):)")
+            .expect("valid location regex")
+    })
+}
+
+fn error_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"(?i)\berror\b").expect("valid error regex"))
+}
+
+fn warning_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"(?i)\bwarning\b").expect("valid warning regex"))
+}
+
+// GitLab section names may only contain letters, digits, `_`, `.` and `-`.
+fn section_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_provider_from_env() {
+        assert_eq!(
+            CiProvider::from_env(|key| key == "GITHUB_ACTIONS"),
+            Some(CiProvider::GitHub)
+        );
+        assert_eq!(
+            CiProvider::from_env(|key| key == "GITLAB_CI"),
+            Some(CiProvider::GitLab)
+        );
+        assert_eq!(CiProvider::from_env(|_| false), None);
+    }
+
+    #[test]
+    fn annotations_extract_file_and_line() {
+        let github = CiProvider::GitHub;
+        assert_eq!(
+            github.annotation("src/app.ts:12:5: Unexpected any").as_deref(),
+            Some("::error file=src/app.ts,line=12,col=5::src/app.ts:12:5: Unexpected any")
+        );
+        assert_eq!(
+            github.annotation("main.c:3: error: expected ';'").as_deref(),
+            Some("::error file=main.c,line=3::main.c:3: error: expected ';'")
+        );
+        assert_eq!(
+            github.annotation("error[E0308]: mismatched types").as_deref(),
+            Some("::error::error[E0308]: mismatched types")
+        );
+        assert_eq!(
+            github.annotation("src/app.ts(12,5): error TS2322: bad").as_deref(),
+            Some("::error file=src/app.ts,line=12,col=5::src/app.ts(12,5): error TS2322: bad")
+        );
+        assert_eq!(
+            github.annotation("lib.rs:4:1: warning: unused").as_deref(),
+            Some("::warning file=lib.rs,line=4,col=1::lib.rs:4:1: warning: unused")
+        );
+        assert_eq!(github.annotation("warning: unused variable"), None);
+        assert_eq!(CiProvider::GitLab.annotation("error: boom"), None);
+    }
+
+    #[test]
+    fn gitlab_sections_use_safe_names() {
+        let start = CiProvider::GitLab.group_start("web api");
+        assert!(start.contains(":web_api[collapsed=true]\r"));
+        assert!(start.ends_with("web api"));
+        assert!(CiProvider::GitLab.group_end("web api").contains(":web_api\r"));
+        assert_eq!(CiProvider::GitHub.group_start("web"), "::group::web");
+    }
+}
@@ -3,12 +3,16 @@
 //! This module defines the structure of the `piperack.toml` configuration file
 //! and provides functionality to load and parse it.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
 
+use crate::keys::{KeyAction, KeyBindingConfig};
+use crate::notify::{NotifyEvent, NotifyMethod};
+use crate::theme::ThemeConfig;
+
 /// Top-level configuration structure corresponding to `piperack.toml`.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
@@ -46,11 +50,33 @@ pub struct Config {
     pub handle_input: Option<bool>,
     /// Template for log file paths.
     pub log_file: Option<String>,
+    /// Port for the local HTTP API (0 picks a free port); disabled when unset.
+    pub http_port: Option<u16>,
+    /// Port for the Prometheus metrics listener (0 picks a free port); disabled when unset.
+    pub metrics_port: Option<u16>,
+    /// Terminal notifications from the TUI (`[notify]`); disabled when unset.
+    pub notify: Option<NotifyConfig>,
+    /// TUI key bindings (`[keys]`), mapping actions to key chords.
+    pub keys: Option<BTreeMap<KeyAction, KeyBindingConfig>>,
+    /// TUI colours (`[theme]`): a built-in theme and style overrides.
+    pub theme: Option<ThemeConfig>,
     /// List of processes to run.
     #[serde(rename = "process")]
     pub processes: Vec<ProcessConfig>,
 }
 
+/// Terminal notification settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// Events to notify about (default: `["failure"]`).
+    pub events: Option<Vec<NotifyEvent>>,
+    /// How to notify (default: `["bell"]`).
+    pub methods: Option<Vec<NotifyMethod>>,
+    /// Minimum time between two notifications of one event for the same process (ms).
+    pub min_interval_ms: Option<u64>,
+}
+
 /// Configuration for a single process.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ProcessConfig {
@@ -66,6 +92,20 @@ pub struct ProcessConfig {
     pub env: Option<HashMap<String, String>>,
     /// Whether to restart the process if it fails.
     pub restart_on_fail: Option<bool>,
+    /// What to do when the process fails on its own (default: "restart" with
+    /// `restart_on_fail`, otherwise "ignore").
+    pub on_exit: Option<OnExit>,
+    /// Exit codes that count as success (default: `[0]`).
+    pub success_codes: Option<Vec<i32>>,
+    /// Whether exiting after Piperack sent its stop signal counts as success (default: false).
+    pub success_on_stop: Option<bool>,
+    /// Maximum run time in milliseconds; the process is stopped and counted as failed after it.
+    pub timeout_ms: Option<u64>,
+    /// Commands run on lifecycle events (`[process.hooks]`).
+    pub hooks: Option<HooksConfig>,
+    /// Actions run when an output line matches a pattern (`[[process.trigger]]`).
+    #[serde(rename = "trigger")]
+    pub triggers: Option<Vec<TriggerConfig>>,
     /// Whether to automatically follow the logs of this process (default: true).
     pub follow: Option<bool>,
     /// Command to run before starting the main process.
@@ -81,14 +121,187 @@ pub struct ProcessConfig {
     /// List of process names this process depends on.
     pub depends_on: Option<Vec<String>>,
     /// Readiness check configuration.
-    pub ready_check: Option<ReadinessCheck>,
+    pub ready_check: Option<ReadyCheckConfig>,
+    /// TCP ports the process listens on (a `tcp` readiness port is included automatically).
+    pub ports: Option<PortsConfig>,
     /// Tags for grouping processes.
     pub tags: Option<Vec<String>>,
 }
 
-/// Configuration for process readiness checks.
-#[derive(Debug, Clone, Deserialize)]
+/// Ports declared by a process.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum PortsConfig {
+    /// Fixed port numbers (e.g. `[3000, 9229]`).
+    List(Vec<u16>),
+    /// Named ports exported as environment variables (e.g. `{ HTTP = "auto" }`).
+    Named(BTreeMap<String, PortSetting>),
+}
+
+/// Value of a named port.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum PortSetting {
+    /// A fixed port number.
+    Fixed(u16),
+    /// `"auto"` to allocate a free port at startup.
+    Auto(String),
+}
+
+/// A port given either as a number or as the name of a named port.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum PortRef {
+    Number(u16),
+    Name(String),
+}
+
+/// Action taken when a process fails (non-zero exit, signal, or failure to start) without
+/// Piperack having asked it to stop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnExit {
+    /// Leave everything else running.
+    #[default]
+    Ignore,
+    /// Restart the process (same as `restart_on_fail = true`).
+    Restart,
+    /// Stop every process and end the run with an error.
+    StopAll,
+    /// Stop the processes that depend on it, directly or transitively.
+    StopDependents,
+}
+
+impl OnExit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OnExit::Ignore => "ignore",
+            OnExit::Restart => "restart",
+            OnExit::StopAll => "stop_all",
+            OnExit::StopDependents => "stop_dependents",
+        }
+    }
+}
+
+/// Lifecycle event that can run a hook command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    Start,
+    Ready,
+    Exit,
+    Restart,
+}
+
+impl Hook {
+    pub fn label(self) -> &'static str {
+        match self {
+            Hook::Start => "start hook",
+            Hook::Ready => "ready hook",
+            Hook::Exit => "exit hook",
+            Hook::Restart => "restart hook",
+        }
+    }
+}
+
+/// Commands run alongside a process on lifecycle events. They run in the process's `cwd`
+/// and environment, and their output goes to the process log.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Run after the process is spawned.
+    pub start: Option<String>,
+    /// Run when the process becomes ready.
+    pub ready: Option<String>,
+    /// Run after the process exits, for whatever reason.
+    pub exit: Option<String>,
+    /// Run before the process is restarted.
+    pub restart: Option<String>,
+}
+
+impl HooksConfig {
+    pub fn command(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::Start => self.start.as_deref(),
+            Hook::Ready => self.ready.as_deref(),
+            Hook::Exit => self.exit.as_deref(),
+            Hook::Restart => self.restart.as_deref(),
+        }
+        .filter(|cmd| !cmd.trim().is_empty())
+    }
+}
+
+/// Output trigger as written in the config file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TriggerConfig {
+    /// Regex matched against each stdout and stderr line.
+    pub pattern: String,
+    /// What to do when a line matches.
+    pub action: TriggerKind,
+    /// Process name or tag to restart (`restart` only; default: this process).
+    pub target: Option<String>,
+    /// Command to run (`run` only).
+    pub cmd: Option<String>,
+    /// Status warning to show (`warn` only; default: the matched line).
+    pub message: Option<String>,
+}
+
+/// Action named by a trigger's `action` key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "snake_case")]
+pub enum TriggerKind {
+    Restart,
+    Run,
+    Fail,
+    Warn,
+}
+
+impl TriggerKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TriggerKind::Restart => "restart",
+            TriggerKind::Run => "run",
+            TriggerKind::Fail => "fail",
+            TriggerKind::Warn => "warn",
+        }
+    }
+}
+
+/// Action run when a trigger matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerAction {
+    /// Restart a process name or tag, or the process itself when `None`.
+    Restart(Option<String>),
+    /// Run a command next to the process.
+    Run(String),
+    /// Stop the process and count the run as failed.
+    Fail,
+    /// Show a status warning, or the matched line when `None`.
+    Warn(Option<String>),
+}
+
+/// A resolved output trigger.
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    /// Regex matched against each output line.
+    pub pattern: String,
+    pub action: TriggerAction,
+}
+
+/// Readiness check as written in the config file, before port names are resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadyCheckConfig {
+    /// Wait for a TCP port (number or named port) to accept connections.
+    Tcp(PortRef),
+    /// Wait for a specific duration (milliseconds).
+    Delay(u64),
+    /// Wait for a log line matching a regex.
+    Log(String),
+}
+
+/// Configuration for process readiness checks.
+#[derive(Debug, Clone)]
 pub enum ReadinessCheck {
     /// Wait for a TCP port to accept connections.
     Tcp(u16),
@@ -142,6 +355,20 @@ watch = ["src", "Cargo.toml"]
 watch_ignore = ["target", "**/*.log"]
 watch_ignore_gitignore = true
 watch_debounce_ms = 150
+ports = [3000, 9229]
+
+[process.hooks]
+ready = "open http://localhost:3000"
+exit = "rm -rf tmp/api"
+
+[[process.trigger]]
+pattern = "EADDRINUSE"
+action = "restart"
+
+[[process.trigger]]
+pattern = "too many connections"
+action = "run"
+cmd = "notify-send db"
 
 [[process]]
 name = "web"
@@ -168,5 +395,38 @@ cmd = "pnpm dev"
         assert_eq!(config.processes.len(), 2);
         assert_eq!(config.processes[0].restart_on_fail, Some(true));
         assert_eq!(config.processes[0].follow, Some(false));
+        assert_eq!(config.processes[0].ports, Some(PortsConfig::List(vec![3000, 9229])));
+        let hooks = config.processes[0].hooks.clone().unwrap();
+        assert_eq!(hooks.command(Hook::Ready), Some("open http://localhost:3000"));
+        assert_eq!(hooks.command(Hook::Exit), Some("rm -rf tmp/api"));
+        assert_eq!(hooks.command(Hook::Start), None);
+        assert!(config.processes[1].hooks.is_none());
+        let triggers = config.processes[0].triggers.clone().unwrap();
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[0].action, TriggerKind::Restart);
+        assert_eq!(triggers[1].action, TriggerKind::Run);
+        assert_eq!(triggers[1].cmd.as_deref(), Some("notify-send db"));
+    }
+
+    #[test]
+    fn parses_named_ports_and_tcp_port_names() {
+        let raw = r#"
+[[process]]
+name = "api"
+cmd = "cargo run"
+ports = { HTTP = "auto", DEBUG = 9229 }
+ready_check = { tcp = "HTTP" }
+"#;
+        let config: Config = toml::from_str(raw).unwrap();
+        let process = &config.processes[0];
+        let Some(PortsConfig::Named(ports)) = &process.ports else {
+            panic!("expected named ports");
+        };
+        assert_eq!(ports.get("HTTP"), Some(&PortSetting::Auto("auto".to_string())));
+        assert_eq!(ports.get("DEBUG"), Some(&PortSetting::Fixed(9229)));
+        assert!(matches!(
+            &process.ready_check,
+            Some(ReadyCheckConfig::Tcp(PortRef::Name(name))) if name == "HTTP"
+        ));
     }
 }
@@ -0,0 +1,458 @@
+//! Control socket for scripting a running session.
+//!
+//! A running Piperack instance listens on a Unix domain socket and accepts one JSON
+//! request per line (`{"cmd":"restart","name":"api"}`). Each request is forwarded to the
+//! main loop as `Event::Control` together with a reply channel; every JSON line sent on
+//! that channel is written back to the client. The `piperack ctl` subcommand is the
+//! bundled client.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use crate::events::{Event, UserSignal};
+use crate::output::{LogLine, StreamKind};
+use crate::process::{ProcessState, ProcessStatus};
+
+/// Environment variable overriding the socket path.
+pub const SOCKET_ENV: &str = "PIPERACK_SOCKET";
+
+/// A request sent by a control client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum ControlRequest {
+    Restart { name: String },
+    Kill { name: String },
+    /// Sends a signal without stopping the process.
+    Signal { name: String, signal: UserSignal },
+    RestartGroup { group: String },
+    Input { name: String, text: String },
+    /// Raw bytes typed into an attached client.
+    InputBytes { name: String, bytes: Vec<u8> },
+    Export { name: String },
+    Status,
+    Logs {
+        name: String,
+        #[serde(default)]
+        follow: bool,
+        #[serde(default)]
+        lines: Option<usize>,
+    },
+    /// Streams the session state to an attached TUI client (see `session`).
+    Attach,
+    /// Stops every process and ends the session.
+    Shutdown,
+    /// Prometheus metrics for every process (see `metrics`).
+    Metrics,
+}
+
+/// A client following the logs of one process.
+#[derive(Debug)]
+pub struct LogFollower {
+    pub id: usize,
+    pub reply: mpsc::Sender<String>,
+}
+
+/// Returns the socket path for a session started in `dir`.
+///
+/// `PIPERACK_SOCKET` takes precedence; otherwise the path is derived from the directory so
+/// that `piperack ctl` run from the same project finds the session. Without
+/// `XDG_RUNTIME_DIR` it lives in a private per-user directory (see `private_runtime_dir`).
+pub fn default_socket_path(dir: &Path) -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(SOCKET_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    let base = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(base) => PathBuf::from(base),
+        None => private_runtime_dir(&std::env::temp_dir())?,
+    };
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    Ok(base.join(format!(
+        "piperack-{:016x}.sock",
+        fnv1a(dir.to_string_lossy().as_bytes())
+    )))
+}
+
+/// Returns `<tmp>/piperack-<uid>`, creating it with mode 0700.
+///
+/// The shared temp dir lets other users pre-create or squat predictable names, so an
+/// existing entry is only used when it is a real directory owned by us that nobody else
+/// can enter.
+#[cfg(unix)]
+fn private_runtime_dir(tmp: &Path) -> Result<PathBuf> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    let uid = unsafe { libc::geteuid() };
+    let dir = tmp.join(format!("piperack-{}", uid));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to create {}", dir.display()))
+        }
+    }
+    let meta = std::fs::symlink_metadata(&dir)
+        .with_context(|| format!("failed to inspect {}", dir.display()))?;
+    if !meta.file_type().is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        bail!(
+            "{} is not a private directory owned by uid {}; remove it or set XDG_RUNTIME_DIR",
+            dir.display(),
+            uid
+        );
+    }
+    Ok(dir)
+}
+
+#[cfg(not(unix))]
+fn private_runtime_dir(tmp: &Path) -> Result<PathBuf> {
+    Ok(tmp.to_path_buf())
+}
+
+// Stable across builds, unlike `DefaultHasher`, so old and new binaries agree on the path.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// A bound control socket; the socket file is removed on drop.
+#[derive(Debug)]
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Binds the control socket and serves clients in the background.
+///
+/// Fails if another live session already listens on `path`; a stale socket file is replaced.
+#[cfg(unix)]
+pub fn spawn_server(path: PathBuf, tx: mpsc::Sender<Event>) -> Result<ControlSocket> {
+    use tokio::net::UnixListener;
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            bail!("another piperack session is listening on {}", path.display());
+        }
+        std::fs::remove_file(&path)
+            .with_context(|| format!("failed to remove stale socket {}", path.display()))?;
+    }
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("failed to bind control socket {}", path.display()))?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_client(stream, tx.clone()));
+        }
+    });
+    Ok(ControlSocket { path })
+}
+
+#[cfg(not(unix))]
+pub fn spawn_server(_path: PathBuf, _tx: mpsc::Sender<Event>) -> Result<ControlSocket> {
+    bail!("the control socket is only supported on Unix")
+}
+
+#[cfg(unix)]
+async fn serve_client(stream: tokio::net::UnixStream, tx: mpsc::Sender<Event>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                let reply = error_reply(format!("invalid request: {}", err));
+                if write.write_all(format!("{}\n", reply).as_bytes()).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+        let (reply_tx, mut reply_rx) = mpsc::channel(256);
+        if tx
+            .send(Event::Control {
+                request,
+                reply: reply_tx,
+            })
+            .await
+            .is_err()
+        {
+            return;
+        }
+        // The main loop drops the sender once the request is complete (or, when following
+        // logs, keeps it until the client goes away).
+        while let Some(reply) = reply_rx.recv().await {
+            if write.write_all(format!("{}\n", reply).as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Sends `request` to the session listening on `path` and calls `on_reply` for every
+/// reply line until the session closes the request.
+#[cfg(unix)]
+pub async fn send_request(
+    path: &Path,
+    request: &ControlRequest,
+    mut on_reply: impl FnMut(Value) -> bool,
+) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    let stream = UnixStream::connect(path).await.with_context(|| {
+        format!(
+            "no running piperack session found at {} (start piperack in this directory or pass --socket)",
+            path.display()
+        )
+    })?;
+    let (read, mut write) = stream.into_split();
+    let payload = serde_json::to_string(request)?;
+    write.write_all(format!("{}\n", payload).as_bytes()).await?;
+    let mut lines = BufReader::new(read).lines();
+    while let Some(line) = lines.next_line().await? {
+        let reply: Value = serde_json::from_str(&line)
+            .with_context(|| format!("invalid reply from session: {}", line))?;
+        if !on_reply(reply) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn send_request(
+    _path: &Path,
+    _request: &ControlRequest,
+    _on_reply: impl FnMut(Value) -> bool,
+) -> Result<()> {
+    bail!("the control socket is only supported on Unix")
+}
+
+/// `{"ok":true}` plus any extra fields from `extra` (which must be an object).
+pub fn ok_reply(extra: Value) -> String {
+    let mut reply = json!({ "ok": true });
+    if let (Some(reply), Value::Object(extra)) = (reply.as_object_mut(), extra) {
+        reply.extend(extra);
+    }
+    reply.to_string()
+}
+
+pub fn error_reply(message: impl Into<String>) -> String {
+    json!({ "ok": false, "error": message.into() }).to_string()
+}
+
+/// One streamed log line.
+pub fn log_reply(line: &LogLine) -> String {
+    log_json(line).to_string()
+}
+
+pub fn log_json(line: &LogLine) -> Value {
+    json!({ "line": line.text, "stream": stream_label(line.stream) })
+}
+
+fn stream_label(stream: StreamKind) -> &'static str {
+    match stream {
+        StreamKind::Stdout => "stdout",
+        StreamKind::Stderr => "stderr",
+    }
+}
+
+/// Status of every process, as returned by the `status` request.
+pub fn status_json(processes: &[ProcessState]) -> Value {
+    let processes: Vec<Value> = processes.iter().map(process_json).collect();
+    json!({ "processes": processes })
+}
+
+/// Status of a single process.
+pub fn process_json(process: &ProcessState) -> Value {
+    let (status, error) = match &process.status {
+        ProcessStatus::Idle => ("idle", None),
+        ProcessStatus::Starting => ("starting", None),
+        ProcessStatus::Running => ("running", None),
+        ProcessStatus::Exited { .. } => ("exited", None),
+        ProcessStatus::Failed { error } => ("failed", Some(error.clone())),
+    };
+    json!({
+        "name": process.spec.name,
+        "status": status,
+        "pid": process.pid,
+        "exit_code": process.exit_code,
+        "stopped": process.stopped,
+        "succeeded": process.succeeded,
+        "ready": process.ready,
+        "uptime_secs": process.started_at.map(|at| at.elapsed().as_secs()),
+        "tags": process.spec.tags,
+        "error": error,
+    })
+}
+
+/// Live lines queued for a follower while its backlog is still being written.
+const FOLLOW_QUEUE: usize = 256;
+
+/// Sends the buffered `backlog` of a `logs` request from a background task, so a client
+/// that stops reading never blocks the main loop.
+///
+/// With `follow`, returns the sender to register as a `LogFollower`: live lines queue on it
+/// until the backlog has been written, so the client still sees them in order.
+pub fn spawn_log_reply(
+    reply: mpsc::Sender<String>,
+    backlog: Vec<String>,
+    follow: bool,
+) -> Option<mpsc::Sender<String>> {
+    let (live_tx, mut live_rx) = mpsc::channel(FOLLOW_QUEUE);
+    tokio::spawn(async move {
+        for line in backlog {
+            if reply.send(line).await.is_err() {
+                return;
+            }
+        }
+        if !follow {
+            let _ = reply.send(ok_reply(Value::Null)).await;
+            return;
+        }
+        while let Some(line) = live_rx.recv().await {
+            if reply.send(line).await.is_err() {
+                return;
+            }
+        }
+    });
+    follow.then_some(live_tx)
+}
+
+/// Forwards a new output line to the clients following process `id`.
+///
+/// Followers whose client disconnected are dropped; a full queue skips the line.
+pub fn forward_line(followers: &mut Vec<LogFollower>, id: usize, line: &LogLine) {
+    if followers.is_empty() {
+        return;
+    }
+    let reply = log_reply(line);
+    followers.retain(|follower| {
+        if follower.id != id {
+            return !follower.reply.is_closed();
+        }
+        !matches!(
+            follower.reply.try_send(reply.clone()),
+            Err(mpsc::error::TrySendError::Closed(_))
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_use_cmd_tag() {
+        let request: ControlRequest =
+            serde_json::from_str(r#"{"cmd":"restart_group","group":"backend"}"#).unwrap();
+        assert_eq!(
+            request,
+            ControlRequest::RestartGroup {
+                group: "backend".to_string()
+            }
+        );
+        let request: ControlRequest = serde_json::from_str(r#"{"cmd":"logs","name":"web"}"#).unwrap();
+        assert_eq!(
+            request,
+            ControlRequest::Logs {
+                name: "web".to_string(),
+                follow: false,
+                lines: None
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&ControlRequest::Status).unwrap(),
+            r#"{"cmd":"status"}"#
+        );
+    }
+
+    #[test]
+    fn replies_merge_extra_fields() {
+        assert_eq!(ok_reply(Value::Null), r#"{"ok":true}"#);
+        let reply: Value = serde_json::from_str(&ok_reply(json!({ "path": "a.log" }))).unwrap();
+        assert_eq!(reply["path"], "a.log");
+        let reply: Value = serde_json::from_str(&error_reply("nope")).unwrap();
+        assert_eq!(reply["ok"], false);
+    }
+
+    #[test]
+    fn forward_line_drops_closed_followers() {
+        let (tx_a, mut rx_a) = mpsc::channel(4);
+        let (tx_b, rx_b) = mpsc::channel(4);
+        drop(rx_b);
+        let mut followers = vec![
+            LogFollower { id: 0, reply: tx_a },
+            LogFollower { id: 0, reply: tx_b },
+        ];
+        let line = LogLine {
+            text: "hello".to_string(),
+            stream: StreamKind::Stderr,
+        };
+        forward_line(&mut followers, 0, &line);
+        assert_eq!(followers.len(), 1);
+        let reply: Value = serde_json::from_str(&rx_a.try_recv().unwrap()).unwrap();
+        assert_eq!(reply["line"], "hello");
+        assert_eq!(reply["stream"], "stderr");
+    }
+
+    #[tokio::test]
+    async fn log_reply_does_not_wait_for_the_client() {
+        let (reply, mut replies) = mpsc::channel(1);
+        let backlog: Vec<String> = (0..10).map(|n| n.to_string()).collect();
+        let live = spawn_log_reply(reply, backlog, true).unwrap();
+        live.try_send("live".to_string()).unwrap();
+        for expected in (0..10).map(|n| n.to_string()).chain(["live".to_string()]) {
+            assert_eq!(replies.recv().await.unwrap(), expected);
+        }
+
+        let (reply, mut replies) = mpsc::channel(4);
+        assert!(spawn_log_reply(reply, vec!["a".to_string()], false).is_none());
+        assert_eq!(replies.recv().await.unwrap(), "a");
+        assert_eq!(replies.recv().await.unwrap(), ok_reply(Value::Null));
+        assert!(replies.recv().await.is_none());
+    }
+
+    #[test]
+    fn socket_path_is_stable_per_directory() {
+        if std::env::var_os(SOCKET_ENV).is_some() {
+            return;
+        }
+        let a = default_socket_path(Path::new("/nonexistent/a")).unwrap();
+        assert_eq!(a, default_socket_path(Path::new("/nonexistent/a")).unwrap());
+        assert_ne!(a, default_socket_path(Path::new("/nonexistent/b")).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_runtime_dir_rejects_foreign_or_open_dirs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = std::env::temp_dir().join(format!("piperack-rundir-{}", std::process::id()));
+        std::fs::create_dir_all(&tmp).unwrap();
+        let dir = private_runtime_dir(&tmp).unwrap();
+        let meta = std::fs::metadata(&dir).unwrap();
+        assert_eq!(meta.permissions().mode() & 0o777, 0o700);
+        assert_eq!(private_runtime_dir(&tmp).unwrap(), dir);
+
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(private_runtime_dir(&tmp).is_err());
+        std::fs::remove_dir(&dir).unwrap();
+
+        std::os::unix::fs::symlink(&tmp, &dir).unwrap();
+        assert!(private_runtime_dir(&tmp).is_err());
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
@@ -5,8 +5,13 @@
 //! user input, and system signals.
 
 use crossterm::event::{KeyEvent, MouseEvent};
+use serde::{Deserialize, Serialize};
 
+use tokio::sync::mpsc;
+
+use crate::control::ControlRequest;
 use crate::output::StreamKind;
+use crate::ports::PortConflict;
 
 /// Signals used for graceful process shutdown.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,6 +29,66 @@ impl ProcessSignal {
     }
 }
 
+/// Signals a user can send to a process on demand, outside of the shutdown sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UserSignal {
+    #[serde(rename = "SIGHUP")]
+    Hup,
+    #[serde(rename = "SIGINT")]
+    Int,
+    #[serde(rename = "SIGTERM")]
+    Term,
+    #[serde(rename = "SIGUSR1")]
+    Usr1,
+    #[serde(rename = "SIGUSR2")]
+    Usr2,
+}
+
+impl UserSignal {
+    pub const ALL: [UserSignal; 5] = [
+        UserSignal::Hup,
+        UserSignal::Int,
+        UserSignal::Term,
+        UserSignal::Usr1,
+        UserSignal::Usr2,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UserSignal::Hup => "SIGHUP",
+            UserSignal::Int => "SIGINT",
+            UserSignal::Term => "SIGTERM",
+            UserSignal::Usr1 => "SIGUSR1",
+            UserSignal::Usr2 => "SIGUSR2",
+        }
+    }
+}
+
+/// Name of a signal number as reported by a process exit (`SIGTERM`), or `signal N`.
+pub fn signal_name(signal: i32) -> String {
+    #[cfg(unix)]
+    {
+        let name = match signal {
+            libc::SIGHUP => Some("SIGHUP"),
+            libc::SIGINT => Some("SIGINT"),
+            libc::SIGQUIT => Some("SIGQUIT"),
+            libc::SIGABRT => Some("SIGABRT"),
+            libc::SIGKILL => Some("SIGKILL"),
+            libc::SIGSEGV => Some("SIGSEGV"),
+            libc::SIGPIPE => Some("SIGPIPE"),
+            libc::SIGALRM => Some("SIGALRM"),
+            libc::SIGTERM => Some("SIGTERM"),
+            libc::SIGUSR1 => Some("SIGUSR1"),
+            libc::SIGUSR2 => Some("SIGUSR2"),
+            _ => None,
+        };
+        if let Some(name) = name {
+            return name.to_string();
+        }
+    }
+    format!("signal {}", signal)
+}
+
 /// Represents an event in the application's main event loop.
 #[derive(Debug, Clone)]
 pub enum Event {
@@ -41,10 +106,22 @@ pub enum Event {
         line: String,
         stream: StreamKind,
     },
-    /// A process exited with an optional exit code (None usually implies signal termination).
-    ProcessExited { id: usize, code: Option<i32> },
+    /// A process exited with an optional exit code, or `signal` when a signal killed it.
+    /// `stopped` is set when Piperack asked it to stop (kill, restart or shutdown).
+    ProcessExited {
+        id: usize,
+        code: Option<i32>,
+        signal: Option<i32>,
+        stopped: bool,
+    },
+    /// An output line of a process matched one of its triggers (index into `spec.triggers`).
+    Triggered { id: usize, trigger: usize, line: String },
+    /// A process ran longer than its `timeout_ms` and is being stopped.
+    ProcessTimedOut { id: usize },
     /// A process failed to start or encountered an error.
     ProcessFailed { id: usize, error: String },
+    /// A process could not start because one of its ports is taken.
+    PortConflict { id: usize, conflict: PortConflict },
     /// A signal was sent to a process.
     ProcessSignal { id: usize, signal: ProcessSignal },
     /// A request to restart a process.
@@ -61,15 +138,28 @@ pub enum Event {
     Mouse(MouseEvent),
     /// The terminal window was resized.
     Resize { width: u16, height: u16 },
+    /// A request from a control socket client; JSON replies go to `reply`.
+    Control {
+        request: ControlRequest,
+        reply: mpsc::Sender<String>,
+    },
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ProcessSignal;
+    use super::{signal_name, ProcessSignal};
 
     #[test]
     fn process_signal_labels() {
         assert_eq!(ProcessSignal::SigInt.label(), "SIGINT");
         assert_eq!(ProcessSignal::SigTerm.label(), "SIGTERM");
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_names() {
+        assert_eq!(signal_name(libc::SIGTERM), "SIGTERM");
+        assert_eq!(signal_name(libc::SIGKILL), "SIGKILL");
+        assert_eq!(signal_name(64), "signal 64");
+    }
 }
@@ -0,0 +1,69 @@
+//! Fuzzy matching for the command palette and the process switcher.
+//!
+//! A candidate matches when the query's characters appear in it in order, ignoring case.
+//! Matches score higher when matched characters are consecutive or start a word, and
+//! when they appear early, so `rw` ranks "Restart web" above "Show the raw export".
+
+/// Scores `candidate` against `query`; `None` when it does not match.
+pub fn score(query: &str, candidate: &str) -> Option<u32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let mut total = 0u32;
+    let mut next = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut previous_char: Option<char> = None;
+    for (index, c) in candidate.chars().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        if lower == query[next] {
+            let mut points = 1;
+            if previous_match.is_some_and(|previous| previous + 1 == index) {
+                points += 4;
+            }
+            let word_start = match previous_char {
+                None => true,
+                Some(p) => !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase()),
+            };
+            if word_start {
+                points += 3;
+            }
+            if index < 8 {
+                points += 1;
+            }
+            total += points;
+            previous_match = Some(index);
+            next += 1;
+        }
+        previous_char = Some(c);
+    }
+    (next == query.len()).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_subsequences_ignoring_case() {
+        assert_eq!(score("", "anything"), Some(0));
+        assert!(score("RSW", "restart web").is_some());
+        assert!(score("wr", "restart web").is_none());
+        assert!(score("api x", "api").is_none());
+    }
+
+    #[test]
+    fn prefers_word_starts_and_runs() {
+        let restart_web = score("rw", "Restart web").unwrap();
+        let raw_export = score("rw", "Show the raw export").unwrap();
+        assert!(restart_web > raw_export);
+        assert!(score("json", "Toggle JSON formatting") > score("json", "Join session"));
+    }
+}
@@ -0,0 +1,507 @@
+//! Local HTTP API.
+//!
+//! An opt-in HTTP/1.1 server bound to `127.0.0.1` that exposes the same operations as the
+//! control socket: process status as JSON, per-process log streaming over Server-Sent Events
+//! and POST endpoints for restart, stop and input. Requests are translated into
+//! `ControlRequest`s and answered by the main loop, so both front ends share one code path.
+//!
+//! Every request must carry the session token, either as `Authorization: Bearer <token>` or
+//! as a `token` query parameter (browsers cannot set headers on `EventSource`).
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use crate::control::ControlRequest;
+use crate::events::Event;
+
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+/// Buffered lines sent when an SSE stream opens, unless `lines` is given.
+const DEFAULT_BACKLOG: usize = 100;
+const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Binds the API on the loopback interface and serves it in the background.
+///
+/// Returns the bound address (useful when `port` is 0). `max_lines` caps the `lines`
+/// backlog an SSE client may ask for.
+pub async fn spawn_server(
+    port: u16,
+    token: String,
+    max_lines: usize,
+    tx: mpsc::Sender<Event>,
+) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("failed to bind HTTP API on 127.0.0.1:{}", port))?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_connection(stream, token.clone(), max_lines, tx.clone()));
+        }
+    });
+    Ok(addr)
+}
+
+/// Generates a random access token (32 hex characters).
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    let from_os = std::fs::File::open("/dev/urandom")
+        .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut bytes))
+        .is_ok();
+    if !from_os {
+        // No /dev/urandom (e.g. Windows): fall back to the clock and PID.
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            ^ (u128::from(std::process::id()) << 64);
+        bytes = seed.to_le_bytes();
+    }
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    query: Vec<(String, String)>,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn query_param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn token(&self) -> Option<&str> {
+        self.authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| self.query_param("token"))
+    }
+}
+
+/// What to do with a parsed request.
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    /// Answer with a single JSON body.
+    Json(ControlRequest),
+    /// Stream log lines as Server-Sent Events.
+    Events(ControlRequest),
+    NotFound,
+    MethodNotAllowed,
+    BadRequest(String),
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    token: String,
+    max_lines: usize,
+    tx: mpsc::Sender<Event>,
+) {
+    let request = match read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(err) => {
+            let _ = write_json(&mut stream, 400, &error_body(&err.to_string())).await;
+            return;
+        }
+    };
+    let route = route(&request, max_lines);
+    if !token_matches(request.token(), &token) {
+        let _ = write_json(&mut stream, 401, &error_body("missing or invalid token")).await;
+        return;
+    }
+    match route {
+        Route::Json(request) => {
+            let Some(mut replies) = dispatch(request, &tx).await else {
+                let _ = write_json(&mut stream, 503, &error_body("session is shutting down")).await;
+                return;
+            };
+            let reply = replies
+                .recv()
+                .await
+                .unwrap_or_else(|| error_body("no reply"));
+            let status = match serde_json::from_str::<Value>(&reply) {
+                Ok(value) if value["ok"] == Value::Bool(true) => 200,
+                _ => 400,
+            };
+            let _ = write_json(&mut stream, status, &reply).await;
+        }
+        Route::Events(request) => {
+            let Some(replies) = dispatch(request, &tx).await else {
+                let _ = write_json(&mut stream, 503, &error_body("session is shutting down")).await;
+                return;
+            };
+            stream_events(&mut stream, replies).await;
+        }
+        Route::NotFound => {
+            let _ = write_json(&mut stream, 404, &error_body("not found")).await;
+        }
+        Route::MethodNotAllowed => {
+            let _ = write_json(&mut stream, 405, &error_body("method not allowed")).await;
+        }
+        Route::BadRequest(message) => {
+            let _ = write_json(&mut stream, 400, &error_body(&message)).await;
+        }
+    }
+}
+
+async fn dispatch(request: ControlRequest, tx: &mpsc::Sender<Event>) -> Option<mpsc::Receiver<String>> {
+    let (reply, replies) = mpsc::channel(256);
+    tx.send(Event::Control { request, reply }).await.ok()?;
+    Some(replies)
+}
+
+async fn stream_events<S: AsyncWrite + Unpin>(stream: &mut S, mut replies: mpsc::Receiver<String>) {
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\
+                Connection: keep-alive\r\n\r\n";
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    let mut keepalive = tokio::time::interval(SSE_KEEPALIVE);
+    keepalive.tick().await;
+    loop {
+        let chunk = tokio::select! {
+            reply = replies.recv() => match reply {
+                Some(reply) => format!("{}data: {}\n\n", sse_event_field(&reply), reply),
+                None => return,
+            },
+            _ = keepalive.tick() => ": keepalive\n\n".to_string(),
+        };
+        // A failed write means the client went away; dropping `replies` unregisters it.
+        if stream.write_all(chunk.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// `event: error` for error replies, so `EventSource` clients can tell them from log lines.
+fn sse_event_field(reply: &str) -> &'static str {
+    let is_error = serde_json::from_str::<Value>(reply).is_ok_and(|reply| reply["ok"] == false);
+    if is_error {
+        "event: error\n"
+    } else {
+        ""
+    }
+}
+
+fn route(request: &Request, max_lines: usize) -> Route {
+    let segments: Vec<String> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .map(percent_decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+    let method = request.method.as_str();
+    match (method, segments.as_slice()) {
+        ("GET", ["api", "processes"]) => Route::Json(ControlRequest::Status),
+        ("GET", ["api", "processes", name, "events"]) => {
+            let lines = match request.query_param("lines").map(str::parse::<usize>) {
+                Some(Ok(lines)) => lines.min(max_lines),
+                Some(Err(_)) => return Route::BadRequest("invalid lines parameter".to_string()),
+                None => DEFAULT_BACKLOG.min(max_lines),
+            };
+            Route::Events(ControlRequest::Logs {
+                name: name.to_string(),
+                follow: true,
+                lines: Some(lines),
+            })
+        }
+        ("POST", ["api", "processes", name, "restart"]) => Route::Json(ControlRequest::Restart {
+            name: name.to_string(),
+        }),
+        ("POST", ["api", "processes", name, "stop"]) => Route::Json(ControlRequest::Kill {
+            name: name.to_string(),
+        }),
+        ("POST", ["api", "processes", name, "input"]) => match input_text(&request.body) {
+            Ok(text) => Route::Json(ControlRequest::Input {
+                name: name.to_string(),
+                text,
+            }),
+            Err(message) => Route::BadRequest(message),
+        },
+        (_, ["api", "processes"])
+        | (_, ["api", "processes", _, "events" | "restart" | "stop" | "input"]) => {
+            Route::MethodNotAllowed
+        }
+        _ => Route::NotFound,
+    }
+}
+
+// The input body is either `{"text": "..."}` or plain text.
+fn input_text(body: &[u8]) -> std::result::Result<String, String> {
+    let body = std::str::from_utf8(body).map_err(|_| "body is not valid UTF-8".to_string())?;
+    if body.trim_start().starts_with('{') {
+        let value: Value =
+            serde_json::from_str(body).map_err(|err| format!("invalid JSON body: {}", err))?;
+        return value["text"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "missing \"text\" field".to_string());
+    }
+    Ok(body.trim_end_matches(['\r', '\n']).to_string())
+}
+
+pub async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Request> {
+    let mut buffer = Vec::new();
+    let header_end = loop {
+        if let Some(pos) = find_subslice(&buffer, b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            bail!("request headers too large");
+        }
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            bail!("connection closed before end of headers");
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+    let head = std::str::from_utf8(&buffer[..header_end]).context("headers are not valid UTF-8")?;
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        bail!("malformed request line");
+    };
+    let mut authorization = None;
+    let mut content_length = 0usize;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().context("invalid content-length")?;
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        bail!("request body too large");
+    }
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            bail!("connection closed before end of body");
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query,
+        authorization,
+        body,
+    })
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| bytes.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                idx += 3;
+            }
+            None => {
+                out.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// Compares without short-circuiting so response timing does not leak the token.
+fn token_matches(given: Option<&str>, expected: &str) -> bool {
+    let Some(given) = given else {
+        return false;
+    };
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn error_body(message: &str) -> String {
+    crate::control::error_reply(message)
+}
+
+async fn write_json<S: AsyncWrite + Unpin>(stream: &mut S, status: u16, body: &str) -> std::io::Result<()> {
+    write_response(stream, status, "application/json", body.as_bytes()).await
+}
+
+pub async fn write_response<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, target: &str, body: &str) -> Request {
+        let raw = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            target,
+            body.len(),
+            body
+        );
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        rt.block_on(read_request(&mut raw.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn read_request_parses_query_and_body() {
+        let raw = "POST /api/processes/my%20api/input?token=abc&x HTTP/1.1\r\n\
+                   authorization: Bearer t0k\r\ncontent-length: 5\r\n\r\nhello";
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let request = rt.block_on(read_request(&mut raw.as_bytes())).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/processes/my%20api/input");
+        assert_eq!(request.query_param("token"), Some("abc"));
+        assert_eq!(request.query_param("x"), Some(""));
+        assert_eq!(request.token(), Some("t0k"));
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[test]
+    fn route_maps_endpoints_to_control_requests() {
+        assert_eq!(
+            route(&request("GET", "/api/processes", ""), 1000),
+            Route::Json(ControlRequest::Status)
+        );
+        assert_eq!(
+            route(&request("GET", "/api/processes/web/events?lines=5", ""), 1000),
+            Route::Events(ControlRequest::Logs {
+                name: "web".to_string(),
+                follow: true,
+                lines: Some(5),
+            })
+        );
+        assert_eq!(
+            route(&request("POST", "/api/processes/my%20api/stop", ""), 1000),
+            Route::Json(ControlRequest::Kill {
+                name: "my api".to_string()
+            })
+        );
+        assert_eq!(
+            route(&request("POST", "/api/processes/api/input", r#"{"text":"rs"}"#), 1000),
+            Route::Json(ControlRequest::Input {
+                name: "api".to_string(),
+                text: "rs".to_string()
+            })
+        );
+        assert_eq!(
+            route(&request("GET", "/api/processes/api/restart", ""), 1000),
+            Route::MethodNotAllowed
+        );
+        assert_eq!(
+            route(&request("GET", "/api/processes/web/events?lines=1000000", ""), 1000),
+            Route::Events(ControlRequest::Logs {
+                name: "web".to_string(),
+                follow: true,
+                lines: Some(1000),
+            })
+        );
+        assert_eq!(
+            route(&request("OPTIONS", "/api/processes", ""), 1000),
+            Route::MethodNotAllowed
+        );
+        assert_eq!(route(&request("GET", "/", ""), 1000), Route::NotFound);
+        assert!(matches!(
+            route(&request("GET", "/api/processes/web/events?lines=x", ""), 1000),
+            Route::BadRequest(_)
+        ));
+    }
+
+    #[test]
+    fn sse_event_field_marks_only_error_replies() {
+        assert_eq!(sse_event_field(r#"{"ok":false,"error":"no such process"}"#), "event: error\n");
+        assert_eq!(sse_event_field(r#"{"ok": false, "error": "x"}"#), "event: error\n");
+        assert_eq!(sse_event_field(r#"{"line":"{\"ok\":false}"}"#), "");
+        assert_eq!(sse_event_field(r#"{"ok":false} is not json"#), "");
+    }
+
+    #[test]
+    fn input_text_accepts_json_or_plain_text() {
+        assert_eq!(input_text(b"ls -la\n").unwrap(), "ls -la");
+        assert_eq!(input_text(br#"{"text":"y"}"#).unwrap(), "y");
+        assert!(input_text(br#"{"txt":"y"}"#).is_err());
+    }
+
+    #[test]
+    fn token_matches_requires_exact_token() {
+        assert!(token_matches(Some("abc"), "abc"));
+        assert!(!token_matches(Some("abd"), "abc"));
+        assert!(!token_matches(Some("ab"), "abc"));
+        assert!(!token_matches(None, "abc"));
+        assert_eq!(generate_token().len(), 32);
+    }
+
+    #[test]
+    fn percent_decode_handles_invalid_escapes() {
+        assert_eq!(percent_decode("a%2Fb"), "a/b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
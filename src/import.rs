@@ -0,0 +1,449 @@
+//! Importing process definitions from other tools.
+//!
+//! `piperack import <file>` reads a `Procfile`, the `scripts` of a `package.json`, or the
+//! services of a docker-compose file and renders an equivalent `piperack.toml`. Settings
+//! that have no direct equivalent are kept as comments above the process they belong to.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::{PortRef, ReadyCheckConfig};
+
+/// Package scripts that start a development server, in order of preference.
+pub const DEV_SCRIPTS: &[&str] = &["dev", "start", "serve"];
+
+/// First `PORT` foreman hands out; each further Procfile entry gets the next hundred.
+const PROCFILE_BASE_PORT: u16 = 5000;
+
+/// A process definition read from another tool's file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedProcess {
+    pub name: String,
+    pub cmd: String,
+    pub cwd: Option<String>,
+    pub depends_on: Vec<String>,
+    pub ports: Vec<u16>,
+    pub ready_check: Option<ReadyCheckConfig>,
+    pub watch: Vec<String>,
+    pub watch_ignore: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    /// Rendered as comments above the process.
+    pub notes: Vec<String>,
+    /// Rendered commented out, for the user to enable by hand.
+    pub commented: bool,
+}
+
+/// Reads `path` and returns its processes, picking the format from the file name.
+pub fn import_file(path: &Path) -> Result<Vec<ImportedProcess>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let cwd = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty() && *parent != Path::new("."))
+        .map(|parent| parent.display().to_string());
+    let mut processes = if file_name.starts_with("procfile") {
+        parse_procfile(&raw)?
+    } else if file_name == "package.json" {
+        parse_package_json(&raw, package_runner(path))?
+    } else if file_name.ends_with(".yml") || file_name.ends_with(".yaml") {
+        parse_compose(&raw, &path.display().to_string())?
+    } else {
+        bail!(
+            "don't know how to import {} (expected a Procfile, package.json or docker-compose file)",
+            path.display()
+        );
+    };
+    if processes.is_empty() {
+        bail!("no processes found in {}", path.display());
+    }
+    // Compose commands already name the file, so they run from the current directory.
+    if !file_name.ends_with(".yml") && !file_name.ends_with(".yaml") {
+        for process in &mut processes {
+            process.cwd = cwd.clone();
+        }
+    }
+    Ok(processes)
+}
+
+/// Parses `name: command` lines; blank lines and `#` comments are skipped.
+///
+/// Like foreman, each command runs through `sh -c` with its own `PORT`.
+pub fn parse_procfile(raw: &str) -> Result<Vec<ImportedProcess>> {
+    let mut processes: Vec<ImportedProcess> = Vec::new();
+    for (idx, line) in raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, cmd) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Procfile line {}: expected `name: command`", idx + 1))?;
+        let (name, cmd) = (name.trim(), cmd.trim());
+        if name.is_empty() || cmd.is_empty() {
+            bail!("Procfile line {}: expected `name: command`", idx + 1);
+        }
+        let port = PROCFILE_BASE_PORT + 100 * processes.len() as u16;
+        processes.push(ImportedProcess {
+            name: name.to_string(),
+            cmd: format!("sh -c {}", shell_words::quote(cmd)),
+            env: BTreeMap::from([("PORT".to_string(), port.to_string())]),
+            ..Default::default()
+        });
+    }
+    Ok(processes)
+}
+
+/// Picks the package manager from the lockfile next to `package.json`.
+pub fn package_runner(path: &Path) -> &'static str {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let lockfiles = [
+        ("pnpm-lock.yaml", "pnpm"),
+        ("yarn.lock", "yarn"),
+        ("bun.lockb", "bun"),
+        ("bun.lock", "bun"),
+    ];
+    lockfiles
+        .iter()
+        .find(|(lockfile, _)| dir.join(lockfile).exists())
+        .map(|(_, runner)| *runner)
+        .unwrap_or("npm")
+}
+
+/// One process per script, run through `runner`. `pre`/`post` hooks of another script are
+/// skipped because the package manager runs them itself, and scripts other than the dev
+/// servers (`build`, `test`, ...) usually run once, so they are rendered commented out.
+pub fn parse_package_json(raw: &str, runner: &str) -> Result<Vec<ImportedProcess>> {
+    let package: Value = serde_json::from_str(raw).context("failed to parse package.json")?;
+    let Some(scripts) = package.get("scripts").and_then(Value::as_object) else {
+        bail!("package.json has no scripts");
+    };
+    let is_hook = |name: &str| {
+        ["pre", "post"].iter().any(|prefix| {
+            name.strip_prefix(prefix)
+                .is_some_and(|base| scripts.contains_key(base))
+        })
+    };
+    let mut processes = Vec::new();
+    for (name, script) in scripts {
+        if is_hook(name) {
+            continue;
+        }
+        let Some(script) = script.as_str() else {
+            continue;
+        };
+        processes.push(ImportedProcess {
+            name: name.clone(),
+            cmd: format!("{} run {}", runner, name),
+            notes: vec![format!("script: {}", script)],
+            commented: !is_dev_script(name),
+            ..Default::default()
+        });
+    }
+    Ok(processes)
+}
+
+// `dev`, `start:api`, `serve-docs`, ...
+fn is_dev_script(name: &str) -> bool {
+    let base = name.split([':', '-']).next().unwrap_or(name);
+    DEV_SCRIPTS.contains(&base)
+}
+
+#[derive(Debug, Deserialize)]
+struct ComposeFile {
+    #[serde(default)]
+    services: BTreeMap<String, ComposeService>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ComposeService {
+    depends_on: Option<ComposeDependsOn>,
+    healthcheck: Option<ComposeHealthcheck>,
+    ports: Vec<ComposePort>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ComposeDependsOn {
+    List(Vec<String>),
+    /// Long syntax: `db: { condition: service_healthy }`.
+    Map(BTreeMap<String, serde_norway::Value>),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ComposeHealthcheck {
+    test: Option<serde_norway::Value>,
+    disable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ComposePort {
+    Short(String),
+    Long { published: Option<serde_norway::Value> },
+    /// A bare container port, published on a random host port.
+    Container(serde::de::IgnoredAny),
+}
+
+impl ComposePort {
+    /// The port published on the host, if any (`"8080:80"`, `"127.0.0.1:8080:80/tcp"`).
+    fn host_port(&self) -> Option<u16> {
+        match self {
+            ComposePort::Container(_) => None,
+            ComposePort::Short(spec) => {
+                let spec = spec.split('/').next().unwrap_or(spec);
+                let mut parts = spec.rsplitn(3, ':');
+                let _container = parts.next()?;
+                parts.next()?.parse().ok()
+            }
+            ComposePort::Long { published } => match published.as_ref()? {
+                serde_norway::Value::Number(number) => number.as_u64()?.try_into().ok(),
+                serde_norway::Value::String(text) => text.parse().ok(),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// One `docker compose up` process per service. Dependencies are started by Piperack
+/// (`--no-deps`), and a healthcheck becomes a TCP readiness check on the first published port.
+pub fn parse_compose(raw: &str, file: &str) -> Result<Vec<ImportedProcess>> {
+    let compose: ComposeFile = serde_norway::from_str(raw).context("failed to parse compose file")?;
+    let file = shell_words::quote(file);
+    let mut processes = Vec::new();
+    for (name, service) in compose.services {
+        let depends_on = match service.depends_on {
+            Some(ComposeDependsOn::List(names)) => names,
+            Some(ComposeDependsOn::Map(names)) => names.into_keys().collect(),
+            None => Vec::new(),
+        };
+        let ports: Vec<u16> = service.ports.iter().filter_map(ComposePort::host_port).collect();
+        let mut notes = Vec::new();
+        let mut ready_check = None;
+        if let Some(healthcheck) = service.healthcheck.filter(|check| !check.disable) {
+            let test = healthcheck.test.as_ref().map(healthcheck_text).unwrap_or_default();
+            ready_check = ports.first().map(|port| ReadyCheckConfig::Tcp(PortRef::Number(*port)));
+            if ready_check.is_none() {
+                notes.push(format!("healthcheck not imported (no published port): {}", test));
+            } else {
+                notes.push(format!("healthcheck: {}", test));
+            }
+        }
+        processes.push(ImportedProcess {
+            cmd: format!(
+                "docker compose -f {} up --no-deps --no-log-prefix {}",
+                file,
+                shell_words::quote(&name)
+            ),
+            name,
+            depends_on,
+            ports,
+            ready_check,
+            notes,
+            ..Default::default()
+        });
+    }
+    Ok(processes)
+}
+
+fn healthcheck_text(test: &serde_norway::Value) -> String {
+    match test {
+        serde_norway::Value::String(text) => text.clone(),
+        serde_norway::Value::Sequence(items) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    }
+}
+
+/// Renders `processes` as a `piperack.toml` document, starting with a `header` comment.
+pub fn render_toml(processes: &[ImportedProcess], header: &str) -> String {
+    let quote = |text: &str| toml::Value::String(text.to_string()).to_string();
+    let list = |items: &[String]| {
+        let items: Vec<String> = items.iter().map(|item| quote(item)).collect();
+        format!("[{}]", items.join(", "))
+    };
+    let mut out = format!("# {}\n", header);
+    for process in processes {
+        out.push('\n');
+        for note in &process.notes {
+            out.push_str(&format!("# {}\n", note.replace('\n', " ")));
+        }
+        let start = out.len();
+        out.push_str("[[process]]\n");
+        out.push_str(&format!("name = {}\n", quote(&process.name)));
+        out.push_str(&format!("cmd = {}\n", quote(&process.cmd)));
+        if let Some(cwd) = &process.cwd {
+            out.push_str(&format!("cwd = {}\n", quote(cwd)));
+        }
+        if !process.depends_on.is_empty() {
+            out.push_str(&format!("depends_on = {}\n", list(&process.depends_on)));
+        }
+        if !process.ports.is_empty() {
+            let ports: Vec<String> = process.ports.iter().map(u16::to_string).collect();
+            out.push_str(&format!("ports = [{}]\n", ports.join(", ")));
+        }
+        if !process.watch.is_empty() {
+            out.push_str(&format!("watch = {}\n", list(&process.watch)));
+        }
+        if !process.watch_ignore.is_empty() {
+            out.push_str(&format!("watch_ignore = {}\n", list(&process.watch_ignore)));
+        }
+        let ready_check = match &process.ready_check {
+            Some(ReadyCheckConfig::Tcp(PortRef::Number(port))) => Some(format!("tcp = {}", port)),
+            Some(ReadyCheckConfig::Tcp(PortRef::Name(name))) => Some(format!("tcp = {}", quote(name))),
+            Some(ReadyCheckConfig::Delay(ms)) => Some(format!("delay = {}", ms)),
+            Some(ReadyCheckConfig::Log(pattern)) => Some(format!("log = {}", quote(pattern))),
+            None => None,
+        };
+        if let Some(ready_check) = ready_check {
+            out.push_str(&format!("ready_check = {{ {} }}\n", ready_check));
+        }
+        if !process.env.is_empty() {
+            let env: Vec<String> = process
+                .env
+                .iter()
+                .map(|(key, value)| format!("{} = {}", key, quote(value)))
+                .collect();
+            out.push_str(&format!("env = {{ {} }}\n", env.join(", ")));
+        }
+        if process.commented {
+            let block = out.split_off(start);
+            for line in block.lines() {
+                out.push_str(&format!("# {}\n", line));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn procfile_lines_become_processes() {
+        let processes = parse_procfile("# dev\nweb: bundle exec rails s -p $PORT\n\nworker:  sidekiq\n").unwrap();
+        assert_eq!(processes.len(), 2);
+        assert_eq!(processes[0].name, "web");
+        assert_eq!(processes[0].cmd, "sh -c 'bundle exec rails s -p $PORT'");
+        assert_eq!(processes[0].env["PORT"], "5000");
+        assert_eq!(processes[1].cmd, "sh -c sidekiq");
+        assert_eq!(processes[1].env["PORT"], "5100");
+        assert!(parse_procfile("just a command").is_err());
+    }
+
+    #[test]
+    fn package_scripts_skip_lifecycle_hooks() {
+        let raw = r#"{"scripts": {"predev": "codegen", "dev": "vite", "build": "vite build", "prepare": "husky", "start:api": "node api.js"}}"#;
+        let processes = parse_package_json(raw, "pnpm").unwrap();
+        let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["build", "dev", "prepare", "start:api"]);
+        assert_eq!(processes[1].cmd, "pnpm run dev");
+        assert_eq!(processes[1].notes, ["script: vite"]);
+        let commented: Vec<bool> = processes.iter().map(|p| p.commented).collect();
+        assert_eq!(commented, [true, false, true, false]);
+
+        let text = render_toml(&processes, "Imported from package.json");
+        assert!(text.contains("# script: vite build\n# [[process]]\n# name = \"build\"\n"));
+        let config: Config = toml::from_str(&text).unwrap();
+        let names: Vec<&str> = config.processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["dev", "start:api"]);
+    }
+
+    #[test]
+    fn compose_services_map_dependencies_and_healthchecks() {
+        let raw = r#"
+services:
+  db:
+    image: postgres
+    ports: ["127.0.0.1:5432:5432/tcp"]
+    healthcheck:
+      test: ["CMD", "pg_isready"]
+  cache:
+    image: redis
+    ports:
+      - 6379
+    healthcheck:
+      test: redis-cli ping
+  api:
+    build: .
+    ports:
+      - target: 80
+        published: "8080"
+    depends_on:
+      db:
+        condition: service_healthy
+      cache:
+        condition: service_started
+"#;
+        let processes = parse_compose(raw, "docker-compose.yml").unwrap();
+        let api = &processes[0];
+        assert_eq!(api.name, "api");
+        assert_eq!(api.cmd, "docker compose -f docker-compose.yml up --no-deps --no-log-prefix api");
+        assert_eq!(api.depends_on, ["cache", "db"]);
+        assert_eq!(api.ports, [8080]);
+        let cache = &processes[1];
+        assert_eq!(cache.ready_check, None);
+        assert!(cache.notes[0].starts_with("healthcheck not imported"));
+        let db = &processes[2];
+        assert_eq!(db.ports, [5432]);
+        assert_eq!(db.ready_check, Some(ReadyCheckConfig::Tcp(PortRef::Number(5432))));
+        assert_eq!(db.notes, ["healthcheck: CMD pg_isready"]);
+    }
+
+    #[test]
+    fn rendered_toml_loads_as_config() {
+        let processes = vec![
+            ImportedProcess {
+                name: "db".to_string(),
+                cmd: "docker compose up db".to_string(),
+                ports: vec![5432],
+                ready_check: Some(ReadyCheckConfig::Tcp(PortRef::Number(5432))),
+                notes: vec!["healthcheck: pg_isready".to_string()],
+                ..Default::default()
+            },
+            ImportedProcess {
+                name: "web \"app\"".to_string(),
+                cmd: "npm run dev".to_string(),
+                cwd: Some("frontend".to_string()),
+                depends_on: vec!["db".to_string()],
+                ready_check: Some(ReadyCheckConfig::Log("Local: +http".to_string())),
+                watch: vec!["src".to_string()],
+                watch_ignore: vec!["dist".to_string()],
+                env: BTreeMap::from([("PORT".to_string(), "5000".to_string())]),
+                ..Default::default()
+            },
+        ];
+        let text = render_toml(&processes, "Imported from docker-compose.yml");
+        assert!(text.contains("# healthcheck: pg_isready\n[[process]]\nname = \"db\""));
+        let config: Config = toml::from_str(&text).unwrap();
+        assert_eq!(config.processes.len(), 2);
+        assert!(matches!(
+            config.processes[0].ready_check,
+            Some(ReadyCheckConfig::Tcp(PortRef::Number(5432)))
+        ));
+        assert_eq!(config.processes[1].name, "web \"app\"");
+        assert_eq!(config.processes[1].cwd.as_deref(), Some("frontend"));
+        assert_eq!(config.processes[1].depends_on.as_deref(), Some(&["db".to_string()][..]));
+        assert_eq!(config.processes[1].watch_ignore.as_deref(), Some(&["dist".to_string()][..]));
+        assert_eq!(config.processes[1].env.as_ref().unwrap()["PORT"], "5000");
+        assert_eq!(
+            config.processes[1].ready_check,
+            Some(ReadyCheckConfig::Log("Local: +http".to_string()))
+        );
+    }
+}
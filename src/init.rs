@@ -0,0 +1,394 @@
+//! Project scaffolding for `piperack init`.
+//!
+//! Scans the working tree (a few levels deep, honouring `.gitignore`) for the services a
+//! repository usually runs: Cargo binaries, the dev script of each `package.json`, Django's
+//! `manage.py`, Go `main` packages and docker-compose services. Each one becomes a proposed
+//! process with `watch`, `watch_ignore` and `ready_check` defaults for its toolchain.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::config::{PortRef, ReadyCheckConfig};
+use crate::import::{self, ImportedProcess};
+
+/// How deep below the root manifests are looked for (`apps/web/package.json` is depth 3).
+const MAX_DEPTH: usize = 3;
+
+/// Directories that never contain services of their own.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+
+const COMPOSE_FILES: &[&str] = &[
+    "docker-compose.yml",
+    "docker-compose.yaml",
+    "compose.yml",
+    "compose.yaml",
+];
+
+/// Default ports of common JavaScript dev servers, matched against the script.
+const JS_DEV_PORTS: &[(&str, u16)] = &[
+    ("vite", 5173),
+    ("next", 3000),
+    ("nuxt", 3000),
+    ("astro", 4321),
+    ("react-scripts", 3000),
+];
+
+/// Services found by `detect`.
+#[derive(Debug, Default)]
+pub struct Detected {
+    pub processes: Vec<ImportedProcess>,
+    /// Manifests that could not be read or parsed, with the reason; the scan goes on without them.
+    pub skipped: Vec<String>,
+}
+
+/// Detects the services under `root`, with names made unique.
+pub fn detect(root: &Path) -> Result<Detected> {
+    let walker = ignore::WalkBuilder::new(root)
+        .max_depth(Some(MAX_DEPTH))
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_str().unwrap_or_default();
+            !(entry.depth() > 0 && SKIPPED_DIRS.contains(&name))
+        })
+        .build();
+    let mut detected = Detected::default();
+    for entry in walker {
+        let entry = entry.context("failed to scan the working tree")?;
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        let dir = path.parent().unwrap_or(root);
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        let cwd = dir
+            .strip_prefix(root)
+            .ok()
+            .filter(|rel| !rel.as_os_str().is_empty())
+            .map(|rel| rel.display().to_string());
+        let file_name = entry.file_name().to_str().unwrap_or_default();
+        let found = match file_name {
+            "Cargo.toml" => detect_cargo(dir),
+            "package.json" => detect_node(dir),
+            "manage.py" => detect_django(dir),
+            "go.mod" => detect_go(dir),
+            name if COMPOSE_FILES.contains(&name) => read_file(path)
+                .and_then(|raw| import::parse_compose(&raw, &rel.display().to_string())),
+            _ => continue,
+        };
+        // Fixtures and examples often hold broken manifests; they must not abort the scan.
+        let mut found = match found {
+            Ok(found) => found,
+            Err(err) => {
+                detected.skipped.push(format!("{:#}", err));
+                continue;
+            }
+        };
+        // Compose commands name the file, so they keep the root as working directory.
+        if !COMPOSE_FILES.contains(&file_name) {
+            for process in &mut found {
+                process.cwd = cwd.clone();
+            }
+        }
+        detected.processes.extend(found);
+    }
+    make_names_unique(&mut detected.processes);
+    Ok(detected)
+}
+
+/// One process per binary target of the package (workspace roots without a package have none).
+fn detect_cargo(dir: &Path) -> Result<Vec<ImportedProcess>> {
+    let manifest: toml::Value = read_file(&dir.join("Cargo.toml"))?
+        .parse()
+        .with_context(|| format!("failed to parse {}", dir.join("Cargo.toml").display()))?;
+    let Some(package) = manifest.get("package").and_then(|package| package.get("name")) else {
+        return Ok(Vec::new());
+    };
+    let mut bins: Vec<String> = manifest
+        .get("bin")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|bin| bin.get("name")?.as_str().map(str::to_string))
+        .collect();
+    if bins.is_empty() && dir.join("src/main.rs").is_file() {
+        bins.push(package.as_str().unwrap_or("app").to_string());
+    }
+    if let Ok(entries) = std::fs::read_dir(dir.join("src/bin")) {
+        let mut extra: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .filter(|name| !bins.contains(name))
+            .collect();
+        extra.sort();
+        bins.extend(extra);
+    }
+    let single = bins.len() == 1;
+    Ok(bins
+        .into_iter()
+        .map(|bin| ImportedProcess {
+            cmd: if single {
+                "cargo run".to_string()
+            } else {
+                format!("cargo run --bin {}", bin)
+            },
+            name: bin,
+            watch: existing(dir, &["src", "Cargo.toml", "build.rs"]),
+            watch_ignore: vec!["target".to_string()],
+            ..Default::default()
+        })
+        .collect())
+}
+
+/// The first dev-server script of the package. Known dev servers reload themselves, so only
+/// plain `node` scripts are watched.
+fn detect_node(dir: &Path) -> Result<Vec<ImportedProcess>> {
+    let package: Value = serde_json::from_str(&read_file(&dir.join("package.json"))?)
+        .with_context(|| format!("failed to parse {}", dir.join("package.json").display()))?;
+    let scripts = package.get("scripts").and_then(Value::as_object);
+    let Some((script_name, script)) = import::DEV_SCRIPTS.iter().find_map(|name| {
+        let script = scripts?.get(*name)?.as_str()?;
+        Some((*name, script))
+    }) else {
+        return Ok(Vec::new());
+    };
+    let name = package
+        .get("name")
+        .and_then(Value::as_str)
+        .map(|name| name.rsplit('/').next().unwrap_or(name).to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| dir_name(dir, "web"));
+    let port = JS_DEV_PORTS
+        .iter()
+        .find(|(tool, _)| script.contains(tool))
+        .map(|(_, port)| *port);
+    let mut process = ImportedProcess {
+        name,
+        cmd: format!("{} run {}", import::package_runner(&dir.join("package.json")), script_name),
+        ready_check: Some(match port {
+            Some(port) => ReadyCheckConfig::Tcp(PortRef::Number(port)),
+            None => ReadyCheckConfig::Log(r"(?i)\b(ready|listening)\b|localhost:\d+".to_string()),
+        }),
+        notes: vec![format!("script: {}", script)],
+        ..Default::default()
+    };
+    if script.starts_with("node ") {
+        process.watch = existing(dir, &["src", "lib", "package.json"]);
+        process.watch_ignore = vec!["node_modules".to_string()];
+    }
+    Ok(vec![process])
+}
+
+/// Django's development server (it reloads itself on changes).
+fn detect_django(dir: &Path) -> Result<Vec<ImportedProcess>> {
+    if !read_file(&dir.join("manage.py"))?.contains("django") {
+        return Ok(Vec::new());
+    }
+    Ok(vec![ImportedProcess {
+        name: "django".to_string(),
+        cmd: "python manage.py runserver".to_string(),
+        ready_check: Some(ReadyCheckConfig::Tcp(PortRef::Number(8000))),
+        ..Default::default()
+    }])
+}
+
+/// The module's `main` package at its root and under `cmd/*`.
+fn detect_go(dir: &Path) -> Result<Vec<ImportedProcess>> {
+    let module = read_file(&dir.join("go.mod"))?
+        .lines()
+        .find_map(|line| line.strip_prefix("module "))
+        .map(|module| module.trim().rsplit('/').next().unwrap_or_default().to_string())
+        .filter(|module| !module.is_empty())
+        .unwrap_or_else(|| dir_name(dir, "app"));
+    let mut mains = Vec::new();
+    if is_main_package(dir) {
+        mains.push((module, "go run .".to_string()));
+    }
+    if let Ok(entries) = std::fs::read_dir(dir.join("cmd")) {
+        let mut commands: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| is_main_package(&entry.path()))
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect();
+        commands.sort();
+        for command in commands {
+            let cmd = format!("go run ./cmd/{}", command);
+            mains.push((command, cmd));
+        }
+    }
+    Ok(mains
+        .into_iter()
+        .map(|(name, cmd)| ImportedProcess {
+            name,
+            cmd,
+            watch: vec![".".to_string()],
+            watch_ignore: vec!["vendor".to_string(), "**/*_test.go".to_string()],
+            ..Default::default()
+        })
+        .collect())
+}
+
+fn is_main_package(dir: &Path) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "go"))
+        .filter(|path| !path.to_string_lossy().ends_with("_test.go"))
+        .any(|path| {
+            std::fs::read_to_string(path)
+                .map(|source| source.lines().any(|line| line.trim() == "package main"))
+                .unwrap_or(false)
+        })
+}
+
+fn make_names_unique(processes: &mut [ImportedProcess]) {
+    let mut seen = HashSet::new();
+    for process in processes {
+        let base = process.name.clone();
+        let mut suffix = 2;
+        while !seen.insert(process.name.clone()) {
+            process.name = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+    }
+}
+
+fn existing(dir: &Path, paths: &[&str]) -> Vec<String> {
+    paths
+        .iter()
+        .filter(|path| dir.join(path).exists())
+        .map(|path| path.to_string())
+        .collect()
+}
+
+fn dir_name(dir: &Path, fallback: &str) -> String {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    struct Tree(PathBuf);
+
+    impl Tree {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("piperack-init-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            std::fs::create_dir_all(&root).unwrap();
+            Tree(root)
+        }
+
+        fn file(&self, path: &str, contents: &str) -> &Self {
+            let path = self.0.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+            self
+        }
+    }
+
+    impl Drop for Tree {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn find<'a>(processes: &'a [ImportedProcess], name: &str) -> &'a ImportedProcess {
+        processes
+            .iter()
+            .find(|process| process.name == name)
+            .unwrap_or_else(|| panic!("no process {:?} in {:?}", name, processes))
+    }
+
+    #[test]
+    fn detects_services_across_toolchains() {
+        let tree = Tree::new("detect");
+        tree.file("Cargo.toml", "[package]\nname = \"api\"\n")
+            .file("src/main.rs", "fn main() {}")
+            .file("src/bin/migrate.rs", "fn main() {}")
+            .file("web/package.json", r#"{"name": "@acme/web", "scripts": {"dev": "vite", "build": "vite build"}}"#)
+            .file("web/node_modules/dep/package.json", r#"{"scripts": {"dev": "x"}}"#)
+            .file("backend/manage.py", "import django\n")
+            .file("svc/go.mod", "module example.com/acme/svc\n")
+            .file("svc/cmd/worker/main.go", "package main\n")
+            .file("docker-compose.yml", "services:\n  db:\n    image: postgres\n");
+        let processes = detect(&tree.0).unwrap().processes;
+        let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["api", "migrate", "django", "db", "worker", "web"]);
+
+        let api = find(&processes, "api");
+        assert_eq!(api.cmd, "cargo run --bin api");
+        assert_eq!(api.cwd, None);
+        assert_eq!(api.watch, ["src", "Cargo.toml"]);
+        assert_eq!(api.watch_ignore, ["target"]);
+
+        let web = find(&processes, "web");
+        assert_eq!(web.cmd, "npm run dev");
+        assert_eq!(web.cwd.as_deref(), Some("web"));
+        assert_eq!(web.ready_check, Some(ReadyCheckConfig::Tcp(PortRef::Number(5173))));
+        assert!(web.watch.is_empty());
+
+        let django = find(&processes, "django");
+        assert_eq!(django.cwd.as_deref(), Some("backend"));
+        assert_eq!(django.ready_check, Some(ReadyCheckConfig::Tcp(PortRef::Number(8000))));
+
+        let worker = find(&processes, "worker");
+        assert_eq!(worker.cmd, "go run ./cmd/worker");
+        assert_eq!(worker.cwd.as_deref(), Some("svc"));
+    }
+
+    #[test]
+    fn node_scripts_without_known_server_use_log_readiness_and_watch() {
+        let tree = Tree::new("node");
+        tree.file("package.json", r#"{"scripts": {"start": "node src/server.js"}}"#)
+            .file("src/server.js", "")
+            .file("yarn.lock", "");
+        let processes = detect(&tree.0).unwrap().processes;
+        assert_eq!(processes.len(), 1);
+        let process = &processes[0];
+        assert_eq!(process.cmd, "yarn run start");
+        assert!(matches!(process.ready_check, Some(ReadyCheckConfig::Log(_))));
+        assert_eq!(process.watch, ["src", "package.json"]);
+        assert_eq!(process.watch_ignore, ["node_modules"]);
+    }
+
+    #[test]
+    fn unparsable_manifests_are_skipped() {
+        let tree = Tree::new("broken");
+        tree.file("package.json", r#"{"scripts": {"dev": "vite"}}"#)
+            .file("fixtures/bad/Cargo.toml", "[package\nname = ")
+            .file("examples/app/package.json", "{ not json");
+        let detected = detect(&tree.0).unwrap();
+        assert_eq!(detected.processes.len(), 1);
+        assert_eq!(detected.skipped.len(), 2);
+        assert!(detected.skipped.iter().any(|reason| reason.contains("Cargo.toml")));
+        assert!(detected.skipped.iter().any(|reason| reason.contains("package.json")));
+    }
+
+    #[test]
+    fn duplicate_names_get_suffixes() {
+        let mut processes = vec![
+            ImportedProcess { name: "web".to_string(), ..Default::default() },
+            ImportedProcess { name: "web".to_string(), ..Default::default() },
+            ImportedProcess { name: "web-2".to_string(), ..Default::default() },
+        ];
+        make_names_unique(&mut processes);
+        let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["web", "web-2", "web-2-2"]);
+    }
+}
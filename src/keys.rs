@@ -0,0 +1,618 @@
+//! TUI key bindings.
+//!
+//! Every normal-mode action has a default key chord. The `[keys]` table in the config maps
+//! actions to other chords (one string or a list), replacing that action's defaults. The
+//! resulting map is checked for chords bound to two actions, and the help overlay and the
+//! status bar hints are rendered from it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Something a key press does in normal mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    Up,
+    Down,
+    Cycle,
+    PageUp,
+    PageDown,
+    ScrollUp,
+    ScrollDown,
+    Top,
+    Bottom,
+    GoTo,
+    Input,
+    Follow,
+    Timeline,
+    Ansi,
+    Json,
+    Details,
+    Restart,
+    Kill,
+    RestartAll,
+    RestartGroup,
+    Export,
+    Copy,
+    Search,
+    NextMatch,
+    PrevMatch,
+    Filter,
+    Split,
+    ClosePane,
+    NextPane,
+    PrevPane,
+    SplitDirection,
+    Palette,
+    Help,
+    Quit,
+}
+
+impl KeyAction {
+    /// All actions, in help overlay order.
+    pub const ALL: [KeyAction; 34] = [
+        KeyAction::Up,
+        KeyAction::Down,
+        KeyAction::Cycle,
+        KeyAction::PageUp,
+        KeyAction::PageDown,
+        KeyAction::ScrollUp,
+        KeyAction::ScrollDown,
+        KeyAction::Top,
+        KeyAction::Bottom,
+        KeyAction::GoTo,
+        KeyAction::Input,
+        KeyAction::Follow,
+        KeyAction::Timeline,
+        KeyAction::Ansi,
+        KeyAction::Json,
+        KeyAction::Details,
+        KeyAction::Restart,
+        KeyAction::Kill,
+        KeyAction::RestartAll,
+        KeyAction::RestartGroup,
+        KeyAction::Export,
+        KeyAction::Copy,
+        KeyAction::Search,
+        KeyAction::NextMatch,
+        KeyAction::PrevMatch,
+        KeyAction::Filter,
+        KeyAction::Split,
+        KeyAction::ClosePane,
+        KeyAction::NextPane,
+        KeyAction::PrevPane,
+        KeyAction::SplitDirection,
+        KeyAction::Palette,
+        KeyAction::Help,
+        KeyAction::Quit,
+    ];
+
+    /// The name used in the `[keys]` table.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeyAction::Up => "up",
+            KeyAction::Down => "down",
+            KeyAction::Cycle => "cycle",
+            KeyAction::PageUp => "page_up",
+            KeyAction::PageDown => "page_down",
+            KeyAction::ScrollUp => "scroll_up",
+            KeyAction::ScrollDown => "scroll_down",
+            KeyAction::Top => "top",
+            KeyAction::Bottom => "bottom",
+            KeyAction::GoTo => "go_to",
+            KeyAction::Input => "input",
+            KeyAction::Follow => "follow",
+            KeyAction::Timeline => "timeline",
+            KeyAction::Ansi => "ansi",
+            KeyAction::Json => "json",
+            KeyAction::Details => "details",
+            KeyAction::Restart => "restart",
+            KeyAction::Kill => "kill",
+            KeyAction::RestartAll => "restart_all",
+            KeyAction::RestartGroup => "restart_group",
+            KeyAction::Export => "export",
+            KeyAction::Copy => "copy",
+            KeyAction::Search => "search",
+            KeyAction::NextMatch => "next_match",
+            KeyAction::PrevMatch => "prev_match",
+            KeyAction::Filter => "filter",
+            KeyAction::Split => "split",
+            KeyAction::ClosePane => "close_pane",
+            KeyAction::NextPane => "next_pane",
+            KeyAction::PrevPane => "prev_pane",
+            KeyAction::SplitDirection => "split_direction",
+            KeyAction::Palette => "palette",
+            KeyAction::Help => "help",
+            KeyAction::Quit => "quit",
+        }
+    }
+
+    /// One-line description for the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyAction::Up => "Select previous process",
+            KeyAction::Down => "Select next process",
+            KeyAction::Cycle => "Cycle selection",
+            KeyAction::PageUp => "Scroll logs up a page",
+            KeyAction::PageDown => "Scroll logs down a page",
+            KeyAction::ScrollUp => "Scroll logs up a line",
+            KeyAction::ScrollDown => "Scroll logs down a line",
+            KeyAction::Top => "Scroll to top",
+            KeyAction::Bottom => "Scroll to bottom and follow",
+            KeyAction::GoTo => "Go to process (by name or tag)",
+            KeyAction::Input => "Send input to process",
+            KeyAction::Follow => "Toggle auto-follow",
+            KeyAction::Timeline => "Toggle timeline view",
+            KeyAction::Ansi => "Toggle ANSI stripping",
+            KeyAction::Json => "Toggle JSON formatting",
+            KeyAction::Details => "Toggle process details",
+            KeyAction::Restart => "Restart selected",
+            KeyAction::Kill => "Kill selected",
+            KeyAction::RestartAll => "Restart ALL",
+            KeyAction::RestartGroup => "Restart Group (by tag)",
+            KeyAction::Export => "Export logs to file",
+            KeyAction::Copy => "Copy selection (or all logs)",
+            KeyAction::Search => "Search (jump to match)",
+            KeyAction::NextMatch => "Next match",
+            KeyAction::PrevMatch => "Previous match",
+            KeyAction::Filter => "Filter (hide non-matching)",
+            KeyAction::Split => "Split: open a pane on the next process",
+            KeyAction::ClosePane => "Close focused pane",
+            KeyAction::NextPane => "Focus next pane",
+            KeyAction::PrevPane => "Focus previous pane",
+            KeyAction::SplitDirection => "Toggle side-by-side/stacked",
+            KeyAction::Palette => "Open the command palette",
+            KeyAction::Help => "Toggle this help",
+            KeyAction::Quit => "Quit",
+        }
+    }
+
+    fn section(self) -> &'static str {
+        match self {
+            KeyAction::Up
+            | KeyAction::Down
+            | KeyAction::Cycle
+            | KeyAction::PageUp
+            | KeyAction::PageDown
+            | KeyAction::ScrollUp
+            | KeyAction::ScrollDown
+            | KeyAction::Top
+            | KeyAction::Bottom
+            | KeyAction::GoTo => "Navigation:",
+            KeyAction::Search | KeyAction::NextMatch | KeyAction::PrevMatch | KeyAction::Filter => {
+                "Search & Filter:"
+            }
+            KeyAction::Split
+            | KeyAction::ClosePane
+            | KeyAction::NextPane
+            | KeyAction::PrevPane
+            | KeyAction::SplitDirection => "Panes:",
+            KeyAction::Palette | KeyAction::Help | KeyAction::Quit => "General:",
+            _ => "Actions:",
+        }
+    }
+
+    fn default_chords(self) -> Vec<KeyChord> {
+        let code = match self {
+            KeyAction::Up => KeyCode::Up,
+            KeyAction::Down => KeyCode::Down,
+            KeyAction::Cycle => KeyCode::Tab,
+            KeyAction::PageUp => KeyCode::PageUp,
+            KeyAction::PageDown => KeyCode::PageDown,
+            KeyAction::ScrollUp | KeyAction::ScrollDown => return Vec::new(),
+            KeyAction::Top => KeyCode::Home,
+            KeyAction::Bottom => KeyCode::End,
+            KeyAction::GoTo => KeyCode::Char('p'),
+            KeyAction::Input => KeyCode::Enter,
+            KeyAction::Follow => KeyCode::Char('f'),
+            KeyAction::Timeline => KeyCode::Char('t'),
+            KeyAction::Ansi => KeyCode::Char('a'),
+            KeyAction::Json => KeyCode::Char('j'),
+            KeyAction::Details => KeyCode::Char('d'),
+            KeyAction::Restart => KeyCode::Char('r'),
+            KeyAction::Kill => KeyCode::Char('k'),
+            KeyAction::RestartAll => KeyCode::Char('R'),
+            KeyAction::RestartGroup => KeyCode::Char('g'),
+            KeyAction::Export => KeyCode::Char('e'),
+            KeyAction::Copy => {
+                return vec![KeyChord::new(KeyCode::Char('c'), KeyModifiers::CONTROL)]
+            }
+            KeyAction::Search => KeyCode::Char('/'),
+            KeyAction::NextMatch => KeyCode::Char('n'),
+            KeyAction::PrevMatch => KeyCode::Char('N'),
+            KeyAction::Filter => KeyCode::Char('F'),
+            KeyAction::Split => KeyCode::Char('s'),
+            KeyAction::ClosePane => KeyCode::Char('x'),
+            KeyAction::NextPane => KeyCode::Char('w'),
+            KeyAction::PrevPane => KeyCode::Char('W'),
+            KeyAction::SplitDirection => KeyCode::Char('v'),
+            KeyAction::Palette => {
+                return vec![
+                    KeyChord::new(KeyCode::Char(':'), KeyModifiers::NONE),
+                    KeyChord::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+                ]
+            }
+            KeyAction::Help => KeyCode::Char('?'),
+            KeyAction::Quit => KeyCode::Char('q'),
+        };
+        vec![KeyChord::new(code, KeyModifiers::NONE)]
+    }
+}
+
+/// Status bar hints: the actions whose first chords are shown, and the label after them.
+const HINTS: &[(&[KeyAction], &str)] = &[
+    (&[KeyAction::Up, KeyAction::Down], "select"),
+    (&[KeyAction::Cycle], "cycle"),
+    (&[KeyAction::GoTo], "go to"),
+    (&[KeyAction::Input], "input"),
+    (&[KeyAction::Follow], "follow"),
+    (&[KeyAction::Timeline], "timeline"),
+    (&[KeyAction::Ansi], "ansi"),
+    (&[KeyAction::Search], "search"),
+    (&[KeyAction::Filter], "filter"),
+    (&[KeyAction::NextMatch, KeyAction::PrevMatch], "next/prev"),
+    (&[KeyAction::Restart], "restart"),
+    (&[KeyAction::RestartGroup], "group"),
+    (&[KeyAction::RestartAll], "all"),
+    (&[KeyAction::Kill], "kill"),
+    (&[KeyAction::Json], "json"),
+    (&[KeyAction::Details], "details"),
+    (&[KeyAction::Split, KeyAction::ClosePane, KeyAction::NextPane], "panes"),
+    (&[KeyAction::Export], "export"),
+    (&[KeyAction::Copy], "copy"),
+    (&[KeyAction::Quit], "quit"),
+    (&[KeyAction::Palette], "commands"),
+    (&[KeyAction::Help], "help"),
+];
+
+/// A key together with the Ctrl/Alt/Shift modifiers that must be held.
+///
+/// Shift is folded into the character for printable keys (`R`, not `shift+r`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    fn from_event(key: &KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+
+    /// Parses chords like `k`, `K`, `ctrl+p`, `alt+enter`, `shift+tab`, `pagedown` or `f5`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let (mods, key) = if let Some(mods) = text.strip_suffix("++") {
+            (Some(mods), "+")
+        } else {
+            match text.rsplit_once('+') {
+                Some((mods, key)) if !key.is_empty() => (Some(mods), key),
+                _ => (None, text),
+            }
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in mods.into_iter().flat_map(|mods| mods.split('+')) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => bail!("unknown modifier '{}' in key '{}'", other, text),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => {
+                if modifiers.contains(KeyModifiers::SHIFT) {
+                    KeyCode::Char(c.to_ascii_uppercase())
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            _ => match key.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                name => name
+                    .strip_prefix('f')
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .filter(|n| (1..=12).contains(n))
+                    .map(KeyCode::F)
+                    .ok_or_else(|| anyhow!("unknown key '{}'", text))?,
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    /// How the chord is shown in help, using arrows for Up/Down when symbols are on.
+    pub fn label(&self, use_symbols: bool) -> String {
+        match self.code {
+            KeyCode::Up if use_symbols && self.modifiers.is_empty() => "↑".to_string(),
+            KeyCode::Down if use_symbols && self.modifiers.is_empty() => "↓".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            // Ctrl+C rather than Ctrl+c, as terminals do not tell them apart.
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => f.write_str("Up"),
+            KeyCode::Down => f.write_str("Down"),
+            KeyCode::Left => f.write_str("Left"),
+            KeyCode::Right => f.write_str("Right"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::Home => f.write_str("Home"),
+            KeyCode::End => f.write_str("End"),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::BackTab => f.write_str("Shift+Tab"),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Delete => f.write_str("Del"),
+            KeyCode::Insert => f.write_str("Ins"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// A `[keys]` entry: one chord or several.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum KeyBindingConfig {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBindingConfig {
+    fn chords(&self) -> Vec<&str> {
+        match self {
+            KeyBindingConfig::One(chord) => vec![chord.as_str()],
+            KeyBindingConfig::Many(chords) => chords.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// The active key bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: BTreeMap<KeyAction, Vec<KeyChord>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: KeyAction::ALL
+                .iter()
+                .map(|action| (*action, action.default_chords()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Applies the `[keys]` table on top of the defaults. An empty list unbinds an action.
+    pub fn with_overrides(overrides: &BTreeMap<KeyAction, KeyBindingConfig>) -> Result<Self> {
+        let mut map = Self::default();
+        for (action, binding) in overrides {
+            let chords = binding
+                .chords()
+                .into_iter()
+                .map(|text| {
+                    KeyChord::parse(text)
+                        .map_err(|err| anyhow!("keys.{}: {}", action.as_str(), err))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            map.bindings.insert(*action, chords);
+        }
+        map.check_conflicts()?;
+        Ok(map)
+    }
+
+    fn check_conflicts(&self) -> Result<()> {
+        let mut seen: Vec<(KeyChord, KeyAction)> = Vec::new();
+        for action in KeyAction::ALL {
+            for chord in self.chords(action) {
+                if let Some((_, other)) = seen.iter().find(|(seen, _)| seen == chord) {
+                    bail!(
+                        "keys: '{}' is bound to both {} and {}; rebind one of them",
+                        chord,
+                        other.as_str(),
+                        action.as_str()
+                    );
+                }
+                seen.push((*chord, action));
+            }
+        }
+        Ok(())
+    }
+
+    /// The action bound to `key`, if any.
+    pub fn action(&self, key: &KeyEvent) -> Option<KeyAction> {
+        let chord = KeyChord::from_event(key);
+        self.bindings
+            .iter()
+            .find(|(_, chords)| chords.contains(&chord))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn chords(&self, action: KeyAction) -> &[KeyChord] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The chords of `action` joined for display, e.g. `k` or `Ctrl+K/K`.
+    pub fn label(&self, action: KeyAction, use_symbols: bool) -> String {
+        self.chords(action)
+            .iter()
+            .map(|chord| chord.label(use_symbols))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// The help overlay text, grouped by section; unbound actions are left out.
+    pub fn help_text(&self) -> String {
+        let width = KeyAction::ALL
+            .iter()
+            .map(|action| self.label(*action, false).chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(10);
+        let mut lines = Vec::new();
+        let mut section = "";
+        for action in KeyAction::ALL {
+            let label = self.label(action, false);
+            if label.is_empty() {
+                continue;
+            }
+            if action.section() != section {
+                if !section.is_empty() {
+                    lines.push(String::new());
+                }
+                section = action.section();
+                lines.push(section.to_string());
+            }
+            lines.push(format!("  {:<width$} {}", label, action.description(), width = width));
+            if action == KeyAction::GoTo && !self.binds_digits() {
+                lines.push(format!("  {:<width$} {}", "1-9", "Jump to the Nth process", width = width));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Whether a digit is bound, taking it away from the 1-9 process jump.
+    pub fn binds_digits(&self) -> bool {
+        self.bindings.values().flatten().any(|chord| {
+            chord.modifiers.is_empty() && matches!(chord.code, KeyCode::Char('1'..='9'))
+        })
+    }
+
+    /// The one-line key summary shown in the status bar.
+    pub fn hints(&self, use_symbols: bool) -> String {
+        HINTS
+            .iter()
+            .filter_map(|(actions, text)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| self.chords(*action).first())
+                    .map(|chord| chord.label(use_symbols))
+                    .collect();
+                if keys.is_empty() {
+                    None
+                } else {
+                    Some(format!("{} {}", keys.join("/"), text))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_and_displays_chords() {
+        assert_eq!(KeyChord::parse("k").unwrap().to_string(), "k");
+        assert_eq!(KeyChord::parse("shift+k").unwrap().to_string(), "K");
+        assert_eq!(KeyChord::parse("ctrl+p").unwrap().to_string(), "Ctrl+P");
+        assert_eq!(KeyChord::parse("Alt+Enter").unwrap().to_string(), "Alt+Enter");
+        assert_eq!(KeyChord::parse("shift+tab").unwrap().to_string(), "Shift+Tab");
+        assert_eq!(KeyChord::parse("pgdn").unwrap().to_string(), "PgDn");
+        assert_eq!(KeyChord::parse("ctrl++").unwrap().to_string(), "Ctrl++");
+        assert_eq!(KeyChord::parse("+").unwrap().to_string(), "+");
+        assert_eq!(KeyChord::parse("f5").unwrap().to_string(), "F5");
+        assert!(KeyChord::parse("hyper+k").is_err());
+        assert!(KeyChord::parse("f13").is_err());
+        assert!(KeyChord::parse("kk").is_err());
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_match_events() {
+        let overrides = BTreeMap::from([
+            (KeyAction::Kill, KeyBindingConfig::One("K".to_string())),
+            (KeyAction::Json, KeyBindingConfig::One("J".to_string())),
+            (KeyAction::ScrollUp, KeyBindingConfig::One("k".to_string())),
+            (
+                KeyAction::ScrollDown,
+                KeyBindingConfig::Many(vec!["j".to_string(), "ctrl+n".to_string()]),
+            ),
+        ]);
+        let map = KeyMap::with_overrides(&overrides).unwrap();
+        let shifted = press(KeyCode::Char('K'), KeyModifiers::SHIFT);
+        assert_eq!(map.action(&shifted), Some(KeyAction::Kill));
+        let k = press(KeyCode::Char('k'), KeyModifiers::NONE);
+        assert_eq!(map.action(&k), Some(KeyAction::ScrollUp));
+        let ctrl_n = press(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(map.action(&ctrl_n), Some(KeyAction::ScrollDown));
+        let n = press(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(map.action(&n), Some(KeyAction::NextMatch));
+        let ctrl_c = press(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(map.action(&ctrl_c), Some(KeyAction::Copy));
+        assert_eq!(map.action(&press(KeyCode::Char('c'), KeyModifiers::NONE)), None);
+        assert_eq!(map.label(KeyAction::ScrollDown, false), "j/Ctrl+N");
+        assert!(map.help_text().contains("  j/Ctrl+N   Scroll logs down a line"));
+        assert!(map.help_text().contains("  1-9        Jump to the Nth process"));
+        assert!(map.hints(true).starts_with("↑/↓ select | Tab cycle | p go to"));
+        assert!(map.hints(false).contains("K kill"));
+    }
+
+    #[test]
+    fn rejects_conflicting_bindings() {
+        let overrides =
+            BTreeMap::from([(KeyAction::ScrollUp, KeyBindingConfig::One("k".to_string()))]);
+        let err = KeyMap::with_overrides(&overrides).unwrap_err().to_string();
+        assert_eq!(err, "keys: 'k' is bound to both scroll_up and kill; rebind one of them");
+
+        let overrides =
+            BTreeMap::from([(KeyAction::Quit, KeyBindingConfig::One("ctrl+x+y".to_string()))]);
+        let err = KeyMap::with_overrides(&overrides).unwrap_err().to_string();
+        assert_eq!(err, "keys.quit: unknown modifier 'x' in key 'ctrl+x+y'");
+    }
+}
@@ -7,11 +7,29 @@
 mod ansi;
 mod clipboard;
 mod app;
+mod ci;
 mod config;
+mod control;
 mod events;
+mod fuzzy;
+mod http;
+mod import;
+mod init;
+mod keys;
+mod metrics;
+mod notify;
 mod output;
+mod palette;
+mod panes;
+mod ports;
 mod process;
+mod procfs;
+mod reaper;
+mod report;
 mod runner;
+mod session;
+mod stats;
+mod theme;
 mod tui;
 mod update;
 mod watch;
@@ -19,6 +37,7 @@ mod watch;
 use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context, Result};
@@ -28,11 +47,24 @@ use clap::{CommandFactory, Parser, Subcommand};
 use tokio::sync::mpsc;
 
 use crate::app::{App, AppAction};
-use crate::config::ProcessConfig;
+use crate::ci::CiProvider;
+use crate::config::{
+    Hook, HooksConfig, OnExit, PortRef, PortsConfig, ProcessConfig, ReadinessCheck, ReadyCheckConfig,
+    Trigger, TriggerAction, TriggerConfig, TriggerKind,
+};
 use crate::events::{Event, ProcessSignal};
-use crate::output::StreamKind;
+use crate::control::{ControlRequest, LogFollower};
+use crate::keys::KeyMap;
+use crate::notify::{NotifyEvent, NotifyMethod, NotifySettings, Notifier};
+use crate::output::{LogLine, StreamKind};
+use crate::ports::NamedPorts;
 use crate::process::{ProcessSpec, ProcessState, ProcessStatus};
 use crate::runner::{ProcessManager, ShutdownConfig};
+use crate::session::AttachedClients;
+use crate::reaper::{Straggler, TreeTracker};
+use crate::report::{Outcome, ProcessResult, ReportTarget};
+use crate::stats::StatsSampler;
+use crate::theme::Theme;
 use crate::update::check_for_update;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
@@ -40,15 +72,57 @@ enum OutputMode {
     Combined,
     Grouped,
     Raw,
+    /// One JSON object per event (NDJSON) on stdout.
+    Json,
+    /// Folded per-process blocks and error annotations for GitHub Actions / GitLab CI.
+    Ci,
 }
 
-#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum SuccessPolicy {
     First,
     Last,
     All,
+    /// The run ends when the named process exits, with its exit code (`process:<name>`).
+    Process(String),
 }
 
+/// Ends the run with a process's own exit code (see `SuccessPolicy::Process`).
+#[derive(Debug)]
+struct ProcessExit {
+    name: String,
+    code: Option<i32>,
+    signal: Option<i32>,
+}
+
+impl ProcessExit {
+    /// The process's own exit code, or `128 + signal` like a shell when a signal killed it.
+    fn exit_code(&self) -> u8 {
+        let code = match (self.code, self.signal) {
+            (Some(code), _) => Some(code),
+            (None, Some(signal)) => Some(128 + signal),
+            (None, None) => None,
+        };
+        code.and_then(|code| u8::try_from(code).ok())
+            .filter(|code| *code != 0)
+            .unwrap_or(1)
+    }
+}
+
+impl std::fmt::Display for ProcessExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.code, self.signal) {
+            (Some(code), _) => write!(f, "{} exited with code {}", self.name, code),
+            (None, Some(signal)) => {
+                write!(f, "{} was killed by {}", self.name, events::signal_name(signal))
+            }
+            (None, None) => write!(f, "{} exited without an exit code", self.name),
+        }
+    }
+}
+
+impl std::error::Error for ProcessExit {}
+
 /// Command-line interface definition.
 #[derive(Debug, Parser)]
 #[command(
@@ -68,12 +142,21 @@ struct Cli {
     /// Ignore any piperack.toml in the current directory.
     #[arg(long)]
     no_config: bool,
+    /// Control socket path (default: derived from the working directory).
+    #[arg(long, global = true)]
+    socket: Option<PathBuf>,
     /// Max log lines per process.
     #[arg(long)]
     max_lines: Option<usize>,
     /// Disable the TUI and print to stdout.
     #[arg(long)]
     no_ui: bool,
+    /// Run the processes in a background session (attach with `piperack attach`).
+    #[arg(long, conflicts_with = "no_ui")]
+    detach: bool,
+    /// Run as the background supervisor started by --detach.
+    #[arg(long, hide = true)]
+    headless: bool,
     /// Disable prefixed output in non-TUI mode.
     #[arg(long)]
     raw: bool,
@@ -92,8 +175,8 @@ struct Cli {
     /// Output mode in non-TUI mode ("combined", "grouped", "raw").
     #[arg(long, value_enum)]
     output: Option<OutputMode>,
-    /// Success policy when processes exit ("first", "last", "all").
-    #[arg(long, value_enum)]
+    /// Success policy when processes exit ("first", "last", "all", "process:<name>").
+    #[arg(long, value_parser = parse_success_policy)]
     success: Option<SuccessPolicy>,
     /// Stop other processes when any exits.
     #[arg(long)]
@@ -119,6 +202,21 @@ struct Cli {
     /// Log file template (e.g. "logs/{name}.log").
     #[arg(long)]
     log_file: Option<String>,
+    /// Write a report when the run ends (e.g. "junit=results.xml").
+    #[arg(long, value_name = "FORMAT=PATH", value_parser = ReportTarget::parse)]
+    report: Vec<ReportTarget>,
+    /// Serve the HTTP API on 127.0.0.1:<port> (0 picks a free port).
+    #[arg(long)]
+    http_port: Option<u16>,
+    /// Serve Prometheus metrics on 127.0.0.1:<port>/metrics (0 picks a free port).
+    #[arg(long)]
+    metrics_port: Option<u16>,
+    /// Notify from the TUI with these methods ("bell", "osc9", "osc777", "tmux").
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "METHOD")]
+    notify: Vec<NotifyMethod>,
+    /// Events that notify ("failure", "ready", "trigger"; default: failure).
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "EVENT")]
+    notify_on: Vec<NotifyEvent>,
     /// Comma-separated process names (shorthand for commands list).
     #[arg(long)]
     names: Option<String>,
@@ -137,6 +235,9 @@ struct Cli {
     /// Restart CLI-defined processes on failure.
     #[arg(long)]
     restart_on_fail: bool,
+    /// Stop processes that run longer than this many milliseconds and count them as failed.
+    #[arg(long, value_name = "MS")]
+    timeout_ms: Option<u64>,
     /// Process definitions: --name <name> -- <cmd> [args...]
     #[arg(trailing_var_arg = true)]
     args: Vec<String>,
@@ -150,10 +251,82 @@ enum Commands {
     Version,
     /// Print the ANSI banner.
     Banner,
+    /// Attach the TUI to a background session.
+    Attach,
+    /// Detect the services in this directory and propose a piperack.toml.
+    Init {
+        /// Write without asking for confirmation.
+        #[arg(short = 'y', long)]
+        yes: bool,
+        /// Overwrite an existing piperack.toml.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Convert a Procfile, package.json or docker-compose file into piperack.toml.
+    Import {
+        /// File to import.
+        file: PathBuf,
+        /// Write the config to this path instead of stdout.
+        #[arg(short = 'o', long = "out")]
+        out: Option<PathBuf>,
+        /// Overwrite the output file if it exists.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Control a running session.
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CtlCommand {
+    /// Restart a process.
+    Restart { name: String },
+    /// Stop a process.
+    Kill { name: String },
+    /// Restart every process with a tag ("all" for every process).
+    RestartGroup { group: String },
+    /// Send a line of input to a process.
+    Input {
+        name: String,
+        #[arg(required = true)]
+        text: Vec<String>,
+    },
+    /// Export a process's logs to piperack-logs/.
+    Export { name: String },
+    /// Show process status.
+    Status {
+        /// Print the raw JSON reply.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a process's buffered logs.
+    Logs {
+        name: String,
+        /// Keep streaming new lines.
+        #[arg(short, long)]
+        follow: bool,
+        /// Only print the last N buffered lines.
+        #[arg(short = 'n', long)]
+        lines: Option<usize>,
+    },
+    /// Stop every process and end the session.
+    Shutdown,
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
+    let result = run().await;
+    if let Some(exit) = result.as_ref().err().and_then(|err| err.downcast_ref::<ProcessExit>()) {
+        eprintln!("Error: {}", exit);
+        return Ok(ExitCode::from(exit.exit_code()));
+    }
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+async fn run() -> Result<()> {
     let cli = Cli::parse();
     if let Some(command) = &cli.command {
         match command {
@@ -170,12 +343,28 @@ async fn main() -> Result<()> {
                 print_ansi_banner();
                 return Ok(());
             }
+            Commands::Attach => {
+                let (keys, theme) = load_tui_config(&cli)?;
+                return session::run_attach(&socket_path(&cli)?, keys, theme).await;
+            }
+            Commands::Init { yes, force } => {
+                return run_init(*yes, *force);
+            }
+            Commands::Import { file, out, force } => {
+                return run_import(file, out.as_deref(), *force);
+            }
+            Commands::Ctl { command } => {
+                return run_ctl(command, &socket_path(&cli)?).await;
+            }
         }
     }
     let (specs, settings) = load_specs(&cli)?;
     if specs.is_empty() {
         bail!("no processes defined (use piperack.toml or --name ... -- cmd)");
     }
+    if cli.detach {
+        return session::spawn_detached(&socket_path(&cli)?);
+    }
 
     let (event_tx, mut event_rx) = mpsc::channel(256);
     let (output_tx, mut output_rx) = mpsc::channel(256);
@@ -194,20 +383,84 @@ async fn main() -> Result<()> {
         settings.use_symbols,
         settings.input_enabled,
     );
+    app.keys = settings.keys.clone();
+    app.theme = settings.theme.clone();
     let mut restart_attempts: HashMap<usize, u32> = HashMap::new();
+    // Processes whose last start failed on a taken port; retrying cannot help until it is freed.
+    let mut port_conflicts: HashSet<usize> = HashSet::new();
+    let mut log_followers: Vec<LogFollower> = Vec::new();
+    let mut attached = AttachedClients::new(
+        settings.max_lines,
+        settings.use_symbols,
+        settings.input_enabled,
+    );
+    let _control_socket = if cfg!(unix) {
+        match socket_path(&cli).and_then(|path| control::spawn_server(path, event_tx.clone())) {
+            Ok(socket) => Some(socket),
+            Err(err) => {
+                let message = format!("control socket disabled: {:#}", err);
+                if settings.no_ui {
+                    eprintln!("{}", format_tool_message(&message, settings.use_symbols));
+                } else {
+                    app.set_status_warning_for(message, Duration::from_secs(5));
+                }
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(port) = settings.http_port {
+        let token = http::generate_token();
+        let server = http::spawn_server(port, token.clone(), settings.max_lines, event_tx.clone());
+        let message = match server.await {
+            Ok(addr) => format!("HTTP API on http://{} (token: {})", addr, token),
+            Err(err) => format!("HTTP API disabled: {:#}", err),
+        };
+        // Printed before the TUI starts so it stays in the scrollback after exit.
+        eprintln!("{}", format_tool_message(&message, settings.use_symbols));
+        if !settings.no_ui {
+            app.set_status_warning_for(message, Duration::from_secs(15));
+        }
+    }
+    if let Some(port) = settings.metrics_port {
+        let message = match metrics::spawn_server(port, event_tx.clone()).await {
+            Ok(addr) => format!("metrics on http://{}/metrics", addr),
+            Err(err) => format!("metrics disabled: {:#}", err),
+        };
+        eprintln!("{}", format_tool_message(&message, settings.use_symbols));
+        if !settings.no_ui {
+            app.set_status_warning_for(message, Duration::from_secs(15));
+        }
+    }
+    let mut tree_tracker = TreeTracker::new(
+        app.processes
+            .iter()
+            .map(|process| process.spec.name.clone())
+            .collect(),
+    )
+    .with_owned(manager.owned_children());
+    reaper::become_subreaper();
 
     manager.start_all().await?;
 
-    let mut terminal = if settings.no_ui {
+    let mut terminal = if settings.no_ui || settings.headless {
         None
     } else {
         Some(tui::init_terminal()?)
     };
+    // Split panes are restored per project and saved again when the TUI exits.
+    let layout_path = terminal.as_ref().map(|_| panes::layout_path(Path::new(".")));
+    if let Some(layout) = layout_path.as_deref().and_then(panes::load_layout) {
+        app.apply_pane_layout(&layout);
+    }
     let tick_rate = Duration::from_millis(150);
     let draw_interval = Duration::from_millis(33);
     let mut last_draw = Instant::now() - draw_interval;
 
-    if !settings.no_ui {
+    if settings.headless {
+        // Input arrives from attached clients over the control socket.
+    } else if !settings.no_ui {
         spawn_input_listener(event_tx.clone());
     } else if settings.input_enabled {
         spawn_stdin_listener(event_tx.clone());
@@ -218,14 +471,23 @@ async fn main() -> Result<()> {
     let mut ticker = tokio::time::interval(tick_rate);
     let mut result = Ok(());
     let mut output_state = OutputState::new(&app.processes, &settings);
+    // Notifications go to the terminal the TUI runs in.
+    let mut notifier = settings
+        .notify
+        .clone()
+        .filter(|_| !settings.no_ui && !settings.headless)
+        .map(Notifier::new);
     let mut shutdown_in_progress = false;
     let mut shutdown_started_at: Option<Instant> = None;
     const MIN_SHUTDOWN_DISPLAY: Duration = Duration::from_millis(1500);
     const MIN_SIGNAL_DISPLAY: Duration = Duration::from_millis(1500);
     const UPDATE_HINT_TTL: Duration = Duration::from_secs(10);
+    const HOOK_GRACE: Duration = Duration::from_secs(5);
     let mut shutdown_pending: Option<ProcessSignal> = None;
     let mut shutdown_dispatch_at: Option<Instant> = None;
     let mut last_signal_at: Option<Instant> = None;
+    let mut stats_sampler = StatsSampler::new();
+    let mut last_stats_at = Instant::now();
 
     loop {
         let event = tokio::select! {
@@ -234,6 +496,19 @@ async fn main() -> Result<()> {
             Some(event) = output_rx.recv() => event,
             _ = ticker.tick() => {
                 manager.poll_exits().await;
+                if last_stats_at.elapsed() >= stats::SAMPLE_INTERVAL {
+                    last_stats_at = Instant::now();
+                    let entries = procfs::snapshot();
+                    let pids = app.running_pids();
+                    let samples = stats_sampler.sample(&pids, &entries);
+                    for (id, sample) in samples.into_iter().enumerate() {
+                        app.on_process_stats(id, sample);
+                    }
+                    tree_tracker.update(&pids, &entries);
+                    for (id, pid) in pids.iter().enumerate() {
+                        app.on_process_tree(id, tree_tracker.tree(id, *pid, &entries));
+                    }
+                }
                 if let Some(signal) = shutdown_pending {
                     if shutdown_dispatch_at
                         .map(|when| Instant::now() >= when)
@@ -254,10 +529,14 @@ async fn main() -> Result<()> {
                 {
                     app.should_quit = true;
                 }
+                if app.should_quit {
+                    break;
+                }
                 continue;
             }
         };
 
+        output_state.record_event(&event);
         match event {
             Event::ProcessStarting { id } => {
                 app.on_process_starting(id);
@@ -278,14 +557,22 @@ async fn main() -> Result<()> {
                     );
                 }
             }
-            Event::ProcessStarted { id, pid } => app.on_process_started(id, pid),
+            Event::ProcessStarted { id, pid } => {
+                app.on_process_started(id, pid);
+                manager.run_hook(id, Hook::Start, Some(pid), None);
+            }
             Event::ProcessReady { id } => {
                 app.on_process_ready(id);
+                let pid = app.processes.get(id).and_then(|p| p.pid);
+                manager.run_hook(id, Hook::Ready, pid, None);
                 let name = app
                     .processes
                     .get(id)
                     .map(|p| p.spec.name.as_str())
                     .unwrap_or("process");
+                if let Some(notifier) = notifier.as_mut() {
+                    notifier.notify(NotifyEvent::Ready, name, "ready");
+                }
                 app.set_status_message(format!("{} ready", name));
                 emit_tool_message(
                     id,
@@ -319,6 +606,10 @@ async fn main() -> Result<()> {
                 );
             }
             Event::ProcessOutput { id, line, stream } => {
+                if !log_followers.is_empty() {
+                    let log_line = LogLine { text: line.clone(), stream };
+                    control::forward_line(&mut log_followers, id, &log_line);
+                }
                 let line_for_output = line.clone();
                 app.on_process_output(id, line, stream);
                 if settings.no_ui {
@@ -331,8 +622,15 @@ async fn main() -> Result<()> {
                     output_state.log_event(id, &line_for_output, &app, &settings);
                 }
             }
-            Event::ProcessExited { id, code } => {
-                app.on_process_exited(id, code);
+            Event::ProcessExited {
+                id,
+                code,
+                signal,
+                stopped,
+            } => {
+                app.on_process_exited(id, code, signal, stopped);
+                let pid = app.processes.get(id).and_then(|p| p.pid);
+                manager.run_hook(id, Hook::Exit, pid, code);
                 let name = app
                     .processes
                     .get(id)
@@ -343,19 +641,34 @@ async fn main() -> Result<()> {
                         .map(|at| at.elapsed() < MIN_SIGNAL_DISPLAY)
                         .unwrap_or(false);
                     if !signal_recent {
-                        let message = match code {
-                            Some(0) => format!("{} exited successfully", name),
-                            Some(code) => format!("{} exited with code {}", name, code),
-                            None => format!("{} exited", name),
+                        let message = match (code, signal) {
+                            (Some(0), _) => format!("{} exited successfully", name),
+                            (Some(code), _) => format!("{} exited with code {}", name, code),
+                            (None, Some(signal)) => {
+                                format!("{} killed by {}", name, events::signal_name(signal))
+                            }
+                            (None, None) => format!("{} exited", name),
                         };
                         app.set_status_message(message);
                     }
                 }
-                let line = match code {
-                    Some(0) => "process ended successfully".to_string(),
-                    Some(code) => format!("process ended with code {}", code),
-                    None => "process ended".to_string(),
+                let line = match (code, signal) {
+                    (Some(0), _) => "process ended successfully".to_string(),
+                    (Some(code), _) => format!("process ended with code {}", code),
+                    (None, Some(signal)) => {
+                        format!("process killed by {}", events::signal_name(signal))
+                    }
+                    (None, None) => "process ended".to_string(),
                 };
+                if let (Some(notifier), Some(process)) =
+                    (notifier.as_mut(), app.processes.get(id))
+                {
+                    let failed = (!stopped || process.stopped_as_failed())
+                        && !process.exit_succeeded(code);
+                    if failed && !shutdown_in_progress {
+                        notifier.notify(NotifyEvent::Failure, &process.spec.name, &line);
+                    }
+                }
                 emit_tool_message(id, line, &mut app, &settings, &mut output_state);
                 let restart_info = if shutdown_in_progress {
                     None
@@ -370,6 +683,15 @@ async fn main() -> Result<()> {
                     )
                 };
                 if let Some(info) = restart_info {
+                    output_state.print_record(
+                        Some(id),
+                        "restart_scheduled",
+                        serde_json::json!({
+                            "attempt": info.attempt,
+                            "max": info.max,
+                            "delay_ms": info.delay.as_millis() as u64,
+                        }),
+                    );
                     emit_tool_message(
                         id,
                         format_restart_message(&info),
@@ -379,7 +701,8 @@ async fn main() -> Result<()> {
                     );
                 }
                 if shutdown_in_progress {
-                    output_state.handle_exit(id, code);
+                    let succeeded = app.processes.get(id).is_some_and(|p| p.exit_succeeded(code));
+                    output_state.handle_exit(id, code, succeeded);
                     let ready_to_exit = output_state.all_exited()
                         && shutdown_started_at
                             .map(|start| start.elapsed() >= MIN_SHUTDOWN_DISPLAY)
@@ -398,7 +721,124 @@ async fn main() -> Result<()> {
                         &mut result,
                     )
                     .await;
+                    let stopped_as_failed =
+                        app.processes.get(id).is_some_and(|p| p.stopped_as_failed());
+                    if (!stopped || stopped_as_failed) && !app.should_quit {
+                        handle_on_exit(
+                            id,
+                            code,
+                            &mut app,
+                            &settings,
+                            &mut output_state,
+                            &mut manager,
+                            &mut result,
+                        )
+                        .await;
+                    }
+                }
+            }
+            Event::Triggered { id, trigger, line } => {
+                let Some(process) = app.processes.get(id) else {
+                    continue;
+                };
+                let Some(trigger) = process.spec.triggers.get(trigger).cloned() else {
+                    continue;
+                };
+                if shutdown_in_progress {
+                    continue;
+                }
+                let name = process.spec.name.clone();
+                let matched = format!("trigger {:?} matched", trigger.pattern);
+                if let Some(notifier) = notifier.as_mut() {
+                    notifier.notify(NotifyEvent::Trigger, &name, &matched);
                 }
+                match trigger.action {
+                    TriggerAction::Restart(target) => {
+                        let (action, label) = match target {
+                            None => (AppAction::Restart(id), "restarting".to_string()),
+                            Some(target) => match app.process_id(&target) {
+                                Some(other) => {
+                                    (AppAction::Restart(other), format!("restarting {}", target))
+                                }
+                                None => {
+                                    let label = format!("restarting group {}", target);
+                                    (AppAction::RestartGroup(target), label)
+                                }
+                            },
+                        };
+                        emit_tool_message(
+                            id,
+                            format!("{}, {}", matched, label),
+                            &mut app,
+                            &settings,
+                            &mut output_state,
+                        );
+                        app.set_status_message(format!("{}: {}, {}", name, matched, label));
+                        handle_app_action(
+                            action,
+                            &mut app,
+                            &mut manager,
+                            &mut restart_attempts,
+                            &event_tx,
+                        )
+                        .await;
+                    }
+                    TriggerAction::Run(cmd) => {
+                        emit_tool_message(
+                            id,
+                            format!("{}, running: {}", matched, cmd),
+                            &mut app,
+                            &settings,
+                            &mut output_state,
+                        );
+                        manager.run_trigger_command(id, &cmd, &line);
+                    }
+                    TriggerAction::Fail => {
+                        app.on_process_trigger_failed(id, matched.clone());
+                        app.set_status_warning_for(
+                            format!("{} failed: {}", name, matched),
+                            Duration::from_secs(10),
+                        );
+                        emit_tool_message(
+                            id,
+                            format!("{}, stopping as failed", matched),
+                            &mut app,
+                            &settings,
+                            &mut output_state,
+                        );
+                        manager.begin_shutdown_process(id, ProcessSignal::SigInt).await;
+                    }
+                    TriggerAction::Warn(message) => {
+                        let text = message.unwrap_or_else(|| output::sanitize_text(&line, true));
+                        app.set_status_warning_for(
+                            format!("{}: {}", name, text),
+                            Duration::from_secs(10),
+                        );
+                        emit_tool_message(
+                            id,
+                            format!("{}: {}", matched, text),
+                            &mut app,
+                            &settings,
+                            &mut output_state,
+                        );
+                    }
+                }
+            }
+            Event::ProcessTimedOut { id } => {
+                app.on_process_timed_out(id);
+                let Some(process) = app.processes.get(id) else {
+                    continue;
+                };
+                let timeout_ms = process.spec.timeout_ms.unwrap_or(0);
+                let message = format!("{} timed out after {}ms", process.spec.name, timeout_ms);
+                app.set_status_warning_for(message, Duration::from_secs(5));
+                emit_tool_message(
+                    id,
+                    format!("process timed out after {}ms, stopping", timeout_ms),
+                    &mut app,
+                    &settings,
+                    &mut output_state,
+                );
             }
             Event::ProcessFailed { id, error } => {
                 let error_message = error.clone();
@@ -409,6 +849,10 @@ async fn main() -> Result<()> {
                     .map(|p| p.spec.name.as_str())
                     .unwrap_or("process");
                 if !shutdown_in_progress {
+                    if let Some(notifier) = notifier.as_mut() {
+                        let message = format!("failed: {}", error_message);
+                        notifier.notify(NotifyEvent::Failure, name, &message);
+                    }
                     let signal_recent = last_signal_at
                         .map(|at| at.elapsed() < MIN_SIGNAL_DISPLAY)
                         .unwrap_or(false);
@@ -424,7 +868,17 @@ async fn main() -> Result<()> {
                     &settings,
                     &mut output_state,
                 );
-                let restart_info = if shutdown_in_progress {
+                let port_conflict = port_conflicts.remove(&id);
+                if port_conflict && app.processes.get(id).is_some_and(|p| p.spec.restart_on_fail) {
+                    emit_tool_message(
+                        id,
+                        "not restarting until the port is free".to_string(),
+                        &mut app,
+                        &settings,
+                        &mut output_state,
+                    );
+                }
+                let restart_info = if shutdown_in_progress || port_conflict {
                     None
                 } else {
                     handle_restart(
@@ -437,6 +891,15 @@ async fn main() -> Result<()> {
                     )
                 };
                 if let Some(info) = restart_info {
+                    output_state.print_record(
+                        Some(id),
+                        "restart_scheduled",
+                        serde_json::json!({
+                            "attempt": info.attempt,
+                            "max": info.max,
+                            "delay_ms": info.delay.as_millis() as u64,
+                        }),
+                    );
                     emit_tool_message(
                         id,
                         format_restart_message(&info),
@@ -446,7 +909,7 @@ async fn main() -> Result<()> {
                     );
                 }
                 if shutdown_in_progress {
-                    output_state.handle_exit(id, Some(1));
+                    output_state.handle_exit(id, Some(1), false);
                     let ready_to_exit = output_state.all_exited()
                         && shutdown_started_at
                             .map(|start| start.elapsed() >= MIN_SHUTDOWN_DISPLAY)
@@ -465,6 +928,48 @@ async fn main() -> Result<()> {
                         &mut result,
                     )
                     .await;
+                    if !app.should_quit {
+                        handle_on_exit(
+                            id,
+                            Some(1),
+                            &mut app,
+                            &settings,
+                            &mut output_state,
+                            &mut manager,
+                            &mut result,
+                        )
+                        .await;
+                    }
+                }
+            }
+            Event::PortConflict { id, conflict } => {
+                port_conflicts.insert(id);
+                emit_tool_message(
+                    id,
+                    format!("{}; {}", conflict.describe(), conflict.hint()),
+                    &mut app,
+                    &settings,
+                    &mut output_state,
+                );
+                if let Some(owner) = conflict.leftover_owner().filter(|_| !settings.no_ui && !settings.headless && !shutdown_in_progress)
+                {
+                    let name = app
+                        .processes
+                        .get(id)
+                        .map(|p| p.spec.name.as_str())
+                        .unwrap_or("process");
+                    let prompt = format!(
+                        "port {} is held by leftover pid {} ({}); kill it and restart {}?",
+                        conflict.port, owner.pid, owner.command, name
+                    );
+                    app.request_confirm(
+                        prompt,
+                        AppAction::KillPortOwner {
+                            id,
+                            pid: owner.pid,
+                            port: conflict.port,
+                        },
+                    );
                 }
             }
             Event::ProcessSignal { id, signal } => {
@@ -560,7 +1065,21 @@ async fn main() -> Result<()> {
                     let _ = term.autoresize();
                 }
             }
+            Event::Control { request, reply } => {
+                handle_control(
+                    request,
+                    reply,
+                    &mut app,
+                    &mut manager,
+                    &mut restart_attempts,
+                    &event_tx,
+                    &mut log_followers,
+                    &mut attached,
+                )
+                .await;
+            }
         }
+        attached.sync(&app);
 
         if let Some(term) = terminal.as_mut() {
             if last_draw.elapsed() >= draw_interval {
@@ -581,9 +1100,47 @@ async fn main() -> Result<()> {
     }
 
     manager.shutdown_all().await;
+    let stragglers = reaper::terminate_stragglers(&mut tree_tracker).await;
     if let Some(term) = terminal {
         tui::restore_terminal(term)?;
     }
+    if let Some(path) = &layout_path {
+        if let Err(err) = panes::save_layout(path, &app.pane_layout()) {
+            eprintln!(
+                "{}",
+                format_tool_message(&format!("failed to save pane layout: {:#}", err), settings.use_symbols)
+            );
+        }
+    }
+    // Exits from the final shutdown never reach the loop; they still get their exit hooks.
+    while let Ok(event) = event_rx.try_recv() {
+        if let Event::ProcessExited { id, code, .. } = event {
+            let pid = app.processes.get(id).and_then(|p| p.pid);
+            manager.run_hook(id, Hook::Exit, pid, code);
+        }
+    }
+    manager.wait_for_hooks(HOOK_GRACE).await;
+    while let Ok(event) = output_rx.try_recv() {
+        output_state.record_event(&event);
+        if let Event::ProcessOutput { id, line, stream } = &event {
+            app.on_process_output(*id, line.clone(), *stream);
+            if settings.no_ui {
+                output_state.handle_event(&event, &app, &settings);
+            } else {
+                output_state.log_event(*id, line, &app, &settings);
+            }
+        }
+    }
+    for line in output_state.take_unfinished_ci_blocks() {
+        println!("{}", line);
+    }
+    for line in straggler_warnings(&stragglers, &app) {
+        eprintln!("{}", format_tool_message(&line, settings.use_symbols));
+    }
+    output_state.conclude(&app, &settings);
+    if settings.no_ui && !settings.raw && settings.output_mode != OutputMode::Json {
+        print_summary(&app, &output_state, settings.use_symbols);
+    }
     result
 }
 
@@ -674,6 +1231,27 @@ fn backoff_delay(attempt: u32, settings: &RunSettings) -> Duration {
     Duration::from_secs(delay.min(30))
 }
 
+/// The `[keys]` bindings and `[theme]` from the config file, for `piperack attach`.
+fn load_tui_config(cli: &Cli) -> Result<(KeyMap, Theme)> {
+    let config_path = if cli.no_config {
+        None
+    } else {
+        cli.config
+            .clone()
+            .or_else(|| default_config_path().filter(|path| path.exists()))
+    };
+    let Some(path) = config_path else {
+        return Ok((KeyMap::default(), Theme::from_env()));
+    };
+    let config = config::load_config(&path)?;
+    let keys = match &config.keys {
+        Some(keys) => KeyMap::with_overrides(keys)?,
+        None => KeyMap::default(),
+    };
+    let theme = Theme::from_config(config.theme.as_ref(), theme::no_color_requested())?;
+    Ok((keys, theme))
+}
+
 fn load_specs(cli: &Cli) -> Result<(Vec<ProcessSpec>, RunSettings)> {
     let mut specs = Vec::new();
     let mut config_max_lines = None;
@@ -687,8 +1265,16 @@ fn load_specs(cli: &Cli) -> Result<(Vec<ProcessSpec>, RunSettings)> {
             let config = config::load_config(&path)?;
             config_max_lines = config.max_lines;
             config_meta = ConfigMeta::from_config(&config);
+            if let Some(keys) = &config.keys {
+                config_meta.keys = KeyMap::with_overrides(keys)?;
+            }
+            config_meta.theme = Some(Theme::from_config(
+                config.theme.as_ref(),
+                theme::no_color_requested(),
+            )?);
+            let named_ports = ports::resolve_named_ports(&config.processes)?;
             for process in config.processes {
-                specs.push(spec_from_config(process)?);
+                specs.push(spec_from_config(process, &named_ports)?);
             }
         }
     }
@@ -713,11 +1299,30 @@ fn load_specs(cli: &Cli) -> Result<(Vec<ProcessSpec>, RunSettings)> {
         }
     });
 
+    if let Some(timeout_ms) = cli.timeout_ms {
+        for spec in &mut specs {
+            spec.timeout_ms.get_or_insert(timeout_ms);
+        }
+    }
+
     ensure_unique_names(&specs)?;
+    validate_trigger_targets(&specs)?;
     let settings = RunSettings::from_cli(cli, config_meta, config_max_lines);
+    if let SuccessPolicy::Process(name) = &settings.success {
+        if !specs.iter().any(|spec| &spec.name == name) {
+            bail!("success policy refers to unknown process {:?}", name);
+        }
+    }
     Ok((specs, settings))
 }
 
+fn socket_path(cli: &Cli) -> Result<PathBuf> {
+    match &cli.socket {
+        Some(path) => Ok(path.clone()),
+        None => control::default_socket_path(Path::new(".")),
+    }
+}
+
 fn default_config_path() -> Option<PathBuf> {
     let path = Path::new("piperack.toml");
     if path.exists() {
@@ -727,33 +1332,184 @@ fn default_config_path() -> Option<PathBuf> {
     }
 }
 
-fn spec_from_config(config: ProcessConfig) -> Result<ProcessSpec> {
-    let mut parts = shell_words::split(&config.cmd)
+fn spec_from_config(config: ProcessConfig, named_ports: &NamedPorts) -> Result<ProcessSpec> {
+    let depends_on = config.depends_on.unwrap_or_default();
+    // A process may reference its own named ports and those of its dependencies.
+    let scope: Vec<&str> = std::iter::once(config.name.as_str())
+        .chain(depends_on.iter().map(|dep| dep.as_str()))
+        .collect();
+    let expand = |text: &str, field: &str| {
+        ports::expand_port_refs(text, &scope, named_ports)
+            .with_context(|| format!("invalid {} for {}", field, config.name))
+    };
+    let cmd_line = expand(&config.cmd, "cmd")?;
+    let mut parts = shell_words::split(&cmd_line)
         .with_context(|| format!("failed to parse cmd for {}", config.name))?;
     if parts.is_empty() {
         return Err(anyhow!("empty cmd for {}", config.name));
     }
     let cmd = parts.remove(0);
+    let pre_cmd = config
+        .pre_cmd
+        .as_deref()
+        .map(|pre_cmd| expand(pre_cmd, "pre_cmd"))
+        .transpose()?;
+    let hooks = config.hooks.unwrap_or_default();
+    let expand_hook = |hook: Option<String>, field: &str| {
+        hook.map(|cmd| expand(&cmd, &format!("hooks.{}", field)))
+            .transpose()
+    };
+    let hooks = HooksConfig {
+        start: expand_hook(hooks.start, "start")?,
+        ready: expand_hook(hooks.ready, "ready")?,
+        exit: expand_hook(hooks.exit, "exit")?,
+        restart: expand_hook(hooks.restart, "restart")?,
+    };
+    let triggers = config
+        .triggers
+        .unwrap_or_default()
+        .into_iter()
+        .map(|trigger| trigger_from_config(&config.name, trigger, expand))
+        .collect::<Result<Vec<_>>>()?;
+
+    let own_ports = named_ports.get(&config.name);
+    // Named ports are exported as `PORT_<NAME>`; explicit `env` entries take precedence.
+    let mut env: HashMap<String, String> = own_ports
+        .into_iter()
+        .flatten()
+        .map(|(key, port)| (ports::port_env_var(key), port.to_string()))
+        .collect();
+    for (key, value) in config.env.unwrap_or_default() {
+        let value = expand(&value, "env")?;
+        env.insert(key, value);
+    }
+
+    let ready_check = match config.ready_check {
+        Some(ReadyCheckConfig::Tcp(PortRef::Number(port))) => Some(ReadinessCheck::Tcp(port)),
+        Some(ReadyCheckConfig::Tcp(PortRef::Name(key))) => {
+            let port = own_ports.and_then(|ports| ports.get(&key)).with_context(|| {
+                format!("ready_check for {} refers to unknown port {}", config.name, key)
+            })?;
+            Some(ReadinessCheck::Tcp(*port))
+        }
+        Some(ReadyCheckConfig::Delay(ms)) => Some(ReadinessCheck::Delay(ms)),
+        Some(ReadyCheckConfig::Log(pattern)) => Some(ReadinessCheck::Log(pattern)),
+        None => None,
+    };
+    let mut ports = match config.ports {
+        Some(PortsConfig::List(ports)) => ports,
+        Some(PortsConfig::Named(_)) => own_ports.into_iter().flatten().map(|(_, port)| *port).collect(),
+        None => Vec::new(),
+    };
+    if let Some(ReadinessCheck::Tcp(port)) = &ready_check {
+        if !ports.contains(port) {
+            ports.push(*port);
+        }
+    }
+    let success_codes = config.success_codes.unwrap_or_else(|| vec![0]);
+    if success_codes.is_empty() {
+        bail!("process {}: success_codes must not be empty", config.name);
+    }
+    let restart_on_fail = config.restart_on_fail.unwrap_or(false);
+    let on_exit = match config.on_exit {
+        Some(on_exit) if restart_on_fail && on_exit != OnExit::Restart => bail!(
+            "process {}: restart_on_fail conflicts with on_exit = {:?}",
+            config.name,
+            on_exit.as_str()
+        ),
+        on_exit => on_exit,
+    };
     Ok(ProcessSpec {
         name: config.name,
         cmd,
         args: parts,
         cwd: config.cwd,
         color: config.color,
-        env: config.env.unwrap_or_default(),
-        restart_on_fail: config.restart_on_fail.unwrap_or(false),
+        env,
+        restart_on_fail: restart_on_fail || on_exit == Some(OnExit::Restart),
+        on_exit,
+        success_codes,
+        success_on_stop: config.success_on_stop.unwrap_or(false),
+        timeout_ms: config.timeout_ms,
+        hooks,
         follow: config.follow.unwrap_or(true),
-        pre_cmd: config.pre_cmd,
+        pre_cmd,
         watch_paths: config.watch.unwrap_or_default(),
         watch_ignore: config.watch_ignore.unwrap_or_default(),
         watch_ignore_gitignore: config.watch_ignore_gitignore.unwrap_or(false),
         watch_debounce_ms: config.watch_debounce_ms.unwrap_or(200),
-        depends_on: config.depends_on.unwrap_or_default(),
-        ready_check: config.ready_check,
+        depends_on,
+        ready_check,
+        triggers,
+        ports,
         tags: config.tags.unwrap_or_default(),
     })
 }
 
+fn trigger_from_config(
+    name: &str,
+    trigger: TriggerConfig,
+    expand: impl Fn(&str, &str) -> Result<String>,
+) -> Result<Trigger> {
+    regex::Regex::new(&trigger.pattern)
+        .with_context(|| format!("process {}: invalid trigger pattern {:?}", name, trigger.pattern))?;
+    let fields = [
+        ("target", trigger.target.is_some(), TriggerKind::Restart),
+        ("cmd", trigger.cmd.is_some(), TriggerKind::Run),
+        ("message", trigger.message.is_some(), TriggerKind::Warn),
+    ];
+    for (field, set, kind) in fields {
+        if set && trigger.action != kind {
+            bail!(
+                "process {}: trigger {:?} sets {}, which only applies to action = {:?}",
+                name,
+                trigger.pattern,
+                field,
+                kind.as_str()
+            );
+        }
+    }
+    let action = match trigger.action {
+        TriggerKind::Restart => TriggerAction::Restart(trigger.target),
+        TriggerKind::Run => {
+            let cmd = trigger.cmd.with_context(|| {
+                format!("process {}: trigger {:?} needs a cmd", name, trigger.pattern)
+            })?;
+            TriggerAction::Run(expand(&cmd, "trigger cmd")?)
+        }
+        TriggerKind::Fail => TriggerAction::Fail,
+        TriggerKind::Warn => TriggerAction::Warn(trigger.message),
+    };
+    Ok(Trigger {
+        pattern: trigger.pattern,
+        action,
+    })
+}
+
+/// Checks that `restart` triggers name an existing process or tag.
+fn validate_trigger_targets(specs: &[ProcessSpec]) -> Result<()> {
+    for spec in specs {
+        for trigger in &spec.triggers {
+            let TriggerAction::Restart(Some(target)) = &trigger.action else {
+                continue;
+            };
+            let known = target == "all"
+                || specs
+                    .iter()
+                    .any(|other| &other.name == target || other.tags.contains(target));
+            if !known {
+                bail!(
+                    "process {}: trigger {:?} restarts unknown process or tag {:?}",
+                    spec.name,
+                    trigger.pattern,
+                    target
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
 fn parse_cli_processes(args: &[String], restart_on_fail: bool) -> Result<Vec<ProcessSpec>> {
     let mut specs = Vec::new();
     let mut idx = 0;
@@ -880,6 +1636,11 @@ fn parse_cli_processes(args: &[String], restart_on_fail: bool) -> Result<Vec<Pro
             color,
             env,
             restart_on_fail: restart_on_fail_local,
+            on_exit: None,
+            success_codes: vec![0],
+            success_on_stop: false,
+            timeout_ms: None,
+            hooks: Default::default(),
             follow,
             pre_cmd,
             watch_paths,
@@ -888,6 +1649,8 @@ fn parse_cli_processes(args: &[String], restart_on_fail: bool) -> Result<Vec<Pro
             watch_debounce_ms,
             depends_on: Vec::new(),
             ready_check: None,
+            triggers: Vec::new(),
+            ports: Vec::new(),
             tags: Vec::new(),
         });
     }
@@ -944,6 +1707,11 @@ struct ConfigMeta {
     shutdown_sigterm_ms: Option<u64>,
     handle_input: Option<bool>,
     log_file: Option<String>,
+    http_port: Option<u16>,
+    metrics_port: Option<u16>,
+    notify: Option<config::NotifyConfig>,
+    keys: KeyMap,
+    theme: Option<Theme>,
 }
 
 impl ConfigMeta {
@@ -971,6 +1739,11 @@ impl ConfigMeta {
             shutdown_sigterm_ms: config.shutdown_sigterm_ms,
             handle_input: config.handle_input,
             log_file: config.log_file.clone(),
+            http_port: config.http_port,
+            metrics_port: config.metrics_port,
+            notify: config.notify.clone(),
+            keys: KeyMap::default(),
+            theme: None,
         }
     }
 }
@@ -982,6 +1755,8 @@ struct RunSettings {
     max_lines: usize,
     use_symbols: bool,
     no_ui: bool,
+    /// Running as a background supervisor: no terminal, input comes from attached clients.
+    headless: bool,
     raw: bool,
     prefix: Option<String>,
     prefix_length: Option<usize>,
@@ -997,6 +1772,12 @@ struct RunSettings {
     shutdown_sigterm_ms: u64,
     input_enabled: bool,
     log_file: Option<String>,
+    reports: Vec<ReportTarget>,
+    http_port: Option<u16>,
+    metrics_port: Option<u16>,
+    notify: Option<NotifySettings>,
+    keys: KeyMap,
+    theme: Theme,
 }
 
 impl RunSettings {
@@ -1022,8 +1803,14 @@ impl RunSettings {
         } else {
             meta.timestamp.unwrap_or(false)
         };
-        let output_mode = cli.output.or(meta.output).unwrap_or(OutputMode::Combined);
-        let success = cli.success.or(meta.success).unwrap_or(SuccessPolicy::Last);
+        let output_mode = cli.output.or(meta.output).unwrap_or_else(|| {
+            if CiProvider::detect().is_some() {
+                OutputMode::Ci
+            } else {
+                OutputMode::Combined
+            }
+        });
+        let success = cli.success.clone().or(meta.success).unwrap_or(SuccessPolicy::Last);
         let kill_others = cli.kill_others || meta.kill_others.unwrap_or(false);
         let kill_others_on_fail =
             cli.kill_others_on_fail || meta.kill_others_on_fail.unwrap_or(false);
@@ -1043,10 +1830,17 @@ impl RunSettings {
             meta.handle_input.unwrap_or(true)
         };
         let log_file = cli.log_file.clone().or(meta.log_file);
+        let http_port = cli.http_port.or(meta.http_port);
+        let metrics_port = cli.metrics_port.or(meta.metrics_port);
+        let notify = notify_settings(cli, meta.notify);
         Self {
             max_lines,
             use_symbols,
-            no_ui: cli.no_ui,
+            // JSON records and CI blocks go to stdout, which the TUI would otherwise own.
+            no_ui: cli.no_ui
+                || cli.output == Some(OutputMode::Json)
+                || (output_mode == OutputMode::Ci && !cli.headless),
+            headless: cli.headless,
             raw,
             prefix,
             prefix_length,
@@ -1062,15 +1856,47 @@ impl RunSettings {
             shutdown_sigterm_ms,
             input_enabled,
             log_file,
+            reports: cli.report.clone(),
+            http_port,
+            metrics_port,
+            notify,
+            keys: meta.keys,
+            theme: meta.theme.unwrap_or_else(Theme::from_env),
         }
     }
 }
 
+/// Notifications are on when `[notify]` is configured or a `--notify*` flag is given.
+fn notify_settings(cli: &Cli, config: Option<config::NotifyConfig>) -> Option<NotifySettings> {
+    if config.is_none() && cli.notify.is_empty() && cli.notify_on.is_empty() {
+        return None;
+    }
+    let config = config.unwrap_or_default();
+    let events = Some(cli.notify_on.clone())
+        .filter(|events| !events.is_empty())
+        .or(config.events)
+        .unwrap_or_else(|| vec![NotifyEvent::Failure]);
+    let methods = Some(cli.notify.clone())
+        .filter(|methods| !methods.is_empty())
+        .or(config.methods)
+        .unwrap_or_else(|| vec![NotifyMethod::Bell]);
+    let min_interval_ms = config
+        .min_interval_ms
+        .unwrap_or(notify::DEFAULT_MIN_INTERVAL_MS);
+    Some(NotifySettings {
+        events,
+        methods,
+        min_interval: Duration::from_millis(min_interval_ms),
+    })
+}
+
 fn parse_output_mode(value: &str) -> Result<OutputMode> {
     match value.to_lowercase().as_str() {
         "combined" => Ok(OutputMode::Combined),
         "grouped" => Ok(OutputMode::Grouped),
         "raw" => Ok(OutputMode::Raw),
+        "json" => Ok(OutputMode::Json),
+        "ci" => Ok(OutputMode::Ci),
         _ => Err(anyhow!("invalid output mode: {}", value)),
     }
 }
@@ -1080,7 +1906,12 @@ fn parse_success_policy(value: &str) -> Result<SuccessPolicy> {
         "first" => Ok(SuccessPolicy::First),
         "last" => Ok(SuccessPolicy::Last),
         "all" => Ok(SuccessPolicy::All),
-        _ => Err(anyhow!("invalid success policy: {}", value)),
+        _ => match value.split_once(':') {
+            Some((kind, name)) if kind.eq_ignore_ascii_case("process") && !name.is_empty() => {
+                Ok(SuccessPolicy::Process(name.to_string()))
+            }
+            _ => Err(anyhow!("invalid success policy: {}", value)),
+        },
     }
 }
 
@@ -1156,6 +1987,11 @@ fn parse_named_commands(cli: &Cli) -> Result<Vec<ProcessSpec>> {
             color,
             env: env_maps[idx].clone(),
             restart_on_fail: cli.restart_on_fail,
+            on_exit: None,
+            success_codes: vec![0],
+            success_on_stop: false,
+            timeout_ms: None,
+            hooks: Default::default(),
             follow: true,
             pre_cmd: pre_cmds.get(idx).cloned().unwrap_or(None),
             watch_paths: Vec::new(),
@@ -1164,6 +2000,8 @@ fn parse_named_commands(cli: &Cli) -> Result<Vec<ProcessSpec>> {
             watch_debounce_ms: 200,
             depends_on: Vec::new(),
             ready_check: None,
+            triggers: Vec::new(),
+            ports: Vec::new(),
             tags: Vec::new(),
         });
     }
@@ -1199,11 +2037,20 @@ struct OutputState {
     timestamp: bool,
     start: std::time::Instant,
     grouped: Vec<Vec<String>>,
+    ci: Option<CiProvider>,
+    annotations: Vec<Vec<String>>,
     logs: Vec<Option<std::io::BufWriter<std::fs::File>>>,
     names: Vec<String>,
     exit_codes: Vec<Option<i32>>,
+    /// Whether each process's last exit counted as success (see `ProcessState::exit_succeeded`).
+    succeeded: Vec<bool>,
+    /// Whether each process's last failure is ignored by its explicit `on_exit = "ignore"`.
+    tolerated: Vec<bool>,
     exited: Vec<bool>,
-    last_exit: Option<(usize, Option<i32>)>,
+    exited_at: Vec<Option<Instant>>,
+    last_exit: Option<usize>,
+    reports: Vec<ReportTarget>,
+    concluded: bool,
 }
 
 impl OutputState {
@@ -1223,20 +2070,37 @@ impl OutputState {
             timestamp: settings.timestamp,
             start: std::time::Instant::now(),
             grouped,
+            ci: (settings.output_mode == OutputMode::Ci)
+                .then(|| CiProvider::detect().unwrap_or(CiProvider::GitHub)),
+            annotations: vec![Vec::new(); processes.len()],
             logs,
             names,
             exit_codes: vec![None; processes.len()],
+            succeeded: vec![false; processes.len()],
+            tolerated: vec![false; processes.len()],
             exited: vec![false; processes.len()],
+            exited_at: vec![None; processes.len()],
             last_exit: None,
+            reports: settings.reports.clone(),
+            concluded: false,
         }
     }
 
     fn handle_event(&mut self, event: &Event, app: &App, settings: &RunSettings) {
-        if let Event::ProcessOutput { id, line, .. } = event {
+        if let Event::ProcessOutput { id, line, stream } = event {
             // Non-TUI output path: format + log each line as it arrives.
             let output = self.format_line(*id, line, app, settings);
             self.write_line(*id, &output);
-            if self.output_mode == OutputMode::Grouped {
+            if let Some(ci) = self.ci {
+                if *stream == StreamKind::Stderr {
+                    if let Some(annotation) = ci.annotation(&output::sanitize_text(line, true)) {
+                        self.annotations[*id].push(annotation);
+                    }
+                }
+                self.grouped[*id].push(output);
+            } else if self.output_mode == OutputMode::Json {
+                // Printed by `record_event`.
+            } else if self.output_mode == OutputMode::Grouped {
                 self.grouped[*id].push(output);
             } else if self.output_mode != OutputMode::Raw {
                 println!("{}", output);
@@ -1246,19 +2110,43 @@ impl OutputState {
         }
     }
 
+    /// Prints `event` as an NDJSON record in `--output json` mode.
+    fn record_event(&self, event: &Event) {
+        if self.output_mode != OutputMode::Json {
+            return;
+        }
+        if let Some((id, kind, fields)) = event_record(event) {
+            self.print_record(id, kind, fields);
+        }
+    }
+
+    fn print_record(&self, id: Option<usize>, kind: &str, fields: serde_json::Value) {
+        if self.output_mode != OutputMode::Json {
+            return;
+        }
+        println!("{}", json_record(id, id.and_then(|id| self.names.get(id)), kind, fields));
+    }
+
     fn log_event(&mut self, id: usize, line: &str, app: &App, settings: &RunSettings) {
         let output = self.format_line(id, line, app, settings);
         self.write_line(id, &output);
     }
 
-    fn handle_exit(&mut self, id: usize, code: Option<i32>) {
+    fn handle_exit(&mut self, id: usize, code: Option<i32>, succeeded: bool) {
         if id >= self.exit_codes.len() {
             return;
         }
         self.exit_codes[id] = code;
+        self.succeeded[id] = succeeded;
         self.exited[id] = true;
-        self.last_exit = Some((id, code));
-        if self.output_mode == OutputMode::Grouped {
+        self.exited_at[id] = Some(Instant::now());
+        self.last_exit = Some(id);
+        if let Some(ci) = self.ci {
+            let name = self.names.get(id).cloned().unwrap_or_else(|| "process".to_string());
+            for line in self.take_ci_block(ci, id, &name) {
+                println!("{}", line);
+            }
+        } else if self.output_mode == OutputMode::Grouped {
             if let Some(process) = self.grouped.get(id) {
                 if !process.is_empty() {
                     let name = self.names.get(id).map(String::as_str).unwrap_or("process");
@@ -1272,12 +2160,125 @@ impl OutputState {
         }
     }
 
+    /// Drains the CI block buffered for `id`, folded under `title`.
+    fn take_ci_block(&mut self, ci: CiProvider, id: usize, title: &str) -> Vec<String> {
+        // Each run gets its own block; annotations stay outside so they are not folded.
+        let mut lines = vec![ci.group_start(title)];
+        lines.append(&mut self.grouped[id]);
+        lines.push(ci.group_end(title));
+        lines.append(&mut self.annotations[id]);
+        lines
+    }
+
+    /// Drains the CI blocks of processes whose output never reached an exit, e.g. servers
+    /// still running when `--success first` or `kill_others` ended the run.
+    fn take_unfinished_ci_blocks(&mut self) -> Vec<String> {
+        let Some(ci) = self.ci else {
+            return Vec::new();
+        };
+        let mut lines = Vec::new();
+        for id in 0..self.grouped.len() {
+            if self.grouped[id].is_empty() && self.annotations[id].is_empty() {
+                continue;
+            }
+            let name = self.names.get(id).map(String::as_str).unwrap_or("process");
+            let title = format!("{} (still running)", name);
+            lines.extend(self.take_ci_block(ci, id, &title));
+        }
+        lines
+    }
+
+    /// Outcome of every process so far.
+    fn results(&self, app: &App) -> Vec<ProcessResult> {
+        app.processes
+            .iter()
+            .enumerate()
+            .map(|(id, process)| {
+                let exited = self.exited.get(id).copied().unwrap_or(false);
+                let exit_code = self.exit_codes.get(id).copied().flatten();
+                let succeeded = self.succeeded.get(id).copied().unwrap_or(false);
+                let outcome = match (&process.status, exit_code) {
+                    _ if !exited => Outcome::NotFinished,
+                    (ProcessStatus::Failed { error }, _) => Outcome::Failed(error.clone()),
+                    _ if process.timed_out => Outcome::TimedOut(Duration::from_millis(
+                        process.spec.timeout_ms.unwrap_or(0),
+                    )),
+                    _ if process.trigger_failure.is_some() => {
+                        Outcome::Failed(process.trigger_failure.clone().unwrap_or_default())
+                    }
+                    _ if succeeded => Outcome::Passed,
+                    (_, Some(code)) => Outcome::Failed(format!("exit code {}", code)),
+                    (_, None) => Outcome::Failed("killed by a signal".to_string()),
+                };
+                let end = self.exited_at.get(id).copied().flatten().filter(|_| exited);
+                let duration = process
+                    .started_at
+                    .map(|start| end.unwrap_or_else(Instant::now).saturating_duration_since(start));
+                let stderr_tail = if outcome.failure().is_some() {
+                    let mut tail: Vec<String> = process
+                        .logs
+                        .iter()
+                        .rev()
+                        .filter(|line| line.stream == StreamKind::Stderr)
+                        .take(report::STDERR_TAIL_LINES)
+                        .map(|line| output::sanitize_text(&line.text, true))
+                        .collect();
+                    tail.reverse();
+                    tail
+                } else {
+                    Vec::new()
+                };
+                ProcessResult {
+                    name: process.spec.name.clone(),
+                    outcome,
+                    exit_code,
+                    duration,
+                    restarts: process.restarts(),
+                    stderr_tail,
+                }
+            })
+            .collect()
+    }
+
+    /// Writes the `--report` files once the run has concluded (only the first call counts).
+    fn conclude(&mut self, app: &App, settings: &RunSettings) {
+        if self.concluded {
+            return;
+        }
+        self.concluded = true;
+        if self.reports.is_empty() {
+            return;
+        }
+        let results = self.results(app);
+        for target in &self.reports {
+            if let Err(err) = target.write(&results) {
+                let message = format!("report failed: {:#}", err);
+                eprintln!("{}", format_tool_message(&message, settings.use_symbols));
+            }
+        }
+    }
+
     fn all_exited(&self) -> bool {
         self.exited.iter().all(|v| *v)
     }
 
+    fn tolerate(&mut self, id: usize, tolerated: bool) {
+        if let Some(slot) = self.tolerated.get_mut(id) {
+            *slot = tolerated;
+        }
+    }
+
+    fn failed(&self, id: usize) -> bool {
+        !self.succeeded.get(id).copied().unwrap_or(false)
+            && !self.tolerated.get(id).copied().unwrap_or(false)
+    }
+
     fn any_failed(&self) -> bool {
-        self.exit_codes.iter().any(|code| code.unwrap_or(1) != 0)
+        (0..self.succeeded.len()).any(|id| self.failed(id))
+    }
+
+    fn last_exit_failed(&self) -> bool {
+        self.last_exit.is_some_and(|id| self.failed(id))
     }
 
     fn format_line(&self, id: usize, line: &str, app: &App, settings: &RunSettings) -> String {
@@ -1332,6 +2333,86 @@ impl OutputState {
     }
 }
 
+/// Maps an event to its NDJSON record: process ID, event name and extra fields.
+fn event_record(event: &Event) -> Option<(Option<usize>, &'static str, serde_json::Value)> {
+    use serde_json::json;
+
+    let record = match event {
+        Event::ProcessStarting { id } => (Some(*id), "starting", json!({})),
+        Event::ProcessStarted { id, pid } => (Some(*id), "started", json!({ "pid": pid })),
+        Event::ProcessReady { id } => (Some(*id), "ready", json!({})),
+        Event::ProcessWaiting { id, deps } => (Some(*id), "waiting", json!({ "deps": deps })),
+        Event::ProcessOutput { id, line, stream } => {
+            let stream = match stream {
+                StreamKind::Stdout => "stdout",
+                StreamKind::Stderr => "stderr",
+            };
+            (Some(*id), "output", json!({ "stream": stream, "line": line }))
+        }
+        Event::ProcessExited {
+            id,
+            code,
+            signal,
+            stopped,
+        } => (
+            Some(*id),
+            "exited",
+            json!({
+                "code": code,
+                "signal": signal.map(events::signal_name),
+                "stopped": stopped,
+            }),
+        ),
+        Event::ProcessTimedOut { id } => (Some(*id), "timed_out", json!({})),
+        Event::Triggered { id, trigger, line } => (
+            Some(*id),
+            "trigger",
+            json!({ "trigger": trigger, "line": line }),
+        ),
+        Event::ProcessFailed { id, error } => (Some(*id), "failed", json!({ "error": error })),
+        Event::PortConflict { id, conflict } => (
+            Some(*id),
+            "port_conflict",
+            json!({ "port": conflict.port, "message": conflict.describe() }),
+        ),
+        Event::ProcessSignal { id, signal } => {
+            (Some(*id), "signal", json!({ "signal": signal.label() }))
+        }
+        Event::Restart { id } => (Some(*id), "restart", json!({})),
+        Event::Shutdown { signal } => (None, "shutdown", json!({ "signal": signal.label() })),
+        _ => return None,
+    };
+    Some(record)
+}
+
+fn json_record(
+    id: Option<usize>,
+    name: Option<&String>,
+    kind: &str,
+    fields: serde_json::Value,
+) -> serde_json::Value {
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let mut record = serde_json::json!({
+        "event": kind,
+        "timestamp_ms": timestamp_ms,
+    });
+    if let Some(record) = record.as_object_mut() {
+        if let Some(id) = id {
+            record.insert("id".to_string(), id.into());
+        }
+        if let Some(name) = name {
+            record.insert("process".to_string(), name.as_str().into());
+        }
+        if let serde_json::Value::Object(fields) = fields {
+            record.extend(fields);
+        }
+    }
+    record
+}
+
 fn init_log_writers(
     processes: &[ProcessState],
     template: Option<&str>,
@@ -1354,16 +2435,97 @@ fn init_log_writers(
     writers
 }
 
-fn log_timestamp() -> String {
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-    now.as_secs().to_string()
+fn straggler_warnings(stragglers: &[Straggler], app: &App) -> Vec<String> {
+    // Descendants that outlived their process; SIGKILLed ones are called out separately.
+    let describe = |list: Vec<&Straggler>| {
+        list.iter()
+            .map(|s| {
+                let name = app
+                    .processes
+                    .get(s.id)
+                    .map(|p| p.spec.name.as_str())
+                    .unwrap_or("process");
+                format!("{} {} ({})", name, s.pid, s.comm)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let (forced, stopped): (Vec<&Straggler>, Vec<&Straggler>) =
+        stragglers.iter().partition(|s| s.force_killed);
+    let mut lines = Vec::new();
+    if !stopped.is_empty() {
+        lines.push(format!("stopped leftover processes: {}", describe(stopped)));
+    }
+    if !forced.is_empty() {
+        lines.push(format!(
+            "warning: force-killed leftover processes: {}",
+            describe(forced)
+        ));
+    }
+    lines
 }
 
-fn render_template(template: &str, name: &str, index: usize, time: &str) -> String {
-    // Simple token replacement for log/prefix templates.
-    template
+fn print_summary(app: &App, output_state: &OutputState, use_symbols: bool) {
+    // Exit summary for non-TUI mode: one row per process, then stderr tails of failures.
+    println!("{}", format_tool_message("summary", use_symbols));
+    for line in summary_lines(app, output_state) {
+        println!("{}", format_tool_message(&line, use_symbols));
+    }
+}
+
+fn summary_lines(app: &App, output_state: &OutputState) -> Vec<String> {
+    let width = app
+        .processes
+        .iter()
+        .map(|p| p.spec.name.len())
+        .max()
+        .unwrap_or(0);
+    let results = output_state.results(app);
+    let mut lines = Vec::new();
+    for (process, result) in app.processes.iter().zip(&results) {
+        let mut line = format!(
+            "  {:<width$}  {:<8} {:>7}  {} restarts",
+            result.name,
+            result.exit_label(),
+            result.duration_label(),
+            result.restarts,
+            width = width,
+        );
+        let stats = &process.stats;
+        if stats.has_samples() {
+            line.push_str(&format!(
+                " | peak cpu {:.1}% | peak rss {} | max children {}",
+                stats.peak_cpu_percent,
+                stats::format_bytes(stats.peak_rss_bytes),
+                stats.peak_children,
+            ));
+        }
+        lines.push(line);
+    }
+    for result in &results {
+        if let Some(message) = result.outcome.failure() {
+            if result.stderr_tail.is_empty() {
+                continue;
+            }
+            lines.push(format!("  {} ({}), last stderr:", result.name, message));
+            for text in &result.stderr_tail {
+                lines.push(format!("    | {}", text));
+            }
+        }
+    }
+    lines
+}
+
+fn log_timestamp() -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    now.as_secs().to_string()
+}
+
+fn render_template(template: &str, name: &str, index: usize, time: &str) -> String {
+    // Simple token replacement for log/prefix templates.
+    template
         .replace("{name}", name)
         .replace("{index}", &index.to_string())
         .replace("{time}", time)
@@ -1509,7 +2671,11 @@ fn handle_restart(
         .get(id)
         .map(|process| process.spec.restart_on_fail)
         .unwrap_or(false);
-    if should_restart && code.unwrap_or(1) != 0 {
+    let succeeded = app
+        .processes
+        .get(id)
+        .is_some_and(|process| process.exit_succeeded(code));
+    if should_restart && !succeeded {
         let attempt = restart_attempts
             .entry(id)
             .and_modify(|a| *a += 1)
@@ -1531,7 +2697,7 @@ fn handle_restart(
                 delay: backoff,
             });
         }
-    } else if code.unwrap_or(1) == 0 {
+    } else if succeeded {
         restart_attempts.remove(&id);
     }
     None
@@ -1547,28 +2713,41 @@ async fn handle_exit_policy(
     result: &mut Result<()>,
 ) {
     // Apply success/kill policies after a process exits.
-    output_state.handle_exit(id, code);
+    let succeeded = app
+        .processes
+        .get(id)
+        .is_some_and(|process| process.exit_succeeded(code));
+    output_state.handle_exit(id, code, succeeded);
+    // An explicit `on_exit` decides what a failure of this process means, not the global flags.
+    let on_exit = app.processes.get(id).and_then(|process| process.spec.on_exit);
+    output_state.tolerate(id, !succeeded && on_exit == Some(OnExit::Ignore));
+    let kill_others = if succeeded {
+        settings.kill_others
+    } else {
+        on_exit.is_none() && (settings.kill_others || settings.kill_others_on_fail)
+    };
 
-    if settings.kill_others || (settings.kill_others_on_fail && code.unwrap_or(1) != 0) {
+    if kill_others {
+        output_state.conclude(app, settings);
         manager.shutdown_all().await;
         app.should_quit = true;
         return;
     }
 
-    match settings.success {
+    match &settings.success {
         SuccessPolicy::First => {
-            if code.unwrap_or(1) == 0 {
+            if succeeded {
+                output_state.conclude(app, settings);
                 manager.shutdown_all().await;
                 app.should_quit = true;
             }
         }
         SuccessPolicy::Last => {
             if output_state.all_exited() {
-                if let Some((_, last)) = output_state.last_exit {
-                    if last.unwrap_or(1) != 0 {
-                        *result = Err(anyhow!("last process failed"));
-                    }
+                if output_state.last_exit_failed() {
+                    *result = Err(anyhow!("last process failed"));
                 }
+                output_state.conclude(app, settings);
                 if settings.no_ui {
                     app.should_quit = true;
                 }
@@ -1579,14 +2758,98 @@ async fn handle_exit_policy(
                 if output_state.any_failed() {
                     *result = Err(anyhow!("one or more processes failed"));
                 }
+                output_state.conclude(app, settings);
                 if settings.no_ui {
                     app.should_quit = true;
                 }
             }
         }
+        SuccessPolicy::Process(name) => {
+            if app.processes.get(id).is_some_and(|process| &process.spec.name == name) {
+                if !succeeded {
+                    let signal = code
+                        .is_none()
+                        .then(|| app.processes.get(id).and_then(|p| p.exit_signal))
+                        .flatten();
+                    *result = Err(ProcessExit {
+                        name: name.clone(),
+                        code,
+                        signal,
+                    }
+                    .into());
+                }
+                output_state.conclude(app, settings);
+                manager.shutdown_all().await;
+                app.should_quit = true;
+            }
+        }
     }
 }
 
+/// Applies the `on_exit` action of a process that failed on its own.
+async fn handle_on_exit(
+    id: usize,
+    code: Option<i32>,
+    app: &mut App,
+    settings: &RunSettings,
+    output_state: &mut OutputState,
+    manager: &mut ProcessManager,
+    result: &mut Result<()>,
+) {
+    let Some(process) = app.processes.get(id) else {
+        return;
+    };
+    if process.exit_succeeded(code) {
+        return;
+    }
+    let name = process.spec.name.clone();
+    match process.spec.on_exit {
+        // Restarts are scheduled by `handle_restart`.
+        None | Some(OnExit::Ignore) | Some(OnExit::Restart) => {}
+        Some(OnExit::StopAll) => {
+            emit_tool_message(id, "stopping all processes".to_string(), app, settings, output_state);
+            if result.is_ok() {
+                *result = Err(anyhow!("{} failed, stopped all processes", name));
+            }
+            output_state.conclude(app, settings);
+            manager.shutdown_all().await;
+            app.should_quit = true;
+        }
+        Some(OnExit::StopDependents) => {
+            let dependents = transitive_dependents(&app.processes, id);
+            if dependents.is_empty() {
+                return;
+            }
+            let names: Vec<&str> = dependents
+                .iter()
+                .map(|dep| app.processes[*dep].spec.name.as_str())
+                .collect();
+            let line = format!("stopping dependents: {}", names.join(", "));
+            app.set_status_message(format!("{} failed, {}", name, line));
+            emit_tool_message(id, line, app, settings, output_state);
+            for dep in dependents {
+                manager.begin_shutdown_process(dep, ProcessSignal::SigInt).await;
+            }
+        }
+    }
+}
+
+/// Processes that depend on `id` directly or through other processes, in ID order.
+fn transitive_dependents(processes: &[ProcessState], id: usize) -> Vec<usize> {
+    let mut found = vec![false; processes.len()];
+    let mut queue = vec![id];
+    while let Some(current) = queue.pop() {
+        let name = &processes[current].spec.name;
+        for (other, process) in processes.iter().enumerate() {
+            if !found[other] && other != id && process.spec.depends_on.contains(name) {
+                found[other] = true;
+                queue.push(other);
+            }
+        }
+    }
+    (0..processes.len()).filter(|other| found[*other]).collect()
+}
+
 async fn handle_app_action(
     action: AppAction,
     app: &mut App,
@@ -1640,9 +2903,30 @@ async fn handle_app_action(
                 }
             }
         }
+        AppAction::KillGroup(tag) => {
+            let ids: Vec<usize> = app
+                .processes
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| tag == "all" || p.spec.tags.contains(&tag))
+                .map(|(id, _)| id)
+                .collect();
+            for id in ids {
+                manager
+                    .begin_shutdown_process(id, ProcessSignal::SigInt)
+                    .await;
+            }
+        }
+        AppAction::Signal(id, signal) => {
+            let name = app.processes.get(id).map(|p| p.spec.name.clone()).unwrap_or_default();
+            match manager.signal_process(id, signal) {
+                Ok(()) => app.set_status_message(format!("sent {} to {}", signal.label(), name)),
+                Err(err) => app.set_status_warning_for(format!("{:#}", err), Duration::from_secs(3)),
+            }
+        }
         AppAction::Export(id) => {
             if app.processes.get(id).is_some() {
-                if let Err(err) = app.export_selected_logs() {
+                if let Err(err) = app.export_logs(id) {
                     app.set_status_message(format!("Export failed: {}", err));
                 }
             }
@@ -1657,28 +2941,304 @@ async fn handle_app_action(
                 app.set_status_message(format!("Input failed: {}", err));
             }
         }
-        AppAction::CopySelection => {
-            let selection = app.selection_text();
-            let payload = selection.or_else(|| app.selected_process_raw_text());
-            if let Some(text) = payload {
-                match clipboard::copy_text(&text) {
-                    Ok(()) => app.set_status_warning_for("copied to clipboard", Duration::from_secs(2)),
-                    Err(err) => app.set_status_warning_for(
-                        format!("clipboard failed: {}", err),
-                        Duration::from_secs(3),
-                    ),
+        AppAction::CopySelection => copy_selection(app),
+        AppAction::KillPortOwner { id, pid, port } => {
+            if ports::free_port(pid, port).await {
+                app.set_status_message(format!("stopped pid {}, port {} is free", pid, port));
+                restart_attempts.remove(&id);
+                if let Err(err) = manager.restart_process(id).await {
+                    app.on_process_failed(id, err.to_string());
                 }
             } else {
-                app.set_status_warning_for("nothing to copy", Duration::from_secs(2));
+                app.set_status_warning_for(
+                    format!("port {} is still in use", port),
+                    Duration::from_secs(3),
+                );
             }
         }
         AppAction::None => {}
     }
 }
 
+fn copy_selection(app: &mut App) {
+    let selection = app.selection_text();
+    let payload = selection.or_else(|| app.selected_process_raw_text());
+    if let Some(text) = payload {
+        match clipboard::copy_text(&text) {
+            Ok(()) => app.set_status_warning_for("copied to clipboard", Duration::from_secs(2)),
+            Err(err) => app.set_status_warning_for(
+                format!("clipboard failed: {}", err),
+                Duration::from_secs(3),
+            ),
+        }
+    } else {
+        app.set_status_warning_for("nothing to copy", Duration::from_secs(2));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_control(
+    request: ControlRequest,
+    reply: mpsc::Sender<String>,
+    app: &mut App,
+    manager: &mut ProcessManager,
+    restart_attempts: &mut HashMap<usize, u32>,
+    event_tx: &mpsc::Sender<Event>,
+    log_followers: &mut Vec<LogFollower>,
+    attached: &mut AttachedClients,
+) {
+    let lookup = |app: &App, name: &str| {
+        app.process_id(name)
+            .ok_or_else(|| control::error_reply(format!("unknown process: {}", name)))
+    };
+    let (action, label) = match request {
+        ControlRequest::Status => {
+            let _ = reply
+                .send(control::ok_reply(control::status_json(&app.processes)))
+                .await;
+            return;
+        }
+        ControlRequest::Logs {
+            name,
+            follow,
+            lines,
+        } => {
+            let id = match lookup(app, &name) {
+                Ok(id) => id,
+                Err(err) => {
+                    let _ = reply.send(err).await;
+                    return;
+                }
+            };
+            let logs = &app.processes[id].logs;
+            let skip = lines.map(|n| logs.len().saturating_sub(n)).unwrap_or(0);
+            let buffered: Vec<String> = logs.iter().skip(skip).map(control::log_reply).collect();
+            if let Some(reply) = control::spawn_log_reply(reply, buffered, follow) {
+                log_followers.push(LogFollower { id, reply });
+            }
+            return;
+        }
+        ControlRequest::Attach => {
+            attached.attach(reply, app);
+            return;
+        }
+        ControlRequest::Metrics => {
+            let text = metrics::render(&app.processes, &manager.output_counters());
+            let _ = reply
+                .send(control::ok_reply(serde_json::json!({ "text": text })))
+                .await;
+            return;
+        }
+        ControlRequest::Shutdown => {
+            let _ = event_tx
+                .send(Event::Shutdown {
+                    signal: ProcessSignal::SigInt,
+                })
+                .await;
+            let _ = reply.send(control::ok_reply(serde_json::Value::Null)).await;
+            return;
+        }
+        ControlRequest::Export { name } => {
+            let result = lookup(app, &name).and_then(|id| {
+                app.export_logs(id)
+                    .map_err(|err| control::error_reply(format!("{:#}", err)))
+            });
+            let message = match result {
+                Ok(path) => control::ok_reply(serde_json::json!({ "path": path })),
+                Err(err) => err,
+            };
+            let _ = reply.send(message).await;
+            return;
+        }
+        ControlRequest::Restart { name } => match lookup(app, &name) {
+            Ok(id) => (AppAction::Restart(id), format!("restart {}", name)),
+            Err(err) => {
+                let _ = reply.send(err).await;
+                return;
+            }
+        },
+        ControlRequest::Kill { name } => match lookup(app, &name) {
+            Ok(id) => (AppAction::Kill(id), format!("kill {}", name)),
+            Err(err) => {
+                let _ = reply.send(err).await;
+                return;
+            }
+        },
+        ControlRequest::Signal { name, signal } => match lookup(app, &name) {
+            Ok(id) => (AppAction::Signal(id, signal), format!("{} to {}", signal.label(), name)),
+            Err(err) => {
+                let _ = reply.send(err).await;
+                return;
+            }
+        },
+        ControlRequest::Input { name, text } => match lookup(app, &name) {
+            Ok(id) => (AppAction::SendInputText(id, text), format!("input to {}", name)),
+            Err(err) => {
+                let _ = reply.send(err).await;
+                return;
+            }
+        },
+        ControlRequest::InputBytes { name, bytes } => match lookup(app, &name) {
+            Ok(id) => (AppAction::SendInputBytes(id, bytes), format!("input to {}", name)),
+            Err(err) => {
+                let _ = reply.send(err).await;
+                return;
+            }
+        },
+        ControlRequest::RestartGroup { group } => {
+            if group != "all" && !app.processes.iter().any(|p| p.spec.tags.contains(&group)) {
+                let _ = reply
+                    .send(control::error_reply(format!("no processes tagged {}", group)))
+                    .await;
+                return;
+            }
+            let label = format!("restart group {}", group);
+            (AppAction::RestartGroup(group), label)
+        }
+    };
+    app.set_status_message(format!("ctl: {}", label));
+    handle_app_action(action, app, manager, restart_attempts, event_tx).await;
+    let _ = reply.send(control::ok_reply(serde_json::Value::Null)).await;
+}
+
+fn run_init(yes: bool, force: bool) -> Result<()> {
+    let path = Path::new("piperack.toml");
+    if path.exists() && !force {
+        bail!("{} already exists (use --force to overwrite)", path.display());
+    }
+    let root = std::env::current_dir().context("failed to resolve current dir")?;
+    let detected = init::detect(&root)?;
+    for skipped in &detected.skipped {
+        eprintln!("{}", format_tool_message(&format!("skipped {}", skipped), true));
+    }
+    let processes = detected.processes;
+    if processes.is_empty() {
+        bail!("no services detected (looked for Cargo.toml, package.json, manage.py, go.mod and docker-compose files)");
+    }
+    let text = import::render_toml(&processes, "Generated by `piperack init`.");
+    println!("{}", text);
+    if !yes {
+        print!("Write {} process(es) to {}? [y/N] ", processes.len(), path.display());
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("nothing written");
+            return Ok(());
+        }
+    }
+    std::fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+fn run_import(file: &Path, out: Option<&Path>, force: bool) -> Result<()> {
+    let processes = import::import_file(file)?;
+    let header = format!("Imported from {} by `piperack import`.", file.display());
+    let text = import::render_toml(&processes, &header);
+    let Some(out) = out else {
+        print!("{}", text);
+        return Ok(());
+    };
+    if out.exists() && !force {
+        bail!("{} already exists (use --force to overwrite)", out.display());
+    }
+    std::fs::write(out, text).with_context(|| format!("failed to write {}", out.display()))?;
+    println!("wrote {} process(es) to {}", processes.len(), out.display());
+    Ok(())
+}
+
+async fn run_ctl(command: &CtlCommand, path: &Path) -> Result<()> {
+    let request = match command {
+        CtlCommand::Restart { name } => ControlRequest::Restart { name: name.clone() },
+        CtlCommand::Kill { name } => ControlRequest::Kill { name: name.clone() },
+        CtlCommand::RestartGroup { group } => ControlRequest::RestartGroup {
+            group: group.clone(),
+        },
+        CtlCommand::Input { name, text } => ControlRequest::Input {
+            name: name.clone(),
+            text: text.join(" "),
+        },
+        CtlCommand::Export { name } => ControlRequest::Export { name: name.clone() },
+        CtlCommand::Status { .. } => ControlRequest::Status,
+        CtlCommand::Logs {
+            name,
+            follow,
+            lines,
+        } => ControlRequest::Logs {
+            name: name.clone(),
+            follow: *follow,
+            lines: *lines,
+        },
+        CtlCommand::Shutdown => ControlRequest::Shutdown,
+    };
+    let mut error = None;
+    control::send_request(path, &request, |reply| {
+        if let Some(line) = reply.get("line").and_then(|line| line.as_str()) {
+            println!("{}", line);
+            return true;
+        }
+        if reply["ok"] != serde_json::Value::Bool(true) {
+            error = Some(reply["error"].as_str().unwrap_or("request failed").to_string());
+            return false;
+        }
+        match command {
+            CtlCommand::Status { json: true } => println!("{}", reply["processes"]),
+            CtlCommand::Status { json: false } => {
+                for line in ctl_status_lines(&reply["processes"]) {
+                    println!("{}", line);
+                }
+            }
+            CtlCommand::Export { .. } => {
+                println!("exported logs to {}", reply["path"].as_str().unwrap_or("-"));
+            }
+            _ => {}
+        }
+        false
+    })
+    .await?;
+    match error {
+        Some(message) => bail!(message),
+        None => Ok(()),
+    }
+}
+
+fn ctl_status_lines(processes: &serde_json::Value) -> Vec<String> {
+    let Some(processes) = processes.as_array() else {
+        return Vec::new();
+    };
+    let width = processes
+        .iter()
+        .filter_map(|p| p["name"].as_str())
+        .map(|name| name.len())
+        .max()
+        .unwrap_or(4)
+        .max(4);
+    let mut lines = vec![format!("{:<width$}  {:<8}  {:>7}  READY", "NAME", "STATUS", "PID")];
+    for process in processes {
+        let mut status = process["status"].as_str().unwrap_or("-").to_string();
+        if let Some(code) = process["exit_code"].as_i64() {
+            status = format!("{} ({})", status, code);
+        }
+        let pid = process["pid"]
+            .as_u64()
+            .map(|pid| pid.to_string())
+            .unwrap_or_else(|| "-".into());
+        lines.push(format!(
+            "{:<width$}  {:<8}  {:>7}  {}",
+            process["name"].as_str().unwrap_or("-"),
+            status,
+            pid,
+            if process["ready"].as_bool().unwrap_or(false) { "yes" } else { "no" },
+        ));
+    }
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::process::test_spec;
 
     #[test]
     fn parse_cli_processes_splits_commands() {
@@ -1706,11 +3266,18 @@ mod tests {
         assert!(matches!(parse_output_mode("combined").unwrap(), OutputMode::Combined));
         assert!(matches!(parse_output_mode("grouped").unwrap(), OutputMode::Grouped));
         assert!(matches!(parse_output_mode("raw").unwrap(), OutputMode::Raw));
+        assert!(matches!(parse_output_mode("JSON").unwrap(), OutputMode::Json));
+        assert!(matches!(parse_output_mode("ci").unwrap(), OutputMode::Ci));
         assert!(parse_output_mode("nope").is_err());
 
         assert!(matches!(parse_success_policy("first").unwrap(), SuccessPolicy::First));
         assert!(matches!(parse_success_policy("last").unwrap(), SuccessPolicy::Last));
         assert!(matches!(parse_success_policy("all").unwrap(), SuccessPolicy::All));
+        assert_eq!(
+            parse_success_policy("process:cypress").unwrap(),
+            SuccessPolicy::Process("cypress".to_string())
+        );
+        assert!(parse_success_policy("process:").is_err());
         assert!(parse_success_policy("nope").is_err());
     }
 
@@ -1736,12 +3303,42 @@ mod tests {
         assert_eq!(format_tool_message("hi", false), "[piperack] hi");
     }
 
+    #[test]
+    fn event_records_carry_process_and_fields() {
+        let event = Event::ProcessOutput {
+            id: 1,
+            line: "boom".to_string(),
+            stream: StreamKind::Stderr,
+        };
+        let (id, kind, fields) = event_record(&event).unwrap();
+        let record = json_record(id, Some(&"worker".to_string()), kind, fields);
+        assert_eq!(record["event"], "output");
+        assert_eq!(record["process"], "worker");
+        assert_eq!(record["id"], 1);
+        assert_eq!(record["stream"], "stderr");
+        assert_eq!(record["line"], "boom");
+        assert!(record["timestamp_ms"].as_u64().unwrap() > 0);
+
+        let (id, kind, fields) = event_record(&Event::ProcessExited {
+            id: 0,
+            code: None,
+            signal: Some(15),
+            stopped: false,
+        }).unwrap();
+        let record = json_record(id, None, kind, fields);
+        assert_eq!(record["event"], "exited");
+        assert!(record["code"].is_null());
+        assert_eq!(record["signal"], events::signal_name(15));
+        assert!(event_record(&Event::Resize { width: 1, height: 1 }).is_none());
+    }
+
     #[test]
     fn backoff_delay_respects_override() {
         let settings = RunSettings {
             max_lines: 100,
             use_symbols: false,
             no_ui: true,
+            headless: false,
             raw: true,
             prefix: None,
             prefix_length: None,
@@ -1757,36 +3354,22 @@ mod tests {
             shutdown_sigterm_ms: 800,
             input_enabled: false,
             log_file: None,
+            reports: Vec::new(),
+            http_port: None,
+            metrics_port: None,
+            notify: None,
+            keys: KeyMap::default(),
+            theme: Theme::default(),
         };
         assert_eq!(backoff_delay(1, &settings), Duration::from_millis(250));
     }
 
-    fn test_spec(name: &str) -> ProcessSpec {
-        ProcessSpec {
-            name: name.to_string(),
-            cmd: "echo".to_string(),
-            args: vec!["ok".to_string()],
-            cwd: None,
-            color: None,
-            env: HashMap::new(),
-            restart_on_fail: false,
-            follow: true,
-            pre_cmd: None,
-            watch_paths: Vec::new(),
-            watch_ignore: Vec::new(),
-            watch_ignore_gitignore: false,
-            watch_debounce_ms: 200,
-            depends_on: Vec::new(),
-            ready_check: None,
-            tags: Vec::new(),
-        }
-    }
-
     fn test_settings(success: SuccessPolicy, no_ui: bool) -> RunSettings {
         RunSettings {
             max_lines: 100,
             use_symbols: false,
             no_ui,
+            headless: false,
             raw: true,
             prefix: None,
             prefix_length: None,
@@ -1802,6 +3385,12 @@ mod tests {
             shutdown_sigterm_ms: 0,
             input_enabled: true,
             log_file: None,
+            reports: Vec::new(),
+            http_port: None,
+            metrics_port: None,
+            notify: None,
+            keys: KeyMap::default(),
+            theme: Theme::default(),
         }
     }
 
@@ -1847,29 +3436,509 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn success_process_ends_run_with_its_exit_code() {
+        let specs = vec![test_spec("api"), test_spec("cypress")];
+        let settings = test_settings(SuccessPolicy::Process("cypress".to_string()), true);
+        let mut app = App::new(specs.clone(), settings.max_lines, settings.use_symbols, true);
+        let (event_tx, _event_rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(settings.shutdown_sigint_ms, settings.shutdown_sigterm_ms);
+        let mut manager = ProcessManager::new(specs, event_tx, output_tx, shutdown, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        let mut result = Ok(());
+
+        handle_exit_policy(0, Some(1), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(!app.should_quit);
+        assert!(result.is_ok());
+        handle_exit_policy(1, Some(3), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(app.should_quit);
+        let err = result.unwrap_err();
+        let exit = err.downcast_ref::<ProcessExit>().unwrap();
+        assert_eq!(exit.exit_code(), 3);
+        assert_eq!(exit.to_string(), "cypress exited with code 3");
+
+        let killed = ProcessExit {
+            name: "cypress".to_string(),
+            code: None,
+            signal: Some(15),
+        };
+        assert_eq!(killed.exit_code(), 143);
+        assert_eq!(
+            killed.to_string(),
+            format!("cypress was killed by {}", events::signal_name(15))
+        );
+    }
+
+    #[test]
+    fn process_exit_code_follows_shell_conventions() {
+        let exit = |code, signal| ProcessExit { name: "e2e".to_string(), code, signal };
+        assert_eq!(exit(Some(3), None).exit_code(), 3);
+        assert_eq!(exit(None, Some(libc::SIGKILL)).exit_code(), 137);
+        // A failure must never end the run with 0.
+        assert_eq!(exit(Some(0), None).exit_code(), 1);
+        assert_eq!(exit(Some(256), None).exit_code(), 1);
+        assert_eq!(exit(None, None).exit_code(), 1);
+    }
+
+    #[tokio::test]
+    async fn success_process_killed_by_signal_exits_with_128_plus_signal() {
+        let specs = vec![test_spec("cypress")];
+        let settings = test_settings(SuccessPolicy::Process("cypress".to_string()), true);
+        let mut app = App::new(specs.clone(), settings.max_lines, settings.use_symbols, true);
+        let (event_tx, _event_rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(settings.shutdown_sigint_ms, settings.shutdown_sigterm_ms);
+        let mut manager = ProcessManager::new(specs, event_tx, output_tx, shutdown, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        let mut result = Ok(());
+
+        app.on_process_exited(0, None, Some(libc::SIGKILL), false);
+        handle_exit_policy(0, None, &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(app.should_quit);
+        let err = result.unwrap_err();
+        assert_eq!(err.downcast_ref::<ProcessExit>().unwrap().exit_code(), 137);
+    }
+
+    #[tokio::test]
+    async fn success_process_failing_with_code_zero_exits_with_one() {
+        let cypress = ProcessSpec {
+            success_codes: vec![2],
+            ..test_spec("cypress")
+        };
+        let specs = vec![cypress];
+        let settings = test_settings(SuccessPolicy::Process("cypress".to_string()), true);
+        let mut app = App::new(specs.clone(), settings.max_lines, settings.use_symbols, true);
+        let (event_tx, _event_rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(settings.shutdown_sigint_ms, settings.shutdown_sigterm_ms);
+        let mut manager = ProcessManager::new(specs, event_tx, output_tx, shutdown, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        let mut result = Ok(());
+
+        handle_exit_policy(0, Some(0), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(err.downcast_ref::<ProcessExit>().unwrap().exit_code(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn success_process_exit_shuts_down_the_others() {
+        let api = ProcessSpec {
+            cmd: "sleep".to_string(),
+            args: vec!["5".to_string()],
+            ..test_spec("api")
+        };
+        let specs = vec![api, test_spec("cypress")];
+        let settings = test_settings(SuccessPolicy::Process("cypress".to_string()), true);
+        let mut app = App::new(specs.clone(), settings.max_lines, settings.use_symbols, true);
+        let (event_tx, mut event_rx) = mpsc::channel(16);
+        let (output_tx, _output_rx) = mpsc::channel(16);
+        let shutdown = ShutdownConfig::new(1000, 1000);
+        let mut manager = ProcessManager::new(specs, event_tx, output_tx, shutdown, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        let mut result = Ok(());
+        manager.start_process(0).await.unwrap();
+
+        handle_exit_policy(1, Some(0), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(app.should_quit);
+        assert!(result.is_ok());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let stopped = loop {
+            manager.poll_exits().await;
+            match event_rx.try_recv() {
+                Ok(Event::ProcessExited { id: 0, stopped, .. }) => break stopped,
+                Ok(_) => {}
+                Err(_) if Instant::now() < deadline => {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                Err(_) => panic!("api was not shut down"),
+            }
+        };
+        assert!(stopped);
+    }
+
+    #[test]
+    fn spec_from_config_resolves_on_exit() {
+        let raw = r#"
+[[process]]
+name = "db"
+cmd = "postgres"
+on_exit = "stop_all"
+success_codes = [0, 130, 143]
+success_on_stop = true
+
+[[process]]
+name = "worker"
+cmd = "worker"
+restart_on_fail = true
+
+[[process]]
+name = "bad"
+cmd = "bad"
+restart_on_fail = true
+on_exit = "stop_dependents"
+"#;
+        let config: config::Config = toml::from_str(raw).unwrap();
+        let named_ports = ports::resolve_named_ports(&config.processes).unwrap();
+        let mut processes = config.processes.into_iter();
+        let db = spec_from_config(processes.next().unwrap(), &named_ports).unwrap();
+        assert_eq!(db.on_exit, Some(OnExit::StopAll));
+        assert!(!db.restart_on_fail);
+        assert_eq!(db.success_codes, vec![0, 130, 143]);
+        assert!(db.success_on_stop);
+        let worker = spec_from_config(processes.next().unwrap(), &named_ports).unwrap();
+        assert_eq!(worker.on_exit, None);
+        assert!(worker.restart_on_fail);
+        assert_eq!(worker.success_codes, vec![0]);
+        assert!(!worker.success_on_stop);
+        let err = spec_from_config(processes.next().unwrap(), &named_ports).unwrap_err();
+        assert!(err.to_string().contains("conflicts with on_exit"));
+    }
+
+    #[test]
+    fn spec_from_config_checks_triggers() {
+        let raw = r#"
+[[process]]
+name = "api"
+cmd = "node server.js"
+ports = { HTTP = 3000 }
+
+[[process.trigger]]
+pattern = "EADDRINUSE"
+action = "restart"
+target = "db"
+
+[[process.trigger]]
+pattern = "too many connections"
+action = "run"
+cmd = "curl -X POST localhost:${api.HTTP}/reset"
+
+[[process.trigger]]
+pattern = "FATAL"
+action = "fail"
+
+[[process]]
+name = "no-cmd"
+cmd = "true"
+[[process.trigger]]
+pattern = "x"
+action = "run"
+
+[[process]]
+name = "stray"
+cmd = "true"
+[[process.trigger]]
+pattern = "x"
+action = "fail"
+message = "boom"
+
+[[process]]
+name = "bad-regex"
+cmd = "true"
+[[process.trigger]]
+pattern = "("
+action = "warn"
+"#;
+        let config: config::Config = toml::from_str(raw).unwrap();
+        let named_ports = ports::resolve_named_ports(&config.processes).unwrap();
+        let mut processes = config.processes.into_iter();
+        let api = spec_from_config(processes.next().unwrap(), &named_ports).unwrap();
+        let actions: Vec<_> = api.triggers.iter().map(|t| t.action.clone()).collect();
+        assert_eq!(
+            actions,
+            vec![
+                TriggerAction::Restart(Some("db".to_string())),
+                TriggerAction::Run("curl -X POST localhost:3000/reset".to_string()),
+                TriggerAction::Fail,
+            ]
+        );
+        for expected in ["needs a cmd", "sets message", "invalid trigger pattern"] {
+            let err = spec_from_config(processes.next().unwrap(), &named_ports).unwrap_err();
+            assert!(err.to_string().contains(expected), "{}", err);
+        }
+
+        let mut db = test_spec("db");
+        db.tags = vec!["backend".to_string()];
+        let mut specs = vec![api, db];
+        assert!(validate_trigger_targets(&specs).is_ok());
+        specs[1].name = "postgres".to_string();
+        assert!(validate_trigger_targets(&specs).is_err());
+        specs[0].triggers[0].action = TriggerAction::Restart(Some("backend".to_string()));
+        assert!(validate_trigger_targets(&specs).is_ok());
+    }
+
+    #[test]
+    fn transitive_dependents_follow_the_dependency_chain() {
+        let mut api = test_spec("api");
+        api.depends_on = vec!["db".to_string()];
+        let mut web = test_spec("web");
+        web.depends_on = vec!["api".to_string()];
+        let specs = vec![web, test_spec("db"), test_spec("worker"), api];
+        let app = App::new(specs, 10, false, true);
+        assert_eq!(transitive_dependents(&app.processes, 1), vec![0, 3]);
+        assert!(transitive_dependents(&app.processes, 2).is_empty());
+    }
+
+    #[tokio::test]
+    async fn ignored_failure_overrides_kill_others_on_fail() {
+        let mut worker = test_spec("worker");
+        worker.on_exit = Some(OnExit::Ignore);
+        let specs = vec![worker, test_spec("api"), test_spec("db")];
+        let mut settings = test_settings(SuccessPolicy::All, true);
+        settings.kill_others_on_fail = true;
+        let mut app = App::new(specs.clone(), settings.max_lines, settings.use_symbols, true);
+        let (event_tx, _event_rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(settings.shutdown_sigint_ms, settings.shutdown_sigterm_ms);
+        let mut manager = ProcessManager::new(specs, event_tx, output_tx, shutdown, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        let mut result = Ok(());
+
+        handle_exit_policy(0, Some(1), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(!app.should_quit);
+        handle_exit_policy(1, Some(0), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(!app.should_quit);
+        // A failure without an explicit `on_exit` still stops everything.
+        handle_exit_policy(2, Some(1), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(app.should_quit);
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn ignored_failure_does_not_fail_success_all() {
+        let mut worker = test_spec("worker");
+        worker.on_exit = Some(OnExit::Ignore);
+        let specs = vec![worker, test_spec("api")];
+        let settings = test_settings(SuccessPolicy::All, true);
+        let mut app = App::new(specs.clone(), settings.max_lines, settings.use_symbols, true);
+        let (event_tx, _event_rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(settings.shutdown_sigint_ms, settings.shutdown_sigterm_ms);
+        let mut manager = ProcessManager::new(specs, event_tx, output_tx, shutdown, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        let mut result = Ok(());
+
+        handle_exit_policy(0, None, &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        handle_exit_policy(1, Some(0), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(app.should_quit);
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn on_exit_stop_all_ends_run_only_on_failure() {
+        let mut db = test_spec("db");
+        db.on_exit = Some(OnExit::StopAll);
+        let specs = vec![db, test_spec("api")];
+        let settings = test_settings(SuccessPolicy::All, true);
+        let mut app = App::new(specs.clone(), settings.max_lines, settings.use_symbols, true);
+        let (event_tx, _event_rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(settings.shutdown_sigint_ms, settings.shutdown_sigterm_ms);
+        let mut manager = ProcessManager::new(specs, event_tx, output_tx, shutdown, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        let mut result = Ok(());
+
+        handle_on_exit(0, Some(0), &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(!app.should_quit);
+        handle_on_exit(0, None, &mut app, &settings, &mut output_state, &mut manager, &mut result)
+            .await;
+        assert!(app.should_quit);
+        assert_eq!(result.unwrap_err().to_string(), "db failed, stopped all processes");
+    }
+
+    #[test]
+    fn spec_from_config_expands_named_ports() {
+        let raw = r#"
+[[process]]
+name = "api"
+cmd = "serve --port ${api.HTTP}"
+ports = { HTTP = "auto" }
+ready_check = { tcp = "HTTP" }
+
+[[process]]
+name = "web"
+cmd = "vite"
+depends_on = ["api"]
+env = { API_URL = "http://127.0.0.1:${api.HTTP}" }
+
+[[process]]
+name = "docs"
+cmd = "mdbook serve -p ${api.HTTP}"
+"#;
+        let config: config::Config = toml::from_str(raw).unwrap();
+        let named_ports = ports::resolve_named_ports(&config.processes).unwrap();
+        let port = named_ports["api"]["HTTP"];
+        let mut processes = config.processes.into_iter();
+
+        let api = spec_from_config(processes.next().unwrap(), &named_ports).unwrap();
+        assert_eq!(api.args, vec!["--port".to_string(), port.to_string()]);
+        assert_eq!(api.env.get("PORT_HTTP"), Some(&port.to_string()));
+        assert!(!api.env.contains_key("HTTP"));
+        assert!(matches!(api.ready_check, Some(ReadinessCheck::Tcp(p)) if p == port));
+        assert_eq!(api.ports, vec![port]);
+
+        let web = spec_from_config(processes.next().unwrap(), &named_ports).unwrap();
+        assert_eq!(
+            web.env.get("API_URL"),
+            Some(&format!("http://127.0.0.1:{}", port))
+        );
+
+        let err = spec_from_config(processes.next().unwrap(), &named_ports).unwrap_err();
+        assert!(format!("{:#}", err).contains("not listed in depends_on"));
+    }
+
+    #[test]
+    fn spec_from_config_expands_hooks() {
+        let raw = r#"
+[[process]]
+name = "api"
+cmd = "serve"
+ports = { HTTP = 4100 }
+
+[process.hooks]
+ready = "open http://localhost:${api.HTTP}"
+exit = "rm -rf tmp/api"
+"#;
+        let config: config::Config = toml::from_str(raw).unwrap();
+        let named_ports = ports::resolve_named_ports(&config.processes).unwrap();
+        let mut processes = config.processes.into_iter();
+        let api = spec_from_config(processes.next().unwrap(), &named_ports).unwrap();
+        assert_eq!(api.hooks.command(Hook::Ready), Some("open http://localhost:4100"));
+        assert_eq!(api.hooks.command(Hook::Exit), Some("rm -rf tmp/api"));
+        assert_eq!(api.hooks.command(Hook::Start), None);
+    }
+
     #[test]
     fn format_command_joins_args() {
         let spec = ProcessSpec {
-            name: "api".to_string(),
             cmd: "cargo".to_string(),
             args: vec!["run".to_string(), "--".to_string(), "help".to_string()],
-            cwd: None,
-            color: None,
-            env: HashMap::new(),
-            restart_on_fail: false,
-            follow: true,
-            pre_cmd: None,
-            watch_paths: Vec::new(),
-            watch_ignore: Vec::new(),
-            watch_ignore_gitignore: false,
-            watch_debounce_ms: 200,
-            depends_on: Vec::new(),
-            ready_check: None,
-            tags: Vec::new(),
+            ..test_spec("api")
         };
         assert_eq!(format_command(&spec), "cargo run -- help");
     }
 
+    #[test]
+    fn summary_lines_include_exit_and_peaks() {
+        let settings = test_settings(SuccessPolicy::All, true);
+        let mut app = App::new(vec![test_spec("api"), test_spec("worker")], 10, false, false);
+        app.on_process_stats(
+            0,
+            Some(stats::StatsSample {
+                rss_bytes: 48 * 1024 * 1024,
+                cpu_percent: 12.5,
+                threads: 4,
+                children: 2,
+            }),
+        );
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        output_state.handle_exit(0, Some(0), true);
+        let lines = summary_lines(&app, &output_state);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("exit 0"));
+        assert!(lines[0].contains("peak cpu 12.5%"));
+        assert!(lines[0].contains("peak rss 48M"));
+        assert!(lines[0].contains("max children 2"));
+        assert!(lines[1].contains("running"));
+    }
+
+    #[test]
+    fn summary_lines_show_stderr_tail_of_failures() {
+        let settings = test_settings(SuccessPolicy::All, true);
+        let mut app = App::new(vec![test_spec("lint"), test_spec("test")], 10, false, false);
+        app.on_process_started(1, 42);
+        app.on_process_output(1, "running 3 tests".to_string(), StreamKind::Stdout);
+        app.on_process_output(1, "assertion failed".to_string(), StreamKind::Stderr);
+        app.on_process_exited(1, Some(101), None, false);
+        app.on_process_started(1, 43);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        output_state.handle_exit(0, Some(0), true);
+        output_state.handle_exit(1, Some(101), false);
+        let lines = summary_lines(&app, &output_state);
+        assert!(lines[0].contains("exit 0"));
+        assert!(lines[1].contains("exit 101"));
+        assert!(lines[1].contains("1 restarts"));
+        assert_eq!(lines[2], "  test (exit code 101), last stderr:");
+        assert_eq!(lines[3], "    | assertion failed");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn ci_output_keeps_blocks_of_unfinished_processes() {
+        let mut settings = test_settings(SuccessPolicy::First, true);
+        settings.raw = false;
+        settings.output_mode = OutputMode::Ci;
+        let mut app = App::new(vec![test_spec("srv"), test_spec("job")], 10, false, false);
+        let mut output_state = OutputState::new(&app.processes, &settings);
+        output_state.ci = Some(CiProvider::GitHub);
+        for (id, line, stream) in [
+            (0, "server-up", StreamKind::Stdout),
+            (0, "error: slow start", StreamKind::Stderr),
+            (1, "job-done", StreamKind::Stdout),
+        ] {
+            let event = Event::ProcessOutput {
+                id,
+                line: line.to_string(),
+                stream,
+            };
+            app.on_process_output(id, line.to_string(), stream);
+            output_state.handle_event(&event, &app, &settings);
+        }
+        output_state.handle_exit(1, Some(0), true);
+        assert!(output_state.grouped[1].is_empty());
+
+        let lines = output_state.take_unfinished_ci_blocks();
+        assert_eq!(
+            lines,
+            vec![
+                "::group::srv (still running)".to_string(),
+                "[srv] server-up".to_string(),
+                "[srv] error: slow start".to_string(),
+                "::endgroup::".to_string(),
+                "::error::error: slow start".to_string(),
+            ]
+        );
+        assert!(output_state.take_unfinished_ci_blocks().is_empty());
+    }
+
+    #[test]
+    fn straggler_warnings_split_force_killed() {
+        let app = App::new(vec![test_spec("web")], 10, false, false);
+        let stragglers = vec![
+            Straggler {
+                id: 0,
+                pid: 10,
+                comm: "sleep".to_string(),
+                force_killed: false,
+            },
+            Straggler {
+                id: 0,
+                pid: 11,
+                comm: "esbuild".to_string(),
+                force_killed: true,
+            },
+        ];
+        let lines = straggler_warnings(&stragglers, &app);
+        assert_eq!(lines[0], "stopped leftover processes: web 10 (sleep)");
+        assert_eq!(
+            lines[1],
+            "warning: force-killed leftover processes: web 11 (esbuild)"
+        );
+        assert!(straggler_warnings(&[], &app).is_empty());
+    }
+
     #[test]
     fn apply_color_wraps_when_known() {
         let colored = apply_color("[api]", Some("red"));
@@ -0,0 +1,266 @@
+//! Prometheus metrics.
+//!
+//! With `--metrics-port`, Piperack serves `GET /metrics` in the Prometheus text exposition
+//! format. Line counters are updated by the stream readers as output arrives (including
+//! lines dropped on overflow); everything else is read from the process state when the
+//! endpoint is scraped, via a `metrics` control request answered by the main loop.
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use crate::control::ControlRequest;
+use crate::events::Event;
+use crate::http;
+use crate::output::StreamKind;
+use crate::process::{ProcessState, ProcessStatus};
+
+/// Output counters for one process, shared with its stream readers.
+#[derive(Debug, Default)]
+pub struct OutputCounters {
+    stdout: AtomicU64,
+    stderr: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl OutputCounters {
+    pub fn record_line(&self, stream: StreamKind) {
+        let counter = match stream {
+            StreamKind::Stdout => &self.stdout,
+            StreamKind::Stderr => &self.stderr,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a line that was read but discarded because the output queue was full.
+    pub fn record_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn lines(&self, stream: StreamKind) -> u64 {
+        match stream {
+            StreamKind::Stdout => self.stdout.load(Ordering::Relaxed),
+            StreamKind::Stderr => self.stderr.load(Ordering::Relaxed),
+        }
+    }
+
+    fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Binds the metrics listener on the loopback interface and serves it in the background.
+pub async fn spawn_server(port: u16, tx: mpsc::Sender<Event>) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("failed to bind metrics listener on 127.0.0.1:{}", port))?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve_connection(stream, tx.clone()));
+        }
+    });
+    Ok(addr)
+}
+
+async fn serve_connection(mut stream: TcpStream, tx: mpsc::Sender<Event>) {
+    let request = match http::read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(err) => {
+            let _ = http::write_response(&mut stream, 400, "text/plain", err.to_string().as_bytes()).await;
+            return;
+        }
+    };
+    if request.path != "/metrics" {
+        let _ = http::write_response(&mut stream, 404, "text/plain", b"not found").await;
+        return;
+    }
+    if request.method != "GET" {
+        let _ = http::write_response(&mut stream, 405, "text/plain", b"method not allowed").await;
+        return;
+    }
+    let (reply, mut replies) = mpsc::channel(1);
+    let sent = tx
+        .send(Event::Control {
+            request: ControlRequest::Metrics,
+            reply,
+        })
+        .await;
+    let text = match sent {
+        Ok(()) => replies
+            .recv()
+            .await
+            .and_then(|reply| serde_json::from_str::<Value>(&reply).ok())
+            .and_then(|reply| reply["text"].as_str().map(str::to_string)),
+        Err(_) => None,
+    };
+    let _ = match text {
+        Some(text) => {
+            http::write_response(
+                &mut stream,
+                200,
+                "text/plain; version=0.0.4; charset=utf-8",
+                text.as_bytes(),
+            )
+            .await
+        }
+        None => http::write_response(&mut stream, 503, "text/plain", b"session is shutting down").await,
+    };
+}
+
+/// Renders every metric for `processes` (with the matching output counters).
+pub fn render(processes: &[ProcessState], counters: &[Arc<OutputCounters>]) -> String {
+    let mut out = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    };
+    let label = |process: &ProcessState| format!("process=\"{}\"", escape_label(&process.spec.name));
+    let per_process = |value: &dyn Fn(&ProcessState) -> Option<String>| {
+        processes
+            .iter()
+            .filter_map(|process| value(process).map(|value| (label(process), value)))
+            .collect::<Vec<_>>()
+    };
+
+    family(
+        "piperack_process_up",
+        "gauge",
+        "Whether the process is running (1) or not (0).",
+        per_process(&|process| {
+            Some(u8::from(matches!(process.status, ProcessStatus::Running)).to_string())
+        }),
+    );
+    family(
+        "piperack_process_ready",
+        "gauge",
+        "Whether the process passed its readiness check.",
+        per_process(&|process| Some(u8::from(process.ready).to_string())),
+    );
+    family(
+        "piperack_process_restarts_total",
+        "counter",
+        "Number of times the process was started again after its first run.",
+        per_process(&|process| Some(process.restarts().to_string())),
+    );
+    family(
+        "piperack_process_uptime_seconds",
+        "gauge",
+        "Seconds since the current run started.",
+        per_process(&|process| match process.status {
+            ProcessStatus::Running => process
+                .started_at
+                .map(|at| format!("{:.3}", at.elapsed().as_secs_f64())),
+            _ => None,
+        }),
+    );
+    family(
+        "piperack_process_last_exit_code",
+        "gauge",
+        "Exit code of the last run (absent while running or when killed by a signal).",
+        per_process(&|process| process.exit_code.map(|code| code.to_string())),
+    );
+    family(
+        "piperack_process_ready_latency_seconds",
+        "gauge",
+        "Seconds from spawning the current run until it became ready.",
+        per_process(&|process| {
+            process
+                .ready_latency()
+                .map(|latency| format!("{:.3}", latency.as_secs_f64()))
+        }),
+    );
+    let mut lines = Vec::new();
+    for (process, counters) in processes.iter().zip(counters) {
+        for (stream, name) in [(StreamKind::Stdout, "stdout"), (StreamKind::Stderr, "stderr")] {
+            lines.push((
+                format!("{},stream=\"{}\"", label(process), name),
+                counters.lines(stream).to_string(),
+            ));
+        }
+    }
+    family(
+        "piperack_process_output_lines_total",
+        "counter",
+        "Output lines read from the process, per stream.",
+        lines,
+    );
+    family(
+        "piperack_process_dropped_lines_total",
+        "counter",
+        "Output lines dropped because the output queue was full.",
+        processes
+            .iter()
+            .zip(counters)
+            .map(|(process, counters)| (label(process), counters.dropped().to_string()))
+            .collect(),
+    );
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::test_spec;
+    use std::time::{Duration, Instant};
+
+    fn make_state(name: &str) -> ProcessState {
+        ProcessState::new(test_spec(name), 10)
+    }
+
+    #[test]
+    fn render_reports_state_and_counters() {
+        let mut api = make_state("api");
+        let started = Instant::now() - Duration::from_secs(5);
+        api.status = ProcessStatus::Running;
+        api.started_at = Some(started);
+        api.ready = true;
+        api.ready_at = Some(started + Duration::from_millis(1500));
+        api.starts = 3;
+        let mut worker = make_state("worker");
+        worker.status = ProcessStatus::Exited { code: Some(2) };
+        worker.exit_code = Some(2);
+        worker.starts = 1;
+
+        let counters = vec![Arc::new(OutputCounters::default()), Arc::new(OutputCounters::default())];
+        counters[0].record_line(StreamKind::Stdout);
+        counters[0].record_line(StreamKind::Stdout);
+        counters[0].record_line(StreamKind::Stderr);
+        counters[1].record_dropped();
+
+        let text = render(&[api, worker], &counters);
+        assert!(text.contains("# TYPE piperack_process_restarts_total counter"));
+        assert!(text.contains("piperack_process_up{process=\"api\"} 1"));
+        assert!(text.contains("piperack_process_up{process=\"worker\"} 0"));
+        assert!(text.contains("piperack_process_restarts_total{process=\"api\"} 2"));
+        assert!(text.contains("piperack_process_last_exit_code{process=\"worker\"} 2"));
+        assert!(!text.contains("piperack_process_last_exit_code{process=\"api\"}"));
+        assert!(text.contains("piperack_process_ready_latency_seconds{process=\"api\"} 1.500"));
+        assert!(!text.contains("piperack_process_uptime_seconds{process=\"worker\"}"));
+        assert!(text.contains(
+            "piperack_process_output_lines_total{process=\"api\",stream=\"stdout\"} 2"
+        ));
+        assert!(text.contains("piperack_process_dropped_lines_total{process=\"worker\"} 1"));
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
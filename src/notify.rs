@@ -0,0 +1,192 @@
+//! Terminal notifications from the TUI.
+//!
+//! When a process fails, becomes ready or matches a trigger, Piperack can ring the terminal
+//! bell, send an OSC 9 or OSC 777 desktop notification, or show a tmux `display-message`.
+//! Escape sequences are wrapped in tmux passthrough when running inside tmux. Notifications
+//! are rate-limited per process and event, so a crash loop rings once, not on every restart.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+/// Default minimum time between two notifications of the same event for the same process.
+pub const DEFAULT_MIN_INTERVAL_MS: u64 = 10_000;
+
+/// What a notification is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    /// A process failed on its own (bad exit code, timeout, `fail` trigger, spawn error).
+    Failure,
+    /// A process became ready.
+    Ready,
+    /// An output trigger matched.
+    Trigger,
+}
+
+/// How a notification is delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyMethod {
+    /// Terminal bell (BEL).
+    Bell,
+    /// OSC 9 desktop notification (iTerm2, Windows Terminal, WezTerm, kitty).
+    Osc9,
+    /// OSC 777 desktop notification (VTE terminals, foot, WezTerm).
+    Osc777,
+    /// `tmux display-message`, when running inside tmux.
+    Tmux,
+}
+
+/// Resolved notification settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifySettings {
+    pub events: Vec<NotifyEvent>,
+    pub methods: Vec<NotifyMethod>,
+    pub min_interval: Duration,
+}
+
+/// Sends notifications to the controlling terminal.
+pub struct Notifier {
+    settings: NotifySettings,
+    in_tmux: bool,
+    last_sent: HashMap<(String, NotifyEvent), Instant>,
+}
+
+impl Notifier {
+    pub fn new(settings: NotifySettings) -> Self {
+        Self {
+            settings,
+            in_tmux: std::env::var_os("TMUX").is_some(),
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Notifies about `event` for process `name`, unless that event is not enabled or was
+    /// already sent for the process less than `min_interval` ago.
+    pub fn notify(&mut self, event: NotifyEvent, name: &str, message: &str) {
+        if !self.should_send(event, name, Instant::now()) {
+            return;
+        }
+        let title = format!("piperack: {}", name);
+        let sequences = self.escape_sequences(&title, message);
+        if !sequences.is_empty() {
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(sequences.as_bytes());
+            let _ = stdout.flush();
+        }
+        if self.in_tmux && self.settings.methods.contains(&NotifyMethod::Tmux) {
+            // Reaped by tokio in the background; the result does not matter.
+            let _ = tokio::process::Command::new("tmux")
+                .arg("display-message")
+                .arg(format!("{}: {}", title, message))
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+        }
+    }
+
+    fn should_send(&mut self, event: NotifyEvent, name: &str, now: Instant) -> bool {
+        if !self.settings.events.contains(&event) {
+            return false;
+        }
+        let key = (name.to_string(), event);
+        if let Some(last) = self.last_sent.get(&key) {
+            if now.saturating_duration_since(*last) < self.settings.min_interval {
+                return false;
+            }
+        }
+        self.last_sent.insert(key, now);
+        true
+    }
+
+    /// The bytes written to the terminal for the enabled escape-sequence methods.
+    fn escape_sequences(&self, title: &str, message: &str) -> String {
+        let title = sanitize(title);
+        let message = sanitize(message);
+        let mut out = String::new();
+        for method in &self.settings.methods {
+            match method {
+                // tmux forwards the bell itself and flags the window.
+                NotifyMethod::Bell => out.push('\x07'),
+                NotifyMethod::Osc9 => {
+                    out.push_str(&self.passthrough(&format!("\x1b]9;{}: {}\x07", title, message)))
+                }
+                NotifyMethod::Osc777 => {
+                    // Fields are `;`-separated, so the title cannot contain one.
+                    let title = title.replace(';', ",");
+                    let sequence = format!("\x1b]777;notify;{};{}\x07", title, message);
+                    out.push_str(&self.passthrough(&sequence));
+                }
+                NotifyMethod::Tmux => {}
+            }
+        }
+        out
+    }
+
+    /// Wraps an escape sequence so tmux hands it to the outer terminal.
+    fn passthrough(&self, sequence: &str) -> String {
+        if self.in_tmux {
+            format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+        } else {
+            sequence.to_string()
+        }
+    }
+}
+
+/// Drops control characters, which would end or corrupt the escape sequence.
+fn sanitize(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notifier(methods: Vec<NotifyMethod>, in_tmux: bool) -> Notifier {
+        Notifier {
+            settings: NotifySettings {
+                events: vec![NotifyEvent::Failure, NotifyEvent::Trigger],
+                methods,
+                min_interval: Duration::from_secs(10),
+            },
+            in_tmux,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn builds_escape_sequences() {
+        let plain = notifier(vec![NotifyMethod::Bell, NotifyMethod::Osc9], false);
+        assert_eq!(
+            plain.escape_sequences("piperack: api", "exited with code 1\x1b[0m"),
+            "\x07\x1b]9;piperack: api: exited with code 1[0m\x07"
+        );
+        let osc777 = notifier(vec![NotifyMethod::Osc777, NotifyMethod::Tmux], false);
+        assert_eq!(
+            osc777.escape_sequences("a;b", "failed"),
+            "\x1b]777;notify;a,b;failed\x07"
+        );
+        let tmux = notifier(vec![NotifyMethod::Bell, NotifyMethod::Osc9], true);
+        assert_eq!(
+            tmux.escape_sequences("t", "m"),
+            "\x07\x1bPtmux;\x1b\x1b]9;t: m\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn rate_limits_per_process_and_filters_events() {
+        let mut notifier = notifier(vec![NotifyMethod::Bell], false);
+        let start = Instant::now();
+        assert!(notifier.should_send(NotifyEvent::Failure, "api", start));
+        assert!(!notifier.should_send(NotifyEvent::Failure, "api", start + Duration::from_secs(3)));
+        assert!(notifier.should_send(NotifyEvent::Failure, "db", start + Duration::from_secs(3)));
+        assert!(notifier.should_send(NotifyEvent::Failure, "api", start + Duration::from_secs(11)));
+        assert!(notifier.should_send(NotifyEvent::Trigger, "api", start));
+        assert!(!notifier.should_send(NotifyEvent::Ready, "web", start));
+    }
+}
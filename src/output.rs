@@ -33,6 +33,8 @@ pub struct TimelineEntry {
     pub text: String,
     /// The ID of the process that generated this line.
     pub process_id: usize,
+    /// The stream it originated from (stdout/stderr).
+    pub stream: StreamKind,
 }
 
 /// A fixed-capacity ring buffer for storing `LogLine`s.
@@ -40,6 +42,7 @@ pub struct TimelineEntry {
 pub struct LogBuffer {
     max_lines: usize,
     lines: VecDeque<LogLine>,
+    pushed: u64,
 }
 
 impl LogBuffer {
@@ -48,6 +51,7 @@ impl LogBuffer {
         Self {
             max_lines,
             lines: VecDeque::with_capacity(max_lines.min(1024)),
+            pushed: 0,
         }
     }
 
@@ -57,6 +61,7 @@ impl LogBuffer {
     pub fn push(&mut self, line: LogLine) -> bool {
         let mut dropped = false;
         self.lines.push_back(line);
+        self.pushed += 1;
         while self.lines.len() > self.max_lines {
             self.lines.pop_front();
             dropped = true;
@@ -70,9 +75,14 @@ impl LogBuffer {
     }
 
     /// Returns an iterator over the lines in the buffer.
-    pub fn iter(&self) -> impl Iterator<Item = &LogLine> {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &LogLine> {
         self.lines.iter()
     }
+
+    /// Returns the number of lines ever pushed, including ones since dropped.
+    pub fn total_pushed(&self) -> u64 {
+        self.pushed
+    }
 }
 
 /// A fixed-capacity ring buffer for storing `TimelineEntry`s.
@@ -165,10 +175,12 @@ mod tests {
         buffer.push(TimelineEntry {
             text: "x".into(),
             process_id: 0,
+            stream: StreamKind::Stdout,
         });
         let dropped = buffer.push(TimelineEntry {
             text: "y".into(),
             process_id: 1,
+            stream: StreamKind::Stderr,
         });
         assert!(dropped);
         assert_eq!(buffer.len(), 1);
@@ -0,0 +1,153 @@
+//! The command palette.
+//!
+//! `:` or `Ctrl-P` opens a fuzzy-searchable list of every TUI action: the key-bound
+//! actions with their shortcuts, plus per-process and per-tag commands (restart, kill,
+//! export, send a signal) that have no key of their own. Recently run commands are listed
+//! first.
+
+use std::collections::BTreeSet;
+
+use crate::app::App;
+use crate::events::UserSignal;
+use crate::fuzzy;
+use crate::keys::KeyAction;
+
+/// How many recently run commands are remembered.
+pub const RECENT_LIMIT: usize = 8;
+
+/// What a palette entry does when run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteCommand {
+    /// A key-bound action, run as if its key was pressed.
+    Key(KeyAction),
+    Restart(usize),
+    Kill(usize),
+    Export(usize),
+    Signal(usize, UserSignal),
+    RestartGroup(String),
+    KillGroup(String),
+    ExportTimeline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub label: String,
+    /// The key chords that run the command directly, if any.
+    pub shortcut: String,
+    pub command: PaletteCommand,
+}
+
+impl PaletteEntry {
+    fn new(label: String, command: PaletteCommand) -> Self {
+        Self {
+            label,
+            shortcut: String::new(),
+            command,
+        }
+    }
+}
+
+/// Every command available for the current processes.
+pub fn commands(app: &App) -> Vec<PaletteEntry> {
+    let mut entries: Vec<PaletteEntry> = KeyAction::ALL
+        .iter()
+        .filter(|action| **action != KeyAction::Palette)
+        .map(|action| PaletteEntry {
+            label: action.description().to_string(),
+            shortcut: app.keys.label(*action, app.use_symbols),
+            command: PaletteCommand::Key(*action),
+        })
+        .collect();
+    entries.push(PaletteEntry::new(
+        "Export timeline to file".to_string(),
+        PaletteCommand::ExportTimeline,
+    ));
+    for (id, process) in app.processes.iter().enumerate() {
+        let name = &process.spec.name;
+        entries.push(PaletteEntry::new(format!("Restart {}", name), PaletteCommand::Restart(id)));
+        entries.push(PaletteEntry::new(format!("Kill {}", name), PaletteCommand::Kill(id)));
+        entries.push(PaletteEntry::new(
+            format!("Export logs of {}", name),
+            PaletteCommand::Export(id),
+        ));
+        for signal in UserSignal::ALL {
+            entries.push(PaletteEntry::new(
+                format!("Send {} to {}", signal.label(), name),
+                PaletteCommand::Signal(id, signal),
+            ));
+        }
+    }
+    let tags: BTreeSet<&String> = app
+        .processes
+        .iter()
+        .flat_map(|process| process.spec.tags.iter())
+        .collect();
+    for tag in tags {
+        entries.push(PaletteEntry::new(
+            format!("Restart tag {}", tag),
+            PaletteCommand::RestartGroup(tag.clone()),
+        ));
+        entries.push(PaletteEntry::new(
+            format!("Kill tag {}", tag),
+            PaletteCommand::KillGroup(tag.clone()),
+        ));
+    }
+    entries
+}
+
+/// The entries matching `query`: recently run ones first (most recent first), then the
+/// rest by match quality, keeping the listing order among equals.
+pub fn rank(entries: Vec<PaletteEntry>, query: &str, recent: &[String]) -> Vec<PaletteEntry> {
+    let mut scored: Vec<(usize, u32, usize, PaletteEntry)> = entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let score = fuzzy::score(query, &entry.label)?;
+            let recency = recent
+                .iter()
+                .position(|label| *label == entry.label)
+                .unwrap_or(usize::MAX);
+            Some((recency, score, index, entry))
+        })
+        .collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+    scored.into_iter().map(|(_, _, _, entry)| entry).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(label: &str) -> PaletteEntry {
+        PaletteEntry::new(label.to_string(), PaletteCommand::ExportTimeline)
+    }
+
+    #[test]
+    fn rank_puts_recent_first_then_best_matches() {
+        let entries = vec![
+            entry("Toggle JSON formatting"),
+            entry("Restart api"),
+            entry("Restart web"),
+            entry("Send SIGHUP to web"),
+        ];
+        let labels = |ranked: Vec<PaletteEntry>| -> Vec<String> {
+            ranked.into_iter().map(|entry| entry.label).collect()
+        };
+        assert_eq!(
+            labels(rank(entries.clone(), "", &["Restart web".to_string()])),
+            ["Restart web", "Toggle JSON formatting", "Restart api", "Send SIGHUP to web"]
+        );
+        assert_eq!(
+            labels(rank(entries.clone(), "web", &[])),
+            ["Restart web", "Send SIGHUP to web"]
+        );
+        assert_eq!(
+            labels(rank(entries, "web", &["Send SIGHUP to web".to_string()])),
+            ["Send SIGHUP to web", "Restart web"]
+        );
+        assert_eq!(
+            labels(rank(vec![entry("Restart web"), entry("Send SIGHUP to web")], "rweb", &[])),
+            ["Restart web"]
+        );
+    }
+}
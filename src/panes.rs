@@ -0,0 +1,118 @@
+//! Split log panes.
+//!
+//! The log panel can be split into up to [`MAX_PANES`] panes, each bound to one process
+//! with its own scroll position, follow flag and search query. The arrangement (which
+//! processes, the split direction and the focused pane) is saved per project directory
+//! when the TUI exits and restored on the next run.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::control::fnv1a;
+
+/// Most log panes shown at once.
+pub const MAX_PANES: usize = 4;
+
+/// How split panes are arranged, named like vim's `:split` and `:vsplit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitDirection {
+    /// Panes side by side.
+    #[default]
+    Vertical,
+    /// Panes stacked on top of each other.
+    Horizontal,
+}
+
+impl SplitDirection {
+    pub fn toggle(self) -> Self {
+        match self {
+            SplitDirection::Vertical => SplitDirection::Horizontal,
+            SplitDirection::Horizontal => SplitDirection::Vertical,
+        }
+    }
+}
+
+/// A log pane bound to one process.
+///
+/// The focused pane's scroll and follow state live on its `ProcessState` and its search
+/// query in `App::search_query`, so the regular scrolling and search code drives it. The
+/// other panes keep theirs here until they are focused again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pane {
+    pub process: usize,
+    pub scroll: usize,
+    pub follow: bool,
+    pub search_query: Option<String>,
+}
+
+/// The persisted pane arrangement; processes are stored by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaneLayout {
+    pub direction: SplitDirection,
+    pub panes: Vec<String>,
+    pub focused: usize,
+}
+
+/// Returns where the layout for a session started in `dir` is stored:
+/// `$XDG_STATE_HOME/piperack/layout-<hash>.json`, falling back to `~/.local/state`.
+pub fn layout_path(dir: &Path) -> PathBuf {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+        .unwrap_or_else(std::env::temp_dir);
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    base.join("piperack").join(format!(
+        "layout-{:016x}.json",
+        fnv1a(dir.to_string_lossy().as_bytes())
+    ))
+}
+
+/// Reads a saved layout. A missing or unreadable file means "no split".
+pub fn load_layout(path: &Path) -> Option<PaneLayout> {
+    let text = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Saves `layout`, or removes the file when it is the default single-pane layout.
+pub fn save_layout(path: &Path, layout: &PaneLayout) -> Result<()> {
+    if *layout == PaneLayout::default() {
+        if path.exists() {
+            fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(layout)?;
+    fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_round_trips_and_default_removes_file() {
+        let dir = std::env::temp_dir().join(format!("piperack-panes-{}", std::process::id()));
+        let path = dir.join("layout.json");
+        let layout = PaneLayout {
+            direction: SplitDirection::Horizontal,
+            panes: vec!["api".to_string(), "web".to_string()],
+            focused: 1,
+        };
+        save_layout(&path, &layout).unwrap();
+        assert_eq!(load_layout(&path), Some(layout));
+
+        save_layout(&path, &PaneLayout::default()).unwrap();
+        assert!(!path.exists());
+        assert_eq!(load_layout(&path), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
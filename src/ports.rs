@@ -0,0 +1,376 @@
+//! Port conflict detection.
+//!
+//! Before a process with declared ports is spawned, `find_conflict` checks that each port
+//! can still be bound. When one cannot, the listening process is looked up through `/proc`
+//! so the error can name it, and so leftovers from an earlier Piperack run (recognised by
+//! the `PIPERACK_NAME` variable in their environment) can be offered for termination.
+//!
+//! It also resolves named ports (`ports = { HTTP = "auto" }`) at load time and expands
+//! `${process.PORT}` references to them.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+use crate::config::{PortSetting, PortsConfig, ProcessConfig};
+use crate::procfs;
+use crate::reaper;
+
+/// Resolved named ports, keyed by process name and then port name.
+pub type NamedPorts = HashMap<String, BTreeMap<String, u16>>;
+
+/// How long a terminated owner gets to release its port before it is force-killed.
+const RELEASE_GRACE: Duration = Duration::from_secs(2);
+
+/// How the process holding a port relates to Piperack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnerKind {
+    /// Not started by Piperack (or its environment could not be read).
+    Foreign,
+    /// Started by this Piperack instance under the given process name.
+    Managed(String),
+    /// Started by a Piperack instance that is no longer running.
+    Leftover(String),
+    /// Started by another Piperack instance that is still running.
+    OtherSession(String),
+}
+
+/// The process listening on a conflicting port.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub command: String,
+    pub kind: OwnerKind,
+}
+
+/// A declared port that is already taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortConflict {
+    pub port: u16,
+    /// The owning process, when it could be identified.
+    pub owner: Option<PortOwner>,
+}
+
+impl PortConflict {
+    /// One-line description naming the port and its owner.
+    pub fn describe(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!(
+                "port {} is already in use by {} (pid {})",
+                self.port, owner.command, owner.pid
+            ),
+            None => format!("port {} is already in use", self.port),
+        }
+    }
+
+    /// Suggestion for resolving the conflict.
+    pub fn hint(&self) -> String {
+        match self.owner.as_ref().map(|owner| (owner, &owner.kind)) {
+            Some((owner, OwnerKind::Leftover(name))) => format!(
+                "it is a leftover '{}' from a previous piperack run; stop it with `kill {}`",
+                name, owner.pid
+            ),
+            Some((_, OwnerKind::Managed(name))) => format!(
+                "it belongs to '{}' in this session; check for duplicate port settings",
+                name
+            ),
+            Some((_, OwnerKind::OtherSession(name))) => {
+                format!("it belongs to '{}' in another running piperack session", name)
+            }
+            Some((_, OwnerKind::Foreign)) | None => {
+                "stop that process or change the port".to_string()
+            }
+        }
+    }
+
+    /// Returns the owner if it is a leftover Piperack child that is safe to offer killing.
+    pub fn leftover_owner(&self) -> Option<&PortOwner> {
+        self.owner
+            .as_ref()
+            .filter(|owner| matches!(owner.kind, OwnerKind::Leftover(_)))
+    }
+}
+
+/// Returns the first port in `ports` that cannot be bound.
+pub fn find_conflict(ports: &[u16]) -> Option<PortConflict> {
+    let port = ports.iter().copied().find(|port| port_in_use(*port))?;
+    Some(PortConflict {
+        port,
+        owner: find_owner(port),
+    })
+}
+
+/// Returns `true` if something is already listening on `port`.
+pub fn port_in_use(port: u16) -> bool {
+    // `TcpListener::bind` sets SO_REUSEADDR on Unix, so sockets lingering in TIME_WAIT
+    // do not count as conflicts.
+    matches!(
+        TcpListener::bind(("0.0.0.0", port)),
+        Err(err) if err.kind() == ErrorKind::AddrInUse
+    )
+}
+
+fn find_owner(port: u16) -> Option<PortOwner> {
+    let pid = procfs::listener_pid(port)?;
+    let command = procfs::cmdline(pid)
+        .or_else(|| procfs::read_entry(pid).map(|entry| entry.comm))
+        .unwrap_or_else(|| "unknown".to_string());
+    let kind = match procfs::environ_var(pid, reaper::NAME_ENV) {
+        Some(name) => {
+            let supervisor = procfs::environ_var(pid, reaper::SUPERVISOR_ENV)
+                .and_then(|value| value.parse::<u32>().ok());
+            classify(name, supervisor, std::process::id(), |pid| {
+                procfs::read_entry(pid).is_some()
+            })
+        }
+        None => OwnerKind::Foreign,
+    };
+    Some(PortOwner { pid, command, kind })
+}
+
+fn classify(
+    name: String,
+    supervisor: Option<u32>,
+    self_pid: u32,
+    alive: impl Fn(u32) -> bool,
+) -> OwnerKind {
+    match supervisor {
+        Some(pid) if pid == self_pid => OwnerKind::Managed(name),
+        Some(pid) if alive(pid) => OwnerKind::OtherSession(name),
+        _ => OwnerKind::Leftover(name),
+    }
+}
+
+/// Resolves the named ports of every process, allocating a free port for each `"auto"`.
+pub fn resolve_named_ports(processes: &[ProcessConfig]) -> Result<NamedPorts> {
+    let mut taken: HashSet<u16> = HashSet::new();
+    for process in processes {
+        match &process.ports {
+            Some(PortsConfig::List(ports)) => taken.extend(ports),
+            Some(PortsConfig::Named(ports)) => {
+                taken.extend(ports.values().filter_map(|setting| match setting {
+                    PortSetting::Fixed(port) => Some(*port),
+                    PortSetting::Auto(_) => None,
+                }));
+            }
+            None => {}
+        }
+    }
+    let mut resolved = NamedPorts::new();
+    for process in processes {
+        let Some(PortsConfig::Named(ports)) = &process.ports else {
+            continue;
+        };
+        let mut named = BTreeMap::new();
+        for (key, setting) in ports {
+            let port = match setting {
+                PortSetting::Fixed(port) => *port,
+                PortSetting::Auto(value) if value == "auto" => {
+                    let port = allocate_port(&taken)?;
+                    taken.insert(port);
+                    port
+                }
+                PortSetting::Auto(value) => bail!(
+                    "invalid port {:?} for {}.{} (expected a number or \"auto\")",
+                    value,
+                    process.name,
+                    key
+                ),
+            };
+            named.insert(key.clone(), port);
+        }
+        resolved.insert(process.name.clone(), named);
+    }
+    Ok(resolved)
+}
+
+/// Environment variable a named port is exported under: `PORT_` plus the upper-cased name,
+/// so a port called `path` cannot replace `PATH`.
+pub fn port_env_var(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("PORT_{}", name)
+}
+
+/// Asks the OS for a free loopback port that has not been handed out yet.
+fn allocate_port(taken: &HashSet<u16>) -> Result<u16> {
+    for _ in 0..32 {
+        let port = TcpListener::bind(("127.0.0.1", 0))?.local_addr()?.port();
+        if !taken.contains(&port) {
+            return Ok(port);
+        }
+    }
+    bail!("failed to allocate a free port")
+}
+
+/// Expands `${process.PORT}` references in `text`.
+///
+/// Only the processes listed in `scope` (the process itself and its `depends_on`) may be
+/// referenced. `${...}` without a dot is left untouched.
+pub fn expand_port_refs(text: &str, scope: &[&str], ports: &NamedPorts) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return Ok(out);
+        };
+        let reference = &after[..end];
+        match reference.split_once('.') {
+            Some((process, key)) => {
+                if !scope.contains(&process) {
+                    bail!(
+                        "${{{}}} refers to {}, which is not listed in depends_on",
+                        reference,
+                        process
+                    );
+                }
+                let Some(port) = ports.get(process).and_then(|named| named.get(key)) else {
+                    bail!("${{{}}} refers to an unknown port", reference);
+                };
+                out.push_str(&port.to_string());
+            }
+            None => out.push_str(&rest[start..start + 2 + end + 1]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Terminates `pid` and waits for `port` to be released, escalating to SIGKILL.
+///
+/// Returns `true` once the port is free.
+pub async fn free_port(pid: u32, port: u16) -> bool {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(pid as i32, libc::SIGTERM);
+    }
+    let deadline = tokio::time::Instant::now() + RELEASE_GRACE;
+    while tokio::time::Instant::now() < deadline {
+        if !port_in_use(port) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(pid as i32, libc::SIGKILL);
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    !port_in_use(port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_distinguishes_sessions() {
+        let name = || "web".to_string();
+        assert_eq!(classify(name(), Some(10), 10, |_| true), OwnerKind::Managed(name()));
+        assert_eq!(classify(name(), Some(20), 10, |_| true), OwnerKind::OtherSession(name()));
+        assert_eq!(classify(name(), Some(20), 10, |_| false), OwnerKind::Leftover(name()));
+        assert_eq!(classify(name(), None, 10, |_| true), OwnerKind::Leftover(name()));
+    }
+
+    fn named(entries: &[(&str, &[(&str, u16)])]) -> NamedPorts {
+        entries
+            .iter()
+            .map(|(process, ports)| {
+                let ports = ports.iter().map(|(key, port)| (key.to_string(), *port)).collect();
+                (process.to_string(), ports)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn expand_port_refs_respects_scope() {
+        let ports = named(&[("api", &[("HTTP", 41000)]), ("db", &[("PG", 41001)])]);
+        let scope = ["web", "api"];
+        assert_eq!(
+            expand_port_refs("http://localhost:${api.HTTP}/v1", &scope, &ports).unwrap(),
+            "http://localhost:41000/v1"
+        );
+        assert_eq!(expand_port_refs("${HOME} ${", &scope, &ports).unwrap(), "${HOME} ${");
+        let err = expand_port_refs("${db.PG}", &scope, &ports).unwrap_err();
+        assert!(err.to_string().contains("not listed in depends_on"));
+        assert!(expand_port_refs("${api.GRPC}", &scope, &ports).is_err());
+    }
+
+    #[test]
+    fn port_env_var_is_prefixed() {
+        assert_eq!(port_env_var("HTTP"), "PORT_HTTP");
+        assert_eq!(port_env_var("path"), "PORT_PATH");
+        assert_eq!(port_env_var("dev-server"), "PORT_DEV_SERVER");
+    }
+
+    #[test]
+    fn resolve_named_ports_allocates_distinct_ports() {
+        let raw = r#"
+[[process]]
+name = "api"
+cmd = "cargo run"
+ports = { HTTP = "auto", ADMIN = "auto", DEBUG = 9229 }
+
+[[process]]
+name = "web"
+cmd = "pnpm dev"
+ports = [3000]
+"#;
+        let config: crate::config::Config = toml::from_str(raw).unwrap();
+        let resolved = resolve_named_ports(&config.processes).unwrap();
+        let api = &resolved["api"];
+        assert_eq!(api["DEBUG"], 9229);
+        assert_ne!(api["HTTP"], api["ADMIN"]);
+        assert!(!resolved.contains_key("web"));
+
+        let raw = r#"
+[[process]]
+name = "api"
+cmd = "cargo run"
+ports = { HTTP = "any" }
+"#;
+        let config: crate::config::Config = toml::from_str(raw).unwrap();
+        assert!(resolve_named_ports(&config.processes).is_err());
+    }
+
+    #[test]
+    fn find_conflict_reports_bound_port() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let conflict = find_conflict(&[port]).expect("port should be in use");
+        assert_eq!(conflict.port, port);
+        if let Some(owner) = &conflict.owner {
+            assert_eq!(owner.pid, std::process::id());
+        }
+        drop(listener);
+        assert!(find_conflict(&[port]).is_none());
+    }
+
+    #[test]
+    fn leftover_owner_only_for_leftovers() {
+        let mut conflict = PortConflict {
+            port: 3000,
+            owner: Some(PortOwner {
+                pid: 42,
+                command: "node server.js".to_string(),
+                kind: OwnerKind::Leftover("web".to_string()),
+            }),
+        };
+        assert_eq!(conflict.leftover_owner().map(|o| o.pid), Some(42));
+        assert_eq!(
+            conflict.describe(),
+            "port 3000 is already in use by node server.js (pid 42)"
+        );
+        conflict.owner.as_mut().unwrap().kind = OwnerKind::Foreign;
+        assert!(conflict.leftover_owner().is_none());
+    }
+}
@@ -4,10 +4,12 @@
 //! and the full state object (`ProcessState`) that holds logs and runtime information.
 
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use crate::config::ReadinessCheck;
+use crate::config::{HooksConfig, OnExit, ReadinessCheck, Trigger};
 use crate::output::LogBuffer;
+use crate::reaper::TreeNode;
+use crate::stats::ProcessStats;
 
 /// Specification for a process to be run.
 #[derive(Debug, Clone)]
@@ -26,6 +28,17 @@ pub struct ProcessSpec {
     pub env: HashMap<String, String>,
     /// Whether to restart the process on failure.
     pub restart_on_fail: bool,
+    /// Action taken when the process fails on its own, if set. A set action overrides
+    /// `kill_others` and `kill_others_on_fail` for this process's failures.
+    pub on_exit: Option<OnExit>,
+    /// Exit codes that count as success.
+    pub success_codes: Vec<i32>,
+    /// Whether dying from the SIGINT or SIGTERM Piperack sent to stop it counts as success.
+    pub success_on_stop: bool,
+    /// Maximum run time in milliseconds before the process is stopped as timed out.
+    pub timeout_ms: Option<u64>,
+    /// Commands run on lifecycle events.
+    pub hooks: HooksConfig,
     /// Initial follow state for logs.
     pub follow: bool,
     /// Optional command to run before the main process.
@@ -42,6 +55,10 @@ pub struct ProcessSpec {
     pub depends_on: Vec<String>,
     /// Configuration for checking if the process is ready.
     pub ready_check: Option<ReadinessCheck>,
+    /// Actions run when an output line matches a pattern.
+    pub triggers: Vec<Trigger>,
+    /// TCP ports the process listens on, checked for conflicts before spawning.
+    pub ports: Vec<u16>,
     /// Tags for grouping.
     pub tags: Vec<String>,
 }
@@ -74,6 +91,8 @@ pub struct ProcessState {
     pub started_at: Option<Instant>,
     /// Exit code of the last run.
     pub exit_code: Option<i32>,
+    /// Signal that killed the last run, if any.
+    pub exit_signal: Option<i32>,
     /// Buffer containing the process's output logs.
     pub logs: LogBuffer,
     /// Current scroll position in the log view.
@@ -84,6 +103,22 @@ pub struct ProcessState {
     pub input_active: bool,
     /// Whether the process is considered "ready" (passed readiness check).
     pub ready: bool,
+    /// Resource usage samples for the process group.
+    pub stats: ProcessStats,
+    /// Last observed process tree (the process plus every tracked descendant).
+    pub tree: Vec<TreeNode>,
+    /// Number of times the process has been spawned.
+    pub starts: u32,
+    /// Time when the current run became ready.
+    pub ready_at: Option<Instant>,
+    /// Whether the last exit followed a stop requested by Piperack.
+    pub stopped: bool,
+    /// Whether the last exit counted as success (see `exit_succeeded`).
+    pub succeeded: bool,
+    /// Whether the current run exceeded `timeout_ms`.
+    pub timed_out: bool,
+    /// Why a `fail` trigger marked the current run as failed.
+    pub trigger_failure: Option<String>,
 }
 
 impl ProcessState {
@@ -96,13 +131,89 @@ impl ProcessState {
             pid: None,
             started_at: None,
             exit_code: None,
+            exit_signal: None,
             logs: LogBuffer::new(max_lines),
             scroll: 0,
             follow,
             input_active: false,
             ready: false,
+            stats: ProcessStats::default(),
+            tree: Vec::new(),
+            starts: 0,
+            ready_at: None,
+            stopped: false,
+            succeeded: false,
+            timed_out: false,
+            trigger_failure: None,
         }
     }
+
+    /// Number of times the process was started again after its first run.
+    pub fn restarts(&self) -> u32 {
+        self.starts.saturating_sub(1)
+    }
+
+    /// Time from spawning the current run until it became ready.
+    pub fn ready_latency(&self) -> Option<Duration> {
+        self.ready_at?.checked_duration_since(self.started_at?)
+    }
+
+    /// Whether Piperack stopped the current run as a failure (timeout or `fail` trigger).
+    pub fn stopped_as_failed(&self) -> bool {
+        self.timed_out || self.trigger_failure.is_some()
+    }
+
+    /// Whether an exit with `code` counts as success, given `success_codes` and
+    /// `success_on_stop`. A process that failed to start or was stopped as failed never succeeds.
+    pub fn exit_succeeded(&self, code: Option<i32>) -> bool {
+        if self.stopped_as_failed() || matches!(self.status, ProcessStatus::Failed { .. }) {
+            return false;
+        }
+        (self.spec.success_on_stop && self.died_from_stop_signal(code))
+            || code.is_some_and(|code| self.spec.success_codes.contains(&code))
+    }
+
+    /// Whether the last run ended from the SIGINT or SIGTERM Piperack sent to stop it, either
+    /// by the signal itself or by a shell-style 130/143 exit code.
+    fn died_from_stop_signal(&self, code: Option<i32>) -> bool {
+        let stop_signals = [libc::SIGINT, libc::SIGTERM];
+        self.stopped
+            && match (code, self.exit_signal) {
+                (_, Some(signal)) => stop_signals.contains(&signal),
+                (Some(code), None) => stop_signals.iter().any(|signal| code == 128 + signal),
+                (None, None) => false,
+            }
+    }
+}
+
+/// Spec with defaults, for tests to override with struct update syntax.
+#[cfg(test)]
+pub(crate) fn test_spec(name: &str) -> ProcessSpec {
+    ProcessSpec {
+        name: name.to_string(),
+        cmd: "echo".to_string(),
+        args: Vec::new(),
+        cwd: None,
+        color: None,
+        env: HashMap::new(),
+        restart_on_fail: false,
+        on_exit: None,
+        success_codes: vec![0],
+        success_on_stop: false,
+        timeout_ms: None,
+        hooks: Default::default(),
+        follow: true,
+        pre_cmd: None,
+        watch_paths: Vec::new(),
+        watch_ignore: Vec::new(),
+        watch_ignore_gitignore: false,
+        watch_debounce_ms: 200,
+        depends_on: Vec::new(),
+        ready_check: None,
+        triggers: Vec::new(),
+        ports: Vec::new(),
+        tags: Vec::new(),
+    }
 }
 
 #[cfg(test)]
@@ -111,24 +222,7 @@ mod tests {
 
     #[test]
     fn process_state_inherits_follow_and_defaults() {
-        let spec = ProcessSpec {
-            name: "api".to_string(),
-            cmd: "echo".to_string(),
-            args: Vec::new(),
-            cwd: None,
-            color: None,
-            env: HashMap::new(),
-            restart_on_fail: false,
-            follow: true,
-            pre_cmd: None,
-            watch_paths: Vec::new(),
-            watch_ignore: Vec::new(),
-            watch_ignore_gitignore: false,
-            watch_debounce_ms: 200,
-            depends_on: Vec::new(),
-            ready_check: None,
-            tags: Vec::new(),
-        };
+        let spec = test_spec("api");
         let state = ProcessState::new(spec, 10);
         assert_eq!(state.status, ProcessStatus::Idle);
         assert!(state.follow);
@@ -137,4 +231,39 @@ mod tests {
         assert!(state.pid.is_none());
         assert!(state.started_at.is_none());
     }
+
+    #[test]
+    fn exit_success_honours_codes_and_stop() {
+        let spec = ProcessSpec {
+            success_codes: vec![0, 130],
+            success_on_stop: true,
+            ..test_spec("api")
+        };
+        let mut state = ProcessState::new(spec, 10);
+        assert!(state.exit_succeeded(Some(0)));
+        assert!(state.exit_succeeded(Some(130)));
+        assert!(!state.exit_succeeded(Some(143)));
+        assert!(!state.exit_succeeded(None));
+        state.stopped = true;
+        assert!(state.exit_succeeded(Some(143)));
+        assert!(!state.exit_succeeded(Some(1)));
+        state.exit_signal = Some(libc::SIGTERM);
+        assert!(state.exit_succeeded(None));
+        // SIGKILL after the grace period ran out is not a clean stop.
+        state.exit_signal = Some(libc::SIGKILL);
+        assert!(!state.exit_succeeded(None));
+        state.exit_signal = Some(libc::SIGINT);
+        state.spec.success_on_stop = false;
+        assert!(!state.exit_succeeded(None));
+        state.timed_out = true;
+        assert!(!state.exit_succeeded(Some(0)));
+        state.timed_out = false;
+        state.trigger_failure = Some("matched \"EADDRINUSE\"".to_string());
+        assert!(!state.exit_succeeded(Some(0)));
+        state.trigger_failure = None;
+        state.status = ProcessStatus::Failed {
+            error: "spawn failed".to_string(),
+        };
+        assert!(!state.exit_succeeded(Some(0)));
+    }
 }
@@ -0,0 +1,258 @@
+//! Minimal readers for the Linux `/proc` filesystem.
+//!
+//! This module parses `/proc/<pid>/stat` into `ProcEntry` values so other modules can
+//! inspect the processes spawned by Piperack. On platforms without `/proc` every reader
+//! returns nothing.
+
+/// A parsed `/proc/<pid>/stat` record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcEntry {
+    /// Process ID.
+    pub pid: u32,
+    /// State letter (`R`, `S`, `Z` for a zombie, ...).
+    pub state: char,
+    /// Parent process ID.
+    pub ppid: u32,
+    /// Process group ID.
+    pub pgrp: u32,
+    /// Executable name (without the surrounding parentheses).
+    pub comm: String,
+    /// User + system CPU time, in clock ticks.
+    pub cpu_ticks: u64,
+    /// Number of threads.
+    pub threads: u32,
+    /// Start time since boot, in clock ticks (used to detect PID reuse).
+    pub start_time: u64,
+    /// Resident set size, in pages.
+    pub rss_pages: u64,
+}
+
+/// Reads the stat record for a single process.
+#[cfg(target_os = "linux")]
+pub fn read_entry(pid: u32) -> Option<ProcEntry> {
+    let raw = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat(&raw)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_entry(_pid: u32) -> Option<ProcEntry> {
+    None
+}
+
+/// Reads the stat records of every visible process.
+#[cfg(target_os = "linux")]
+pub fn snapshot() -> Vec<ProcEntry> {
+    let Ok(dir) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    dir.filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(read_entry)
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn snapshot() -> Vec<ProcEntry> {
+    Vec::new()
+}
+
+/// Looks up a single variable in the initial environment of a process.
+#[cfg(target_os = "linux")]
+pub fn environ_var(pid: u32, key: &str) -> Option<String> {
+    let raw = std::fs::read(format!("/proc/{}/environ", pid)).ok()?;
+    find_environ_var(&raw, key)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn environ_var(_pid: u32, _key: &str) -> Option<String> {
+    None
+}
+
+fn find_environ_var(raw: &[u8], key: &str) -> Option<String> {
+    raw.split(|b| *b == 0).find_map(|entry| {
+        let entry = String::from_utf8_lossy(entry);
+        let (k, v) = entry.split_once('=')?;
+        (k == key).then(|| v.to_string())
+    })
+}
+
+/// Returns the command line of a process, joined with spaces.
+#[cfg(target_os = "linux")]
+pub fn cmdline(pid: u32) -> Option<String> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let parts: Vec<String> = raw
+        .split(|b| *b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).into_owned())
+        .collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cmdline(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Finds the process holding a listening TCP socket (IPv4 or IPv6) on `port`.
+#[cfg(target_os = "linux")]
+pub fn listener_pid(port: u16) -> Option<u32> {
+    let inodes: Vec<u64> = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|raw| listening_inodes(&raw, port))
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+    let targets: Vec<String> = inodes.iter().map(|inode| format!("socket:[{}]", inode)).collect();
+    let dir = std::fs::read_dir("/proc").ok()?;
+    dir.filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .find(|pid| {
+            let Ok(fds) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+                return false;
+            };
+            fds.filter_map(|fd| fd.ok())
+                .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+                .any(|link| targets.iter().any(|target| link.as_os_str() == target.as_str()))
+        })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn listener_pid(_port: u16) -> Option<u32> {
+    None
+}
+
+// Socket inodes of LISTEN entries for `port` in a `/proc/net/tcp{,6}` table.
+fn listening_inodes(raw: &str, port: u16) -> Vec<u64> {
+    const TCP_LISTEN: &str = "0A";
+    raw.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local = fields.get(1)?;
+            let (_, hex_port) = local.rsplit_once(':')?;
+            if u16::from_str_radix(hex_port, 16).ok()? != port || *fields.get(3)? != TCP_LISTEN {
+                return None;
+            }
+            fields.get(9)?.parse().ok()
+        })
+        .filter(|inode| *inode != 0)
+        .collect()
+}
+
+/// Number of clock ticks per second (`_SC_CLK_TCK`).
+#[cfg(target_os = "linux")]
+pub fn clock_ticks() -> u64 {
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as u64
+    } else {
+        100
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn clock_ticks() -> u64 {
+    100
+}
+
+/// Size of a memory page in bytes.
+#[cfg(target_os = "linux")]
+pub fn page_size() -> u64 {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as u64
+    } else {
+        4096
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn page_size() -> u64 {
+    4096
+}
+
+/// Parses the contents of a `/proc/<pid>/stat` file.
+///
+/// The command name may itself contain spaces or parentheses, so fields are located
+/// relative to the last closing parenthesis.
+pub fn parse_stat(raw: &str) -> Option<ProcEntry> {
+    let open = raw.find('(')?;
+    let close = raw.rfind(')')?;
+    let pid = raw[..open].trim().parse().ok()?;
+    let comm = raw.get(open + 1..close)?.to_string();
+    // Fields after the command name start at field 3 (state).
+    let fields: Vec<&str> = raw[close + 1..].split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3).copied();
+    let state = field(3)?.chars().next()?;
+    let ppid = field(4)?.parse().ok()?;
+    let pgrp = field(5)?.parse().ok()?;
+    let utime: u64 = field(14)?.parse().ok()?;
+    let stime: u64 = field(15)?.parse().ok()?;
+    let threads = field(20)?.parse().ok()?;
+    let start_time = field(22)?.parse().ok()?;
+    let rss_pages = field(24)?.parse::<i64>().ok()?.max(0) as u64;
+    Some(ProcEntry {
+        pid,
+        state,
+        ppid,
+        pgrp,
+        comm,
+        cpu_ticks: utime + stime,
+        threads,
+        start_time,
+        rss_pages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_stat_handles_spaces_in_comm() {
+        let raw = "4242 (node (dev) srv) S 4200 4242 4200 0 -1 4194560 100 0 0 0 \
+                   25 17 0 0 20 0 11 0 98765 1000000 2048 18446744073709551615";
+        let entry = parse_stat(raw).unwrap();
+        assert_eq!(entry.pid, 4242);
+        assert_eq!(entry.comm, "node (dev) srv");
+        assert_eq!(entry.state, 'S');
+        assert_eq!(entry.ppid, 4200);
+        assert_eq!(entry.pgrp, 4242);
+        assert_eq!(entry.cpu_ticks, 42);
+        assert_eq!(entry.threads, 11);
+        assert_eq!(entry.start_time, 98765);
+        assert_eq!(entry.rss_pages, 2048);
+    }
+
+    #[test]
+    fn find_environ_var_splits_on_nul() {
+        let raw = b"PATH=/bin\0PIPERACK_NAME=api\0EMPTY=\0";
+        assert_eq!(find_environ_var(raw, "PIPERACK_NAME").as_deref(), Some("api"));
+        assert_eq!(find_environ_var(raw, "EMPTY").as_deref(), Some(""));
+        assert!(find_environ_var(raw, "HOME").is_none());
+    }
+
+    #[test]
+    fn listening_inodes_filters_port_and_state() {
+        let raw = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 51234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0BB8 0100007F:A1B2 01 00000000:00000000 00:00000000 00000000  1000        0 51300 1 0000000000000000 20 4 30 10 -1
+   2: 0100007F:23F1 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 51400 1 0000000000000000 100 0 0 10 0
+";
+        assert_eq!(listening_inodes(raw, 3000), vec![51234]);
+        assert_eq!(listening_inodes(raw, 9201), vec![51400]);
+        assert!(listening_inodes(raw, 8080).is_empty());
+    }
+
+    #[test]
+    fn parse_stat_rejects_truncated_input() {
+        assert!(parse_stat("12 (sh) S 1 12").is_none());
+        assert!(parse_stat("garbage").is_none());
+    }
+}
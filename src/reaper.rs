@@ -0,0 +1,442 @@
+//! Descendant tracking and cleanup for managed processes.
+//!
+//! Signals sent by `ProcessManager` reach the process group of each managed process, but
+//! children that call `setsid` or double-fork leave that group and can outlive Piperack.
+//! On Linux, Piperack registers itself as a child subreaper so such orphans are re-parented
+//! to it instead of `init`. The `TreeTracker` remembers every descendant it has seen for
+//! each managed process, which lets the TUI show the full tree and lets shutdown terminate
+//! any stragglers.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::procfs::{self, ProcEntry};
+
+/// Environment variable carrying the managed process name into its children.
+pub const NAME_ENV: &str = "PIPERACK_NAME";
+/// Environment variable carrying the PID of the supervising Piperack instance.
+pub const SUPERVISOR_ENV: &str = "PIPERACK_SUPERVISOR_PID";
+
+/// How long stragglers get to exit after SIGTERM before they are force-killed.
+const STRAGGLER_GRACE: Duration = Duration::from_millis(500);
+
+/// Registers the current process as a child subreaper (Linux only).
+///
+/// Returns `true` if the kernel accepted the request.
+#[cfg(target_os = "linux")]
+pub fn become_subreaper() -> bool {
+    unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) == 0 }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn become_subreaper() -> bool {
+    false
+}
+
+/// A single node of a managed process tree, in depth-first order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeNode {
+    /// Nesting level (0 for the managed process itself).
+    pub depth: usize,
+    /// Process ID.
+    pub pid: u32,
+    /// Executable name.
+    pub comm: String,
+    /// Whether the process lost its original parent and was adopted by Piperack.
+    pub orphaned: bool,
+}
+
+/// A descendant that was still alive after its managed process stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Straggler {
+    /// Index of the managed process it belonged to.
+    pub id: usize,
+    /// Process ID.
+    pub pid: u32,
+    /// Executable name.
+    pub comm: String,
+    /// Whether it ignored SIGTERM and had to be killed with SIGKILL.
+    pub force_killed: bool,
+}
+
+/// Children that tokio waits on itself: managed processes, `pre_cmd`, hooks and triggers.
+///
+/// The zombie sweep must never collect these, or tokio would lose their exit status.
+/// They are registered under the same lock the sweep holds, so a child that exits right
+/// after spawning is never mistaken for an adopted orphan.
+#[derive(Debug, Clone, Default)]
+pub struct OwnedChildren(Arc<Mutex<HashSet<u32>>>);
+
+impl OwnedChildren {
+    /// Spawns `command` and registers the child.
+    pub fn spawn(
+        &self,
+        command: &mut tokio::process::Command,
+    ) -> std::io::Result<tokio::process::Child> {
+        let mut pids = self.lock();
+        let child = command.spawn()?;
+        pids.extend(child.id());
+        Ok(child)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashSet<u32>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Tracked {
+    start_time: u64,
+    comm: String,
+}
+
+/// Remembers the descendants of every managed process across `/proc` snapshots.
+#[derive(Debug)]
+pub struct TreeTracker {
+    self_pid: u32,
+    names: Vec<String>,
+    tracked: Vec<HashMap<u32, Tracked>>,
+    owned: OwnedChildren,
+}
+
+impl TreeTracker {
+    /// Creates a tracker for the given managed process names (indexed by process ID).
+    pub fn new(names: Vec<String>) -> Self {
+        Self::with_supervisor(names, std::process::id())
+    }
+
+    /// Creates a tracker for processes supervised by another piperack instance (used by
+    /// `piperack attach`, whose managed processes belong to the background session).
+    pub fn with_supervisor(names: Vec<String>, supervisor_pid: u32) -> Self {
+        let tracked = vec![HashMap::new(); names.len()];
+        Self {
+            self_pid: supervisor_pid,
+            names,
+            tracked,
+            owned: OwnedChildren::default(),
+        }
+    }
+
+    /// Shares the children tokio reaps itself, so the zombie sweep leaves them alone.
+    pub fn with_owned(mut self, owned: OwnedChildren) -> Self {
+        self.owned = owned;
+        self
+    }
+
+    /// Refreshes the tracked descendants from a `/proc` snapshot.
+    ///
+    /// `roots` holds the PID of each running managed process. Descendants stay tracked
+    /// after their parent exits for as long as the same process (PID + start time) lives.
+    pub fn update(&mut self, roots: &[Option<u32>], entries: &[ProcEntry]) {
+        let by_pid: HashMap<u32, &ProcEntry> = entries.iter().map(|e| (e.pid, e)).collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for entry in entries {
+            children.entry(entry.ppid).or_default().push(entry.pid);
+        }
+        let root_pids: HashSet<u32> = roots.iter().flatten().copied().collect();
+        let claimed: HashSet<u32> = self
+            .tracked
+            .iter()
+            .flat_map(|tracked| tracked.keys().copied())
+            .collect();
+
+        for id in 0..self.tracked.len() {
+            let root = roots.get(id).copied().flatten();
+            let mut seeds: Vec<u32> = self.tracked[id]
+                .iter()
+                .filter(|(pid, tracked)| {
+                    by_pid
+                        .get(pid)
+                        .map(|e| e.start_time == tracked.start_time)
+                        .unwrap_or(false)
+                })
+                .map(|(pid, _)| *pid)
+                .collect();
+            if let Some(root) = root {
+                seeds.push(root);
+                seeds.extend(
+                    entries
+                        .iter()
+                        .filter(|e| e.pgrp == root && e.pid != root)
+                        .map(|e| e.pid),
+                );
+            }
+            // Orphans adopted by us are attributed through the environment we set at spawn.
+            seeds.extend(
+                entries
+                    .iter()
+                    .filter(|e| {
+                        e.ppid == self.self_pid
+                            && !root_pids.contains(&e.pid)
+                            && !claimed.contains(&e.pid)
+                    })
+                    .filter(|e| self.spawned_by_us(e.pid, id))
+                    .map(|e| e.pid),
+            );
+
+            let mut next = HashMap::new();
+            let mut stack = seeds;
+            while let Some(pid) = stack.pop() {
+                // The root itself is owned by `ProcessManager`; only its descendants are tracked.
+                if Some(pid) != root {
+                    if next.contains_key(&pid) {
+                        continue;
+                    }
+                    let Some(entry) = by_pid.get(&pid) else {
+                        continue;
+                    };
+                    next.insert(
+                        pid,
+                        Tracked {
+                            start_time: entry.start_time,
+                            comm: entry.comm.clone(),
+                        },
+                    );
+                }
+                stack.extend(children.get(&pid).into_iter().flatten().copied());
+            }
+            self.tracked[id] = next;
+        }
+        self.reap_adopted(entries);
+    }
+
+    /// Returns the tree of a managed process in depth-first order.
+    pub fn tree(&self, id: usize, root: Option<u32>, entries: &[ProcEntry]) -> Vec<TreeNode> {
+        let Some(tracked) = self.tracked.get(id) else {
+            return Vec::new();
+        };
+        let by_pid: HashMap<u32, &ProcEntry> = entries.iter().map(|e| (e.pid, e)).collect();
+        let mut members: HashSet<u32> = tracked.keys().copied().collect();
+        if let Some(root) = root {
+            members.insert(root);
+        }
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        let mut tops = Vec::new();
+        for pid in &members {
+            let parent = by_pid.get(pid).map(|e| e.ppid);
+            match parent {
+                Some(ppid) if members.contains(&ppid) && Some(*pid) != root => {
+                    children.entry(ppid).or_default().push(*pid)
+                }
+                _ => tops.push(*pid),
+            }
+        }
+        for list in children.values_mut() {
+            list.sort_unstable();
+        }
+        // The managed process comes first, followed by any detached subtrees.
+        tops.sort_unstable_by_key(|pid| (Some(*pid) != root, *pid));
+
+        let mut nodes = Vec::new();
+        let mut stack: Vec<(u32, usize)> = tops.iter().rev().map(|pid| (*pid, 0)).collect();
+        while let Some((pid, depth)) = stack.pop() {
+            let comm = by_pid
+                .get(&pid)
+                .map(|e| e.comm.clone())
+                .or_else(|| tracked.get(&pid).map(|t| t.comm.clone()))
+                .unwrap_or_default();
+            let orphaned = depth == 0 && Some(pid) != root;
+            let depth = if orphaned && root.is_some() { 1 } else { depth };
+            nodes.push(TreeNode {
+                depth,
+                pid,
+                comm,
+                orphaned,
+            });
+            if let Some(kids) = children.get(&pid) {
+                stack.extend(kids.iter().rev().map(|kid| (*kid, depth + 1)));
+            }
+        }
+        nodes
+    }
+
+    /// Descendants that are still alive, regardless of whether their root is.
+    fn alive(&self, entries: &[ProcEntry]) -> Vec<(usize, u32, String)> {
+        let by_pid: HashMap<u32, &ProcEntry> = entries.iter().map(|e| (e.pid, e)).collect();
+        let mut alive = Vec::new();
+        for (id, tracked) in self.tracked.iter().enumerate() {
+            for (pid, info) in tracked {
+                if by_pid
+                    .get(pid)
+                    .map(|e| e.start_time == info.start_time)
+                    .unwrap_or(false)
+                {
+                    alive.push((id, *pid, info.comm.clone()));
+                }
+            }
+        }
+        alive.sort_by_key(|(id, pid, _)| (*id, *pid));
+        alive
+    }
+
+    fn spawned_by_us(&self, pid: u32, id: usize) -> bool {
+        let supervisor = self.self_pid.to_string();
+        procfs::environ_var(pid, SUPERVISOR_ENV).as_deref() == Some(supervisor.as_str())
+            && procfs::environ_var(pid, NAME_ENV).as_deref() == self.names.get(id).map(String::as_str)
+    }
+
+    // Orphans re-parented to us become zombies when they exit; collect them. Every zombie
+    // child counts, tracked or not: an orphan that cleared its environment (or lived between
+    // two snapshots) was never tracked. Children tokio waits on are left alone.
+    fn reap_adopted(&self, entries: &[ProcEntry]) {
+        let mut owned = self.owned.lock();
+        // Children tokio has already waited on are gone from /proc.
+        owned.retain(|pid| procfs::read_entry(*pid).is_some());
+        for entry in adopted_zombies(self.self_pid, entries, &owned) {
+            reap(entry);
+        }
+    }
+}
+
+fn adopted_zombies(self_pid: u32, entries: &[ProcEntry], owned: &HashSet<u32>) -> Vec<u32> {
+    entries
+        .iter()
+        .filter(|entry| entry.ppid == self_pid && entry.state == 'Z')
+        .filter(|entry| !owned.contains(&entry.pid))
+        .map(|entry| entry.pid)
+        .collect()
+}
+
+/// Terminates every descendant that outlived its managed process.
+///
+/// Stragglers receive SIGTERM first; any still alive after a short grace period are
+/// killed with SIGKILL. Returns the processes that had to be stopped.
+pub async fn terminate_stragglers(tracker: &mut TreeTracker) -> Vec<Straggler> {
+    let entries = procfs::snapshot();
+    tracker.update(&vec![None; tracker.tracked.len()], &entries);
+    let alive = tracker.alive(&entries);
+    if alive.is_empty() {
+        return Vec::new();
+    }
+    for (_, pid, _) in &alive {
+        send_signal(*pid, Signal::Term);
+    }
+    let deadline = tokio::time::Instant::now() + STRAGGLER_GRACE;
+    let mut remaining = alive.clone();
+    while !remaining.is_empty() && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let entries = procfs::snapshot();
+        tracker.reap_adopted(&entries);
+        let still: HashSet<u32> = tracker.alive(&entries).iter().map(|(_, pid, _)| *pid).collect();
+        remaining.retain(|(_, pid, _)| still.contains(pid));
+    }
+    for (_, pid, _) in &remaining {
+        send_signal(*pid, Signal::Kill);
+        reap(*pid);
+    }
+    let forced: HashSet<u32> = remaining.iter().map(|(_, pid, _)| *pid).collect();
+    alive
+        .into_iter()
+        .map(|(id, pid, comm)| Straggler {
+            id,
+            pid,
+            comm,
+            force_killed: forced.contains(&pid),
+        })
+        .collect()
+}
+
+enum Signal {
+    Term,
+    Kill,
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: Signal) {
+    let sig = match signal {
+        Signal::Term => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+    };
+    unsafe {
+        let _ = libc::kill(pid as i32, sig);
+    }
+}
+
+#[cfg(not(unix))]
+fn send_signal(_pid: u32, _signal: Signal) {}
+
+#[cfg(unix)]
+fn reap(pid: u32) {
+    unsafe {
+        let mut status = 0;
+        let _ = libc::waitpid(pid as i32, &mut status, libc::WNOHANG);
+    }
+}
+
+#[cfg(not(unix))]
+fn reap(_pid: u32) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, ppid: u32, pgrp: u32, comm: &str) -> ProcEntry {
+        ProcEntry {
+            pid,
+            state: 'S',
+            ppid,
+            pgrp,
+            comm: comm.to_string(),
+            cpu_ticks: 0,
+            threads: 1,
+            start_time: pid as u64,
+            rss_pages: 0,
+        }
+    }
+
+    fn tracker() -> TreeTracker {
+        let mut tracker = TreeTracker::new(vec!["web".to_string()]);
+        // Use a PID no test entry refers to, so adoption checks never hit /proc.
+        tracker.self_pid = u32::MAX;
+        tracker
+    }
+
+    #[test]
+    fn update_follows_parent_links_and_keeps_detached_descendants() {
+        let mut tracker = tracker();
+        let entries = vec![
+            entry(100, 1, 100, "npm"),
+            entry(101, 100, 100, "node"),
+            entry(102, 101, 102, "esbuild"),
+            entry(200, 1, 200, "unrelated"),
+        ];
+        tracker.update(&[Some(100)], &entries);
+        let tree = tracker.tree(0, Some(100), &entries);
+        let pids: Vec<(usize, u32)> = tree.iter().map(|n| (n.depth, n.pid)).collect();
+        assert_eq!(pids, vec![(0, 100), (1, 101), (2, 102)]);
+
+        // npm and node exit; esbuild (setsid) is re-parented and must still be tracked.
+        let entries = vec![entry(102, 1, 102, "esbuild"), entry(200, 1, 200, "unrelated")];
+        tracker.update(&[None], &entries);
+        let alive = tracker.alive(&entries);
+        assert_eq!(alive, vec![(0, 102, "esbuild".to_string())]);
+        let tree = tracker.tree(0, None, &entries);
+        assert_eq!(tree.len(), 1);
+        assert!(tree[0].orphaned);
+    }
+
+    #[test]
+    fn adopted_zombies_skip_live_and_owned_children() {
+        let mut zombie = entry(300, 42, 300, "worker");
+        zombie.state = 'Z';
+        let mut hook = entry(301, 42, 301, "sh");
+        hook.state = 'Z';
+        let mut elsewhere = entry(302, 7, 302, "other");
+        elsewhere.state = 'Z';
+        let entries = vec![zombie, hook, elsewhere, entry(303, 42, 303, "node")];
+        let owned = HashSet::from([301]);
+        assert_eq!(adopted_zombies(42, &entries, &owned), vec![300]);
+    }
+
+    #[test]
+    fn update_drops_reused_pids() {
+        let mut tracker = tracker();
+        let entries = vec![entry(100, 1, 100, "sh"), entry(101, 100, 100, "sleep")];
+        tracker.update(&[Some(100)], &entries);
+        let mut reused = entry(101, 1, 101, "other");
+        reused.start_time = 9999;
+        tracker.update(&[None], &[reused.clone()]);
+        assert!(tracker.alive(&[reused]).is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! End-of-run reports.
+//!
+//! When a run concludes, the outcome of each process is collected into a `ProcessResult`.
+//! The results feed the summary table printed in `--no-ui` mode and the files requested
+//! with `--report` (currently JUnit XML, e.g. `--report junit=results.xml`).
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+/// Number of trailing stderr lines kept for failed processes.
+pub const STDERR_TAIL_LINES: usize = 10;
+
+/// A report file requested with `--report <format>=<path>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportTarget {
+    Junit(PathBuf),
+}
+
+impl ReportTarget {
+    pub fn parse(value: &str) -> Result<Self> {
+        let Some((format, path)) = value.split_once('=') else {
+            bail!("invalid report {:?} (expected junit=<path>)", value);
+        };
+        if path.is_empty() {
+            bail!("missing path in report {:?}", value);
+        }
+        match format.to_lowercase().as_str() {
+            "junit" => Ok(ReportTarget::Junit(PathBuf::from(path))),
+            _ => bail!("unknown report format {:?} (supported: junit)", format),
+        }
+    }
+
+    /// Writes the report for `results`.
+    pub fn write(&self, results: &[ProcessResult]) -> Result<()> {
+        match self {
+            ReportTarget::Junit(path) => write_file(path, &junit_xml(results)),
+        }
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// How a process ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// Non-zero exit, killed by a signal, or failed to start.
+    Failed(String),
+    /// Stopped after running longer than its `timeout_ms`.
+    TimedOut(Duration),
+    /// Still running (or never started) when the run ended.
+    NotFinished,
+}
+
+/// The result of one process, as reported at the end of a run.
+#[derive(Debug, Clone)]
+pub struct ProcessResult {
+    pub name: String,
+    pub outcome: Outcome,
+    pub exit_code: Option<i32>,
+    /// Duration of the last run.
+    pub duration: Option<Duration>,
+    pub restarts: u32,
+    /// Last stderr lines, kept for failed processes only.
+    pub stderr_tail: Vec<String>,
+}
+
+impl Outcome {
+    /// Why the process failed, if it did.
+    pub fn failure(&self) -> Option<String> {
+        match self {
+            Outcome::Failed(message) => Some(message.clone()),
+            Outcome::TimedOut(timeout) => Some(format!("timed out after {:.1}s", timeout.as_secs_f64())),
+            Outcome::Passed | Outcome::NotFinished => None,
+        }
+    }
+}
+
+impl ProcessResult {
+    /// Short status for the summary table (`exit 0`, `exit -`, `timeout`, `running`).
+    pub fn exit_label(&self) -> String {
+        match (&self.outcome, self.exit_code) {
+            (Outcome::NotFinished, _) => "running".to_string(),
+            (Outcome::TimedOut(_), _) => "timeout".to_string(),
+            (_, Some(code)) => format!("exit {}", code),
+            (_, None) => "exit -".to_string(),
+        }
+    }
+
+    pub fn duration_label(&self) -> String {
+        match self.duration {
+            Some(duration) => format!("{:.1}s", duration.as_secs_f64()),
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// Renders the results as a JUnit XML document with one test case per process.
+pub fn junit_xml(results: &[ProcessResult]) -> String {
+    let failures = results
+        .iter()
+        .filter(|result| result.outcome.failure().is_some())
+        .count();
+    let skipped = results
+        .iter()
+        .filter(|result| result.outcome == Outcome::NotFinished)
+        .count();
+    let total: f64 = results
+        .iter()
+        .filter_map(|result| result.duration)
+        .map(|duration| duration.as_secs_f64())
+        .sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"piperack\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        results.len(),
+        failures,
+        total
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"piperack\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\">",
+        results.len(),
+        failures,
+        skipped,
+        total
+    );
+    for result in results {
+        let time = result.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0);
+        let _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"piperack\" time=\"{:.3}\"",
+            escape_xml(&result.name),
+            time
+        );
+        match &result.outcome {
+            Outcome::Passed => xml.push_str("/>\n"),
+            Outcome::NotFinished => {
+                xml.push_str(">\n      <skipped message=\"did not finish\"/>\n    </testcase>\n");
+            }
+            Outcome::Failed(_) | Outcome::TimedOut(_) => {
+                let kind = match result.outcome {
+                    Outcome::TimedOut(_) => "timeout",
+                    _ => "exit",
+                };
+                let _ = writeln!(
+                    xml,
+                    ">\n      <failure message=\"{}\" type=\"{}\">{}</failure>",
+                    escape_xml(&result.outcome.failure().unwrap_or_default()),
+                    kind,
+                    escape_xml(&result.stderr_tail.join("\n"))
+                );
+                if result.restarts > 0 {
+                    let _ = writeln!(
+                        xml,
+                        "      <system-out>restarted {} time(s)</system-out>",
+                        result.restarts
+                    );
+                }
+                xml.push_str("    </testcase>\n");
+            }
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters (e.g. stray ANSI escapes) are not allowed in XML 1.0.
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, outcome: Outcome, exit_code: Option<i32>) -> ProcessResult {
+        ProcessResult {
+            name: name.to_string(),
+            outcome,
+            exit_code,
+            duration: Some(Duration::from_millis(1500)),
+            restarts: 0,
+            stderr_tail: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_report_targets() {
+        assert_eq!(
+            ReportTarget::parse("junit=out/results.xml").unwrap(),
+            ReportTarget::Junit(PathBuf::from("out/results.xml"))
+        );
+        assert!(ReportTarget::parse("junit").is_err());
+        assert!(ReportTarget::parse("junit=").is_err());
+        assert!(ReportTarget::parse("tap=x").is_err());
+    }
+
+    #[test]
+    fn junit_marks_failures_and_skips() {
+        let mut failed = result("test<unit>", Outcome::Failed("exit code 1".to_string()), Some(1));
+        failed.stderr_tail = vec!["assert \"a\" == b".to_string(), "\u{1b}[31mFAIL".to_string()];
+        let xml = junit_xml(&[
+            result("lint", Outcome::Passed, Some(0)),
+            failed,
+            result("serve", Outcome::NotFinished, None),
+        ]);
+        assert!(xml.contains("tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\""));
+        assert!(xml.contains("<testcase name=\"lint\" classname=\"piperack\" time=\"1.500\"/>"));
+        assert!(xml.contains("name=\"test&lt;unit&gt;\""));
+        assert!(xml.contains(
+            "<failure message=\"exit code 1\" type=\"exit\">assert &quot;a&quot; == b\n[31mFAIL</failure>"
+        ));
+        assert!(xml.contains("<skipped message=\"did not finish\"/>"));
+    }
+
+    #[test]
+    fn junit_reports_timeouts_as_failures() {
+        let xml = junit_xml(&[result("e2e", Outcome::TimedOut(Duration::from_millis(2500)), None)]);
+        assert!(xml.contains("failures=\"1\""));
+        assert!(xml.contains("<failure message=\"timed out after 2.5s\" type=\"timeout\">"));
+    }
+
+    #[test]
+    fn labels_describe_exit_and_duration() {
+        assert_eq!(result("a", Outcome::Passed, Some(0)).exit_label(), "exit 0");
+        assert_eq!(
+            result("a", Outcome::Failed("killed".to_string()), None).exit_label(),
+            "exit -"
+        );
+        assert_eq!(result("a", Outcome::NotFinished, None).exit_label(), "running");
+        assert_eq!(
+            result("a", Outcome::TimedOut(Duration::from_secs(2)), None).exit_label(),
+            "timeout"
+        );
+        assert_eq!(result("a", Outcome::Passed, Some(0)).duration_label(), "1.5s");
+    }
+}
@@ -5,6 +5,8 @@
 //! and bridges system process events to the application's event channel.
 
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
@@ -14,10 +16,13 @@ use tokio::net::TcpStream;
 use tokio::process::Command;
 use tokio::sync::mpsc;
 
-use crate::config::ReadinessCheck;
-use crate::events::{Event, ProcessSignal};
+use crate::config::{Hook, ReadinessCheck};
+use crate::events::{Event, ProcessSignal, UserSignal};
 use crate::output::StreamKind;
+use crate::ports;
 use crate::process::ProcessSpec;
+use crate::metrics::OutputCounters;
+use crate::reaper::{self, OwnedChildren};
 
 /// Manages the lifecycle and I/O of child processes.
 pub struct ProcessManager {
@@ -26,6 +31,10 @@ pub struct ProcessManager {
     output_tx: mpsc::Sender<Event>,
     shutdown: ShutdownConfig,
     lossy_output: bool,
+    /// Hook commands that may still be running.
+    hooks: Vec<tokio::task::JoinHandle<()>>,
+    /// Every child spawned here, which tokio reaps itself.
+    owned: OwnedChildren,
 }
 
 struct ManagedProcess {
@@ -36,6 +45,9 @@ struct ManagedProcess {
     ready: bool,
     waiting_on: Vec<String>,
     shutdown: Option<ShutdownState>,
+    /// When the current run exceeds `timeout_ms`.
+    deadline: Option<tokio::time::Instant>,
+    counters: Arc<OutputCounters>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -101,6 +113,8 @@ impl ProcessManager {
                 ready: false,
                 waiting_on: Vec::new(),
                 shutdown: None,
+                deadline: None,
+                counters: Arc::new(OutputCounters::default()),
             })
             .collect();
         Self {
@@ -109,9 +123,19 @@ impl ProcessManager {
             output_tx,
             shutdown,
             lossy_output,
+            hooks: Vec::new(),
+            owned: OwnedChildren::default(),
         }
     }
 
+    /// Output counters of every process, indexed by process ID.
+    pub fn output_counters(&self) -> Vec<Arc<OutputCounters>> {
+        self.processes
+            .iter()
+            .map(|process| process.counters.clone())
+            .collect()
+    }
+
     /// Starts all configured processes, respecting dependencies.
     pub async fn start_all(&mut self) -> Result<()> {
         self.update_scheduler().await
@@ -184,6 +208,13 @@ impl ProcessManager {
         self.processes[id].waiting_on.clear();
         let _ = self.event_tx.send(Event::ProcessStarting { id }).await;
 
+        if let Some(conflict) = ports::find_conflict(&spec.ports) {
+            let error = format!("port {} in use", conflict.port);
+            let _ = self.event_tx.send(Event::PortConflict { id, conflict }).await;
+            let _ = self.event_tx.send(Event::ProcessFailed { id, error }).await;
+            return Ok(());
+        }
+
         if !self.run_pre_cmd(id, &spec).await? {
             return Ok(());
         }
@@ -196,6 +227,10 @@ impl ProcessManager {
         if !spec.env.is_empty() {
             command.envs(&spec.env);
         }
+        // Lets the tree tracker attribute descendants that escape the process group.
+        command
+            .env(reaper::NAME_ENV, &spec.name)
+            .env(reaper::SUPERVISOR_ENV, std::process::id().to_string());
         command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -217,8 +252,9 @@ impl ProcessManager {
             });
         }
 
-        let mut child = command
-            .spawn()
+        let mut child = self
+            .owned
+            .spawn(&mut command)
             .with_context(|| format!("failed to spawn {}", spec.name))?;
         let pid = child.id().unwrap_or(0);
         let _ = self.event_tx.send(Event::ProcessStarted { id, pid }).await;
@@ -229,42 +265,42 @@ impl ProcessManager {
             }
         }
 
-        // Determine output capture regex for readiness
-        let log_ready_regex = if let Some(ReadinessCheck::Log(pattern)) = &spec.ready_check {
-            Regex::new(pattern).ok()
-        } else {
-            None
-        };
+        // Readiness and trigger regexes, shared by both streams of this run
+        let matchers = Arc::new(LineMatchers::new(&spec));
 
+        let counters = self.processes[id].counters.clone();
         if let Some(stdout) = child.stdout.take() {
             let tx = self.output_tx.clone();
-            let regex = log_ready_regex.clone();
             let lossy_output = self.lossy_output;
             tokio::spawn(read_stream(
                 id,
                 StreamKind::Stdout,
                 stdout,
                 tx,
-                regex,
+                matchers.clone(),
                 lossy_output,
+                counters.clone(),
             ));
         }
         if let Some(stderr) = child.stderr.take() {
             let tx = self.output_tx.clone();
-            let regex = log_ready_regex; // move last clone
             let lossy_output = self.lossy_output;
             tokio::spawn(read_stream(
                 id,
                 StreamKind::Stderr,
                 stderr,
                 tx,
-                regex,
+                matchers,
                 lossy_output,
+                counters,
             ));
         }
 
         if let Some(process) = self.processes.get_mut(id) {
             process.child = Some(child);
+            process.deadline = spec
+                .timeout_ms
+                .map(|ms| tokio::time::Instant::now() + Duration::from_millis(ms));
         }
 
         // Handle readiness checks
@@ -319,7 +355,7 @@ impl ProcessManager {
             command.envs(&spec.env);
         }
         command.stdout(Stdio::piped()).stderr(Stdio::piped());
-        let mut child = match command.spawn() {
+        let mut child = match self.owned.spawn(&mut command) {
             Ok(child) => child,
             Err(err) => {
                 let _ = self
@@ -341,6 +377,7 @@ impl ProcessManager {
                 stdout,
                 tx,
                 self.lossy_output,
+                self.processes[id].counters.clone(),
             ));
         }
         if let Some(stderr) = child.stderr.take() {
@@ -352,6 +389,7 @@ impl ProcessManager {
                 stderr,
                 tx,
                 self.lossy_output,
+                self.processes[id].counters.clone(),
             ));
         }
         let status = child.wait().await?;
@@ -368,7 +406,157 @@ impl ProcessManager {
         Ok(true)
     }
 
+    /// Runs a lifecycle hook of a process in the background, if one is configured.
+    ///
+    /// The hook gets the process's `cwd` and environment plus `PIPERACK_NAME`, `PIPERACK_PID`
+    /// and `PIPERACK_EXIT_CODE`; its output is logged under the process with a `[hook]` prefix.
+    pub fn run_hook(&mut self, id: usize, hook: Hook, pid: Option<u32>, code: Option<i32>) {
+        let Some(hook_cmd) = self
+            .processes
+            .get(id)
+            .and_then(|process| process.spec.hooks.command(hook))
+            .map(str::to_string)
+        else {
+            return;
+        };
+        let env = vec![
+            ("PIPERACK_PID", pid.map(|pid| pid.to_string()).unwrap_or_default()),
+            ("PIPERACK_EXIT_CODE", code.map(|code| code.to_string()).unwrap_or_default()),
+        ];
+        self.spawn_side_command(id, "[hook] ", hook.label(), &hook_cmd, env);
+    }
+
+    /// Runs the command of a `run` trigger, with the matched line in `PIPERACK_MATCH`.
+    pub fn run_trigger_command(&mut self, id: usize, cmd: &str, line: &str) {
+        let pid = self
+            .processes
+            .get(id)
+            .and_then(|process| process.child.as_ref())
+            .and_then(|child| child.id());
+        let env = vec![
+            ("PIPERACK_PID", pid.map(|pid| pid.to_string()).unwrap_or_default()),
+            ("PIPERACK_MATCH", line.to_string()),
+        ];
+        self.spawn_side_command(id, "[trigger] ", "command", cmd, env);
+    }
+
+    /// Spawns a hook or trigger command next to a process and logs its output under it.
+    fn spawn_side_command(
+        &mut self,
+        id: usize,
+        prefix: &'static str,
+        label: &'static str,
+        cmd: &str,
+        env: Vec<(&'static str, String)>,
+    ) {
+        let Some(process) = self.processes.get(id) else {
+            return;
+        };
+        let spec = &process.spec;
+        let mut command = match shell_words::split(cmd) {
+            Ok(parts) if !parts.is_empty() => {
+                let mut command = Command::new(&parts[0]);
+                command.args(&parts[1..]);
+                command
+            }
+            Ok(_) => return,
+            Err(err) => {
+                let line = format!("{}failed to parse {}: {}", prefix, label, err);
+                let _ = self.output_tx.try_send(Event::ProcessOutput {
+                    id,
+                    line,
+                    stream: StreamKind::Stderr,
+                });
+                return;
+            }
+        };
+        if let Some(cwd) = &spec.cwd {
+            command.current_dir(cwd);
+        }
+        command
+            .envs(&spec.env)
+            .env("PIPERACK_NAME", &spec.name)
+            .envs(env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let tx = self.output_tx.clone();
+        let lossy_output = self.lossy_output;
+        let counters = process.counters.clone();
+        let owned = self.owned.clone();
+        self.hooks.retain(|hook| !hook.is_finished());
+        self.hooks.push(tokio::spawn(async move {
+            let mut child = match owned.spawn(&mut command) {
+                Ok(child) => child,
+                Err(err) => {
+                    let line = format!("{}{} failed: {}", prefix, label, err);
+                    let _ = tx
+                        .send(Event::ProcessOutput { id, line, stream: StreamKind::Stderr })
+                        .await;
+                    return;
+                }
+            };
+            let mut readers = Vec::new();
+            if let Some(stdout) = child.stdout.take() {
+                readers.push(tokio::spawn(read_stream_with_prefix(
+                    id,
+                    StreamKind::Stdout,
+                    prefix,
+                    stdout,
+                    tx.clone(),
+                    lossy_output,
+                    counters.clone(),
+                )));
+            }
+            if let Some(stderr) = child.stderr.take() {
+                readers.push(tokio::spawn(read_stream_with_prefix(
+                    id,
+                    StreamKind::Stderr,
+                    prefix,
+                    stderr,
+                    tx.clone(),
+                    lossy_output,
+                    counters,
+                )));
+            }
+            let status = child.wait().await;
+            for reader in readers {
+                let _ = reader.await;
+            }
+            let failure = match status {
+                Ok(status) if status.success() => return,
+                Ok(status) => match status.code() {
+                    Some(code) => format!("exited with code {}", code),
+                    None => "was killed by a signal".to_string(),
+                },
+                Err(err) => format!("failed: {}", err),
+            };
+            let line = format!("{}{} {}", prefix, label, failure);
+            let _ = tx
+                .send(Event::ProcessOutput { id, line, stream: StreamKind::Stderr })
+                .await;
+        }));
+    }
+
+    /// The children this manager spawned, for the zombie sweep (see `TreeTracker::with_owned`).
+    pub fn owned_children(&self) -> OwnedChildren {
+        self.owned.clone()
+    }
+
+    /// Waits up to `timeout` for running hooks to finish, so their output is not lost on exit.
+    pub async fn wait_for_hooks(&mut self, timeout: Duration) {
+        let deadline = tokio::time::Instant::now() + timeout;
+        for hook in self.hooks.drain(..) {
+            let _ = tokio::time::timeout_at(deadline, hook).await;
+        }
+    }
+
     pub async fn restart_process(&mut self, id: usize) -> Result<()> {
+        if let Some(process) = self.processes.get(id) {
+            let pid = process.child.as_ref().and_then(|child| child.id());
+            self.run_hook(id, Hook::Restart, pid, None);
+        }
         self.stop_process(id, true).await?;
         // Reset state for restart
         if let Some(p) = self.processes.get_mut(id) {
@@ -419,6 +607,17 @@ impl ProcessManager {
         self.begin_shutdown(id, signal).await;
     }
 
+    /// Sends `signal` to the process group of `id` once, without starting a shutdown.
+    pub fn signal_process(&self, id: usize, signal: UserSignal) -> Result<()> {
+        let Some(process) = self.processes.get(id) else {
+            anyhow::bail!("unknown process {}", id);
+        };
+        let Some(pid) = process.child.as_ref().and_then(|child| child.id()) else {
+            anyhow::bail!("{} is not running", process.spec.name);
+        };
+        send_user_signal(pid, signal)
+    }
+
     pub async fn begin_shutdown_all(&mut self, signal: ProcessSignal) {
         for idx in 0..self.processes.len() {
             self.begin_shutdown(idx, signal).await;
@@ -437,7 +636,17 @@ impl ProcessManager {
                 match child.try_wait() {
                     Ok(Some(status)) => {
                         let code = status.code();
-                        let _ = self.event_tx.send(Event::ProcessExited { id, code }).await;
+                        let signal = exit_signal(&status);
+                        let stopped = process.shutdown.is_some();
+                        let _ = self
+                            .event_tx
+                            .send(Event::ProcessExited {
+                                id,
+                                code,
+                                signal,
+                                stopped,
+                            })
+                            .await;
                         process.child = None;
                         process.ready = false; // It exited, so it's not ready
                         process.shutdown = None;
@@ -458,9 +667,27 @@ impl ProcessManager {
                 }
             }
         }
+        self.poll_timeouts().await;
         self.poll_shutdowns().await;
     }
 
+    /// Stops processes that ran past their deadline, through the usual shutdown escalation.
+    async fn poll_timeouts(&mut self) {
+        let now = tokio::time::Instant::now();
+        for id in 0..self.processes.len() {
+            let process = &mut self.processes[id];
+            let expired = process.child.is_some()
+                && process.shutdown.is_none()
+                && process.deadline.is_some_and(|deadline| now >= deadline);
+            if !expired {
+                continue;
+            }
+            process.deadline = None;
+            let _ = self.event_tx.send(Event::ProcessTimedOut { id }).await;
+            self.begin_shutdown(id, ProcessSignal::SigInt).await;
+        }
+    }
+
     async fn begin_shutdown(&mut self, id: usize, initial: ProcessSignal) {
         let now = tokio::time::Instant::now();
         let shutdown = self.shutdown;
@@ -583,14 +810,24 @@ impl ProcessManager {
                     Ok(Some(status)) => {
                         let _ = self
                             .event_tx
-                            .send(Event::ProcessExited { id, code: status.code() })
+                            .send(Event::ProcessExited {
+                                id,
+                                code: status.code(),
+                                signal: exit_signal(&status),
+                                stopped: true,
+                            })
                             .await;
                     }
                     Ok(None) => match child.wait().await {
                         Ok(status) => {
                             let _ = self
                                 .event_tx
-                                .send(Event::ProcessExited { id, code: status.code() })
+                                .send(Event::ProcessExited {
+                                    id,
+                                    code: status.code(),
+                                    signal: exit_signal(&status),
+                                    stopped: true,
+                                })
                                 .await;
                         }
                         Err(err) => {
@@ -629,6 +866,8 @@ impl ProcessManager {
                                     .send(Event::ProcessExited {
                                         id,
                                         code: status.code(),
+                                        signal: exit_signal(&status),
+                                        stopped: true,
                                     })
                                     .await;
                                 return Ok(());
@@ -657,6 +896,8 @@ impl ProcessManager {
                                     .send(Event::ProcessExited {
                                         id,
                                         code: status.code(),
+                                        signal: exit_signal(&status),
+                                        stopped: true,
                                     })
                                     .await;
                                 return Ok(());
@@ -682,6 +923,8 @@ impl ProcessManager {
                             .send(Event::ProcessExited {
                                 id,
                                 code: status.code(),
+                                signal: exit_signal(&status),
+                                stopped: true,
                             })
                             .await;
                     }
@@ -709,6 +952,20 @@ impl ProcessManager {
     }
 }
 
+/// The signal that killed a process, if it did not exit on its own.
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
 #[cfg(unix)]
 fn send_os_signal(pid: u32, signal: ProcessSignal) {
     unsafe {
@@ -722,6 +979,36 @@ fn send_os_signal(pid: u32, signal: ProcessSignal) {
     }
 }
 
+#[cfg(unix)]
+fn send_user_signal(pid: u32, signal: UserSignal) -> Result<()> {
+    let sig = match signal {
+        UserSignal::Hup => libc::SIGHUP,
+        UserSignal::Int => libc::SIGINT,
+        UserSignal::Term => libc::SIGTERM,
+        UserSignal::Usr1 => libc::SIGUSR1,
+        UserSignal::Usr2 => libc::SIGUSR2,
+    };
+    let pid = pid as i32;
+    // Signal the group once (the leader is in it); only fall back to the leader itself
+    // when the group is gone, so handlers never see the signal twice.
+    if unsafe { libc::kill(-pid, sig) } == 0 {
+        return Ok(());
+    }
+    let mut err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        if unsafe { libc::kill(pid, sig) } == 0 {
+            return Ok(());
+        }
+        err = std::io::Error::last_os_error();
+    }
+    Err(err).with_context(|| format!("failed to send {}", signal.label()))
+}
+
+#[cfg(not(unix))]
+fn send_user_signal(_pid: u32, signal: UserSignal) -> Result<()> {
+    anyhow::bail!("{} is not supported on this platform", signal.label())
+}
+
 #[cfg(not(unix))]
 fn send_os_signal(pid: u32, signal: ProcessSignal) {
     send_ctrl_break(pid, signal);
@@ -755,96 +1042,47 @@ async fn wait_for_exit(
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn shutdown_stage_prefers_sigint_then_sigterm() {
-        let shutdown = ShutdownConfig::new(800, 500);
-        let now = tokio::time::Instant::now();
-        let (stage, signal, deadline) =
-            ProcessManager::initial_shutdown_stage(shutdown, ProcessSignal::SigInt, now);
-        assert!(matches!(stage, ShutdownStage::SigInt));
-        assert_eq!(signal, Some(ProcessSignal::SigInt));
-        assert_eq!(deadline, now + Duration::from_millis(800));
-    }
-
-    #[test]
-    fn shutdown_stage_falls_back_when_sigint_disabled() {
-        let shutdown = ShutdownConfig::new(0, 500);
-        let now = tokio::time::Instant::now();
-        let (stage, signal, deadline) =
-            ProcessManager::initial_shutdown_stage(shutdown, ProcessSignal::SigInt, now);
-        assert!(matches!(stage, ShutdownStage::SigTerm));
-        assert_eq!(signal, Some(ProcessSignal::SigTerm));
-        assert_eq!(deadline, now + Duration::from_millis(500));
-    }
-
-    #[test]
-    fn shutdown_stage_handles_all_disabled() {
-        let shutdown = ShutdownConfig::new(0, 0);
-        let now = tokio::time::Instant::now();
-        let (stage, signal, deadline) =
-            ProcessManager::initial_shutdown_stage(shutdown, ProcessSignal::SigTerm, now);
-        assert!(matches!(stage, ShutdownStage::Kill));
-        assert_eq!(signal, None);
-        assert_eq!(deadline, now);
-    }
-
-    #[test]
-    fn shutdown_config_flags() {
-        let config = ShutdownConfig::new(100, 0);
-        assert!(config.sigint_enabled());
-        assert!(!config.sigterm_enabled());
-        assert_eq!(config.sigint_timeout(), Duration::from_millis(100));
-        assert_eq!(config.sigterm_timeout(), Duration::from_millis(0));
-    }
+/// Regexes checked against every output line of one run.
+struct LineMatchers {
+    /// Log readiness check.
+    ready: Option<Regex>,
+    /// Trigger patterns, by index into `spec.triggers`.
+    triggers: Vec<Option<Regex>>,
+    /// Triggers that already fired; each fires at most once per run.
+    fired: Vec<AtomicBool>,
+}
 
-    #[cfg(unix)]
-    #[tokio::test]
-    async fn poll_shutdowns_advances_stage() {
-        let spec = ProcessSpec {
-            name: "sleep".to_string(),
-            cmd: "sleep".to_string(),
-            args: vec!["5".to_string()],
-            cwd: None,
-            color: None,
-            env: std::collections::HashMap::new(),
-            restart_on_fail: false,
-            follow: true,
-            pre_cmd: None,
-            watch_paths: Vec::new(),
-            watch_ignore: Vec::new(),
-            watch_ignore_gitignore: false,
-            watch_debounce_ms: 200,
-            depends_on: Vec::new(),
-            ready_check: None,
-            tags: Vec::new(),
+impl LineMatchers {
+    fn new(spec: &ProcessSpec) -> Self {
+        let ready = match &spec.ready_check {
+            Some(ReadinessCheck::Log(pattern)) => Regex::new(pattern).ok(),
+            _ => None,
         };
-        let (tx, _rx) = mpsc::channel(4);
-        let shutdown = ShutdownConfig::new(10, 1000);
-        let (output_tx, _output_rx) = mpsc::channel(4);
-        let mut manager = ProcessManager::new(vec![spec], tx, output_tx, shutdown, false);
-        let child = tokio::process::Command::new("sleep")
-            .arg("5")
-            .spawn()
-            .unwrap();
-        manager.processes[0].child = Some(child);
-        manager.processes[0].shutdown = Some(ShutdownState {
-            stage: ShutdownStage::SigInt,
-            deadline: tokio::time::Instant::now() - Duration::from_millis(1),
-        });
-
-        manager.poll_shutdowns().await;
-        let stage = manager.processes[0].shutdown.unwrap().stage;
-        assert!(matches!(stage, ShutdownStage::SigTerm | ShutdownStage::Kill));
-
-        if let Some(mut child) = manager.processes[0].child.take() {
-            let _ = child.kill().await;
-            let _ = child.wait().await;
+        let triggers: Vec<Option<Regex>> = spec
+            .triggers
+            .iter()
+            .map(|trigger| Regex::new(&trigger.pattern).ok())
+            .collect();
+        let fired = triggers.iter().map(|_| AtomicBool::new(false)).collect();
+        Self {
+            ready,
+            triggers,
+            fired,
         }
     }
+
+    /// Triggers matching `line` that have not fired yet, marking them as fired.
+    fn fire(&self, line: &str) -> Vec<usize> {
+        self.triggers
+            .iter()
+            .enumerate()
+            .filter(|(index, regex)| {
+                regex.as_ref().is_some_and(|regex| regex.is_match(line))
+                    && !self.fired[*index].swap(true, Ordering::Relaxed)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
 }
 
 async fn read_stream<R>(
@@ -852,8 +1090,9 @@ async fn read_stream<R>(
     stream: StreamKind,
     reader: R,
     tx: mpsc::Sender<Event>,
-    readiness_regex: Option<Regex>,
+    matchers: Arc<LineMatchers>,
     lossy_output: bool,
+    counters: Arc<OutputCounters>,
 ) where
     R: tokio::io::AsyncRead + Unpin,
 {
@@ -861,37 +1100,47 @@ async fn read_stream<R>(
     let mut matched = false;
     let mut dropped: u64 = 0;
     while let Ok(Some(line)) = lines.next_line().await {
+        counters.record_line(stream);
         if !matched {
-            if let Some(regex) = &readiness_regex {
+            if let Some(regex) = &matchers.ready {
                 if regex.is_match(&line) {
                     let _ = tx.send(Event::ProcessReady { id }).await;
                     matched = true;
                 }
             }
         }
+        // Sent after the line itself so the action shows up below it.
+        let triggered: Vec<(usize, String)> = matchers
+            .fire(&line)
+            .into_iter()
+            .map(|trigger| (trigger, line.clone()))
+            .collect();
         if lossy_output {
-            if dropped > 0 {
-                if tx
+            if dropped > 0
+                && tx
                     .try_send(Event::ProcessOutput {
                         id,
                         line: format!("[piperack] dropped {} lines (output overflow)", dropped),
                         stream: StreamKind::Stderr,
                     })
                     .is_ok()
-                {
-                    dropped = 0;
-                }
+            {
+                dropped = 0;
             }
             match tx.try_send(Event::ProcessOutput { id, line, stream }) {
                 Ok(_) => {}
                 Err(mpsc::error::TrySendError::Full(_)) => {
                     dropped = dropped.saturating_add(1);
+                    counters.record_dropped();
                 }
                 Err(mpsc::error::TrySendError::Closed(_)) => break,
             }
         } else {
             let _ = tx.send(Event::ProcessOutput { id, line, stream }).await;
         }
+        for (trigger, line) in triggered {
+            let _ = tx.send(Event::Triggered { id, trigger, line }).await;
+        }
     }
 
     if lossy_output && dropped > 0 {
@@ -913,6 +1162,7 @@ async fn read_stream_with_prefix<R>(
     reader: R,
     tx: mpsc::Sender<Event>,
     lossy_output: bool,
+    counters: Arc<OutputCounters>,
 ) where
     R: tokio::io::AsyncRead + Unpin,
 {
@@ -920,23 +1170,24 @@ async fn read_stream_with_prefix<R>(
     let mut dropped: u64 = 0;
     while let Ok(Some(line)) = lines.next_line().await {
         let line = format!("{}{}", prefix, line);
+        counters.record_line(stream);
         if lossy_output {
-            if dropped > 0 {
-                if tx
+            if dropped > 0
+                && tx
                     .try_send(Event::ProcessOutput {
                         id,
                         line: format!("[piperack] dropped {} lines (output overflow)", dropped),
                         stream: StreamKind::Stderr,
                     })
                     .is_ok()
-                {
-                    dropped = 0;
-                }
+            {
+                dropped = 0;
             }
             match tx.try_send(Event::ProcessOutput { id, line, stream }) {
                 Ok(_) => {}
                 Err(mpsc::error::TrySendError::Full(_)) => {
                     dropped = dropped.saturating_add(1);
+                    counters.record_dropped();
                 }
                 Err(mpsc::error::TrySendError::Closed(_)) => break,
             }
@@ -969,3 +1220,264 @@ async fn check_tcp_readiness(id: usize, port: u16, tx: mpsc::Sender<Event>) {
     }
     // Timeout? We could send Failed, but for now just don't send Ready.
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::config::{HooksConfig, Trigger, TriggerAction};
+    use crate::process::test_spec;
+
+    #[test]
+    fn shutdown_stage_prefers_sigint_then_sigterm() {
+        let shutdown = ShutdownConfig::new(800, 500);
+        let now = tokio::time::Instant::now();
+        let (stage, signal, deadline) =
+            ProcessManager::initial_shutdown_stage(shutdown, ProcessSignal::SigInt, now);
+        assert!(matches!(stage, ShutdownStage::SigInt));
+        assert_eq!(signal, Some(ProcessSignal::SigInt));
+        assert_eq!(deadline, now + Duration::from_millis(800));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn user_signal_reaches_group_leader_once() {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::process::CommandExt;
+
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg("n=0; trap 'n=$((n+1))' USR1; echo ready; i=0; while [ $i -lt 5 ]; do sleep 0.1; i=$((i+1)); done; echo $n")
+            .process_group(0)
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        assert_eq!(line.trim(), "ready");
+        send_user_signal(child.id(), UserSignal::Usr1).unwrap();
+        line.clear();
+        stdout.read_line(&mut line).unwrap();
+        child.wait().unwrap();
+        assert_eq!(line.trim(), "1");
+        assert!(send_user_signal(child.id(), UserSignal::Usr1).is_err());
+    }
+
+    #[test]
+    fn shutdown_stage_falls_back_when_sigint_disabled() {
+        let shutdown = ShutdownConfig::new(0, 500);
+        let now = tokio::time::Instant::now();
+        let (stage, signal, deadline) =
+            ProcessManager::initial_shutdown_stage(shutdown, ProcessSignal::SigInt, now);
+        assert!(matches!(stage, ShutdownStage::SigTerm));
+        assert_eq!(signal, Some(ProcessSignal::SigTerm));
+        assert_eq!(deadline, now + Duration::from_millis(500));
+    }
+
+    #[test]
+    fn shutdown_stage_handles_all_disabled() {
+        let shutdown = ShutdownConfig::new(0, 0);
+        let now = tokio::time::Instant::now();
+        let (stage, signal, deadline) =
+            ProcessManager::initial_shutdown_stage(shutdown, ProcessSignal::SigTerm, now);
+        assert!(matches!(stage, ShutdownStage::Kill));
+        assert_eq!(signal, None);
+        assert_eq!(deadline, now);
+    }
+
+    #[test]
+    fn shutdown_config_flags() {
+        let config = ShutdownConfig::new(100, 0);
+        assert!(config.sigint_enabled());
+        assert!(!config.sigterm_enabled());
+        assert_eq!(config.sigint_timeout(), Duration::from_millis(100));
+        assert_eq!(config.sigterm_timeout(), Duration::from_millis(0));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn poll_shutdowns_advances_stage() {
+        let spec = ProcessSpec {
+            cmd: "sleep".to_string(),
+            args: vec!["5".to_string()],
+            ..test_spec("sleep")
+        };
+        let (tx, _rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(10, 1000);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let mut manager = ProcessManager::new(vec![spec], tx, output_tx, shutdown, false);
+        let child = tokio::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        manager.processes[0].child = Some(child);
+        manager.processes[0].shutdown = Some(ShutdownState {
+            stage: ShutdownStage::SigInt,
+            deadline: tokio::time::Instant::now() - Duration::from_millis(1),
+        });
+
+        manager.poll_shutdowns().await;
+        let stage = manager.processes[0].shutdown.unwrap().stage;
+        assert!(matches!(stage, ShutdownStage::SigTerm | ShutdownStage::Kill));
+
+        if let Some(mut child) = manager.processes[0].child.take() {
+            let _ = child.kill().await;
+            let _ = child.wait().await;
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn poll_exits_reports_the_killing_signal() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(1000, 1000);
+        let mut manager =
+            ProcessManager::new(vec![test_spec("sleep")], tx, output_tx, shutdown, false);
+        let child = tokio::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        unsafe { libc::kill(child.id().unwrap() as i32, libc::SIGTERM) };
+        manager.processes[0].child = Some(child);
+
+        while manager.processes[0].child.is_some() {
+            manager.poll_exits().await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(matches!(
+            rx.recv().await,
+            Some(Event::ProcessExited {
+                id: 0,
+                code: None,
+                signal: Some(libc::SIGTERM),
+                stopped: false,
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn poll_timeouts_stops_expired_processes() {
+        let spec = ProcessSpec {
+            cmd: "sleep".to_string(),
+            args: vec!["5".to_string()],
+            timeout_ms: Some(10),
+            ..test_spec("sleep")
+        };
+        let (tx, mut rx) = mpsc::channel(4);
+        let (output_tx, _output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(1000, 1000);
+        let mut manager = ProcessManager::new(vec![spec], tx, output_tx, shutdown, false);
+        let child = tokio::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        manager.processes[0].child = Some(child);
+        manager.processes[0].deadline = Some(tokio::time::Instant::now() + Duration::from_secs(60));
+
+        manager.poll_timeouts().await;
+        assert!(manager.processes[0].shutdown.is_none());
+
+        manager.processes[0].deadline = Some(tokio::time::Instant::now() - Duration::from_millis(1));
+        manager.poll_timeouts().await;
+        assert!(matches!(rx.recv().await, Some(Event::ProcessTimedOut { id: 0 })));
+        assert!(manager.processes[0].shutdown.is_some());
+        assert!(manager.processes[0].deadline.is_none());
+
+        if let Some(mut child) = manager.processes[0].child.take() {
+            let _ = child.kill().await;
+            let _ = child.wait().await;
+        }
+    }
+
+    #[test]
+    fn line_matchers_fire_each_trigger_once() {
+        let mut spec = ProcessSpec {
+            ready_check: Some(ReadinessCheck::Log("listening".to_string())),
+            ..test_spec("api")
+        };
+        for pattern in ["EADDRINUSE", "FATAL: .*connections", "("] {
+            spec.triggers.push(Trigger {
+                pattern: pattern.to_string(),
+                action: TriggerAction::Fail,
+            });
+        }
+        let matchers = LineMatchers::new(&spec);
+        assert!(matchers.ready.is_some());
+        assert!(matchers.fire("listening on :3000").is_empty());
+        assert_eq!(matchers.fire("FATAL: too many connections, EADDRINUSE"), vec![0, 1]);
+        assert!(matchers.fire("Error: EADDRINUSE").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_hook_logs_output_with_prefix() {
+        let spec = ProcessSpec {
+            env: HashMap::from([("STAGE".to_string(), "dev".to_string())]),
+            hooks: HooksConfig {
+                exit: Some(
+                    "sh -c 'echo $STAGE $PIPERACK_NAME $PIPERACK_PID $PIPERACK_EXIT_CODE; exit 4'"
+                        .to_string(),
+                ),
+                ..Default::default()
+            },
+            ..test_spec("sleep")
+        };
+        let (tx, _rx) = mpsc::channel(4);
+        let (output_tx, mut output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(1000, 1000);
+        let mut manager = ProcessManager::new(vec![spec], tx, output_tx, shutdown, false);
+
+        manager.run_hook(0, Hook::Start, Some(42), None);
+        manager.run_hook(0, Hook::Exit, Some(42), Some(3));
+        manager.wait_for_hooks(Duration::from_secs(5)).await;
+
+        let mut lines = Vec::new();
+        while let Ok(Event::ProcessOutput { line, .. }) = output_rx.try_recv() {
+            lines.push(line);
+        }
+        assert_eq!(lines, vec!["[hook] dev sleep 42 3", "[hook] exit hook exited with code 4"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn exit_hook_gets_empty_exit_code_after_signal_death() {
+        let spec = ProcessSpec {
+            hooks: HooksConfig {
+                exit: Some(
+                    "sh -c 'echo \"$PIPERACK_NAME/$PIPERACK_PID/$PIPERACK_EXIT_CODE\"'".to_string(),
+                ),
+                ..Default::default()
+            },
+            ..test_spec("sleep")
+        };
+        let (tx, mut rx) = mpsc::channel(4);
+        let (output_tx, mut output_rx) = mpsc::channel(4);
+        let shutdown = ShutdownConfig::new(1000, 1000);
+        let mut manager = ProcessManager::new(vec![spec], tx, output_tx, shutdown, false);
+        let child = tokio::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let pid = child.id().unwrap();
+        unsafe { libc::kill(pid as i32, libc::SIGKILL) };
+        manager.processes[0].child = Some(child);
+
+        while manager.processes[0].child.is_some() {
+            manager.poll_exits().await;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let Some(Event::ProcessExited { code, .. }) = rx.recv().await else {
+            panic!("expected an exit event");
+        };
+        manager.run_hook(0, Hook::Exit, Some(pid), code);
+        manager.wait_for_hooks(Duration::from_secs(5)).await;
+
+        let Ok(Event::ProcessOutput { line, .. }) = output_rx.try_recv() else {
+            panic!("expected hook output");
+        };
+        assert_eq!(line, format!("[hook] sleep/{}/", pid));
+    }
+}
@@ -0,0 +1,726 @@
+//! Detachable sessions.
+//!
+//! `piperack --detach` starts a headless supervisor in the background that owns the
+//! processes and their log buffers. `piperack attach` connects to its control socket with
+//! an `attach` request, receives a snapshot of every process (spec, status and buffered
+//! logs) and then a stream of output and state changes, which it mirrors into a local
+//! `App`. Actions taken in the attached TUI are sent back as control requests; detaching
+//! (`Ctrl-b d`) only closes the client.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+use crate::app::{App, AppAction};
+use crate::control::{self, ControlRequest};
+use crate::keys::KeyMap;
+use crate::output::StreamKind;
+use crate::process::{ProcessSpec, ProcessState, ProcessStatus};
+use crate::theme::Theme;
+
+/// One message streamed to an attached client.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SessionMessage {
+    Snapshot {
+        supervisor_pid: u32,
+        max_lines: usize,
+        use_symbols: bool,
+        input_enabled: bool,
+        processes: Vec<ProcessSnapshot>,
+    },
+    /// Lines appended to a process's log buffer since the last message.
+    Output { id: usize, lines: Vec<Value> },
+    /// New status of a process (the `status` request's per-process object).
+    State { id: usize, state: Value },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProcessSnapshot {
+    spec: SpecSnapshot,
+    state: Value,
+    logs: Vec<Value>,
+}
+
+/// The parts of a `ProcessSpec` the TUI needs.
+#[derive(Debug, Serialize, Deserialize)]
+struct SpecSnapshot {
+    name: String,
+    cmd: String,
+    args: Vec<String>,
+    cwd: Option<String>,
+    color: Option<String>,
+    follow: bool,
+    depends_on: Vec<String>,
+    ports: Vec<u16>,
+    tags: Vec<String>,
+}
+
+impl SpecSnapshot {
+    fn new(spec: &ProcessSpec) -> Self {
+        Self {
+            name: spec.name.clone(),
+            cmd: spec.cmd.clone(),
+            args: spec.args.clone(),
+            cwd: spec.cwd.clone(),
+            color: spec.color.clone(),
+            follow: spec.follow,
+            depends_on: spec.depends_on.clone(),
+            ports: spec.ports.clone(),
+            tags: spec.tags.clone(),
+        }
+    }
+
+    fn into_spec(self) -> ProcessSpec {
+        ProcessSpec {
+            name: self.name,
+            cmd: self.cmd,
+            args: self.args,
+            cwd: self.cwd,
+            color: self.color,
+            env: Default::default(),
+            restart_on_fail: false,
+            on_exit: None,
+            success_codes: vec![0],
+            success_on_stop: false,
+            timeout_ms: None,
+            hooks: Default::default(),
+            follow: self.follow,
+            pre_cmd: None,
+            watch_paths: Vec::new(),
+            watch_ignore: Vec::new(),
+            watch_ignore_gitignore: false,
+            watch_debounce_ms: 0,
+            depends_on: self.depends_on,
+            ready_check: None,
+            triggers: Vec::new(),
+            ports: self.ports,
+            tags: self.tags,
+        }
+    }
+}
+
+/// Status fields whose change is pushed to attached clients.
+type StateKey = (ProcessStatus, Option<u32>, Option<i32>, bool);
+
+fn state_key(process: &ProcessState) -> StateKey {
+    (
+        process.status.clone(),
+        process.pid,
+        process.exit_code,
+        process.ready,
+    )
+}
+
+#[derive(Debug)]
+struct Seen {
+    pushed: u64,
+    state: StateKey,
+}
+
+#[derive(Debug)]
+struct AttachedClient {
+    reply: mpsc::Sender<String>,
+    seen: Vec<Seen>,
+}
+
+impl AttachedClient {
+    /// Sends whatever changed since the last sync. Returns `false` once the client is gone.
+    ///
+    /// A full queue leaves the remaining changes for the next sync instead of dropping them.
+    fn sync(&mut self, app: &App) -> bool {
+        for (id, (process, seen)) in app.processes.iter().zip(&mut self.seen).enumerate() {
+            let pushed = process.logs.total_pushed();
+            if pushed > seen.pushed {
+                let new = (pushed - seen.pushed).min(process.logs.len() as u64) as usize;
+                let mut lines: Vec<Value> =
+                    process.logs.iter().rev().take(new).map(control::log_json).collect();
+                lines.reverse();
+                match send(&self.reply, &SessionMessage::Output { id, lines }) {
+                    Ok(()) => seen.pushed = pushed,
+                    Err(TrySendError::Full(_)) => return true,
+                    Err(TrySendError::Closed(_)) => return false,
+                }
+            }
+            let state = state_key(process);
+            if state != seen.state {
+                let message = SessionMessage::State {
+                    id,
+                    state: control::process_json(process),
+                };
+                match send(&self.reply, &message) {
+                    Ok(()) => seen.state = state,
+                    Err(TrySendError::Full(_)) => return true,
+                    Err(TrySendError::Closed(_)) => return false,
+                }
+            }
+        }
+        true
+    }
+}
+
+fn send(reply: &mpsc::Sender<String>, message: &SessionMessage) -> Result<(), TrySendError<String>> {
+    let payload = serde_json::to_string(message).unwrap_or_default();
+    reply.try_send(payload)
+}
+
+/// TUI clients attached to this session.
+#[derive(Debug)]
+pub struct AttachedClients {
+    clients: Vec<AttachedClient>,
+    max_lines: usize,
+    use_symbols: bool,
+    input_enabled: bool,
+}
+
+impl AttachedClients {
+    pub fn new(max_lines: usize, use_symbols: bool, input_enabled: bool) -> Self {
+        Self {
+            clients: Vec::new(),
+            max_lines,
+            use_symbols,
+            input_enabled,
+        }
+    }
+
+    /// Sends the current state to a newly attached client and starts streaming to it.
+    pub fn attach(&mut self, reply: mpsc::Sender<String>, app: &App) {
+        let processes = app
+            .processes
+            .iter()
+            .map(|process| ProcessSnapshot {
+                spec: SpecSnapshot::new(&process.spec),
+                state: control::process_json(process),
+                logs: process.logs.iter().map(control::log_json).collect(),
+            })
+            .collect();
+        let snapshot = SessionMessage::Snapshot {
+            supervisor_pid: std::process::id(),
+            max_lines: self.max_lines,
+            use_symbols: self.use_symbols,
+            input_enabled: self.input_enabled,
+            processes,
+        };
+        if send(&reply, &snapshot).is_err() {
+            return;
+        }
+        let seen = app
+            .processes
+            .iter()
+            .map(|process| Seen {
+                pushed: process.logs.total_pushed(),
+                state: state_key(process),
+            })
+            .collect();
+        self.clients.push(AttachedClient { reply, seen });
+    }
+
+    /// Pushes new output and state changes to every attached client.
+    pub fn sync(&mut self, app: &App) {
+        self.clients.retain_mut(|client| client.sync(app));
+    }
+}
+
+/// Mirrors a streamed process state into the local `App`.
+fn apply_state(app: &mut App, id: usize, state: &Value) {
+    let pid = state["pid"].as_u64().map(|pid| pid as u32);
+    let exit_code = state["exit_code"].as_i64().map(|code| code as i32);
+    let stopped = state["stopped"].as_bool().unwrap_or(false);
+    match state["status"].as_str().unwrap_or_default() {
+        "starting" => app.on_process_starting(id),
+        "running" => app.on_process_started(id, pid.unwrap_or_default()),
+        "exited" => app.on_process_exited(id, exit_code, None, stopped),
+        "failed" => app.on_process_failed(
+            id,
+            state["error"].as_str().unwrap_or_default().to_string(),
+        ),
+        _ => {
+            if let Some(process) = app.processes.get_mut(id) {
+                process.status = ProcessStatus::Idle;
+            }
+        }
+    }
+    if let Some(process) = app.processes.get_mut(id) {
+        process.pid = pid;
+        process.exit_code = exit_code;
+        process.ready = state["ready"].as_bool().unwrap_or(false);
+        // The client's spec has no success codes, so take the supervisor's verdict.
+        process.succeeded = state["succeeded"].as_bool().unwrap_or(false);
+        process.started_at = state["uptime_secs"]
+            .as_u64()
+            .and_then(|secs| Instant::now().checked_sub(Duration::from_secs(secs)));
+    }
+}
+
+fn apply_lines(app: &mut App, id: usize, lines: &[Value]) {
+    for line in lines {
+        let stream = match line["stream"].as_str() {
+            Some("stderr") => StreamKind::Stderr,
+            _ => StreamKind::Stdout,
+        };
+        let text = line["line"].as_str().unwrap_or_default().to_string();
+        app.on_process_output(id, text, stream);
+    }
+}
+
+/// Builds the client-side `App` from the session snapshot. Returns it with the PID of the
+/// supervisor.
+fn app_from_snapshot(message: SessionMessage) -> Result<(App, u32)> {
+    let SessionMessage::Snapshot {
+        supervisor_pid,
+        max_lines,
+        use_symbols,
+        input_enabled,
+        processes,
+    } = message
+    else {
+        bail!("unexpected reply from session");
+    };
+    let mut specs = Vec::new();
+    let mut states = Vec::new();
+    for process in processes {
+        specs.push(process.spec.into_spec());
+        states.push((process.state, process.logs));
+    }
+    let mut app = App::new(specs, max_lines, use_symbols, input_enabled);
+    for (id, (state, logs)) in states.iter().enumerate() {
+        apply_lines(&mut app, id, logs);
+        apply_state(&mut app, id, state);
+    }
+    Ok((app, supervisor_pid))
+}
+
+/// Why the attached client stopped.
+enum Outcome {
+    Detached,
+    Ended,
+}
+
+/// Attaches a TUI to the session listening on `path`.
+#[cfg(unix)]
+pub async fn run_attach(path: &Path, keys: KeyMap, theme: Theme) -> Result<()> {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    use crate::events::Event;
+    use crate::reaper::TreeTracker;
+    use crate::stats::{self, StatsSampler};
+    use crate::{procfs, tui};
+
+    let stream = UnixStream::connect(path).await.with_context(|| {
+        format!(
+            "no running piperack session found at {} (start one with `piperack --detach`)",
+            path.display()
+        )
+    })?;
+    let (read, mut write) = stream.into_split();
+    let payload = serde_json::to_string(&ControlRequest::Attach)?;
+    write.write_all(format!("{}\n", payload).as_bytes()).await?;
+    let mut lines = BufReader::new(read).lines();
+    let first = lines
+        .next_line()
+        .await?
+        .context("session closed before sending its state")?;
+    let message: SessionMessage = serde_json::from_str(&first)
+        .with_context(|| format!("invalid reply from session: {}", first))?;
+    let (mut app, supervisor_pid) = app_from_snapshot(message)?;
+    app.keys = keys;
+    app.theme = theme;
+
+    let (event_tx, mut event_rx) = mpsc::channel(256);
+    let (notice_tx, mut notice_rx) = mpsc::channel(16);
+    let requests = spawn_request_worker(path, notice_tx);
+    let mut tree_tracker = TreeTracker::with_supervisor(
+        app.processes
+            .iter()
+            .map(|process| process.spec.name.clone())
+            .collect(),
+        supervisor_pid,
+    );
+    let mut stats_sampler = StatsSampler::new();
+    let mut last_stats_at = Instant::now() - stats::SAMPLE_INTERVAL;
+    let draw_interval = Duration::from_millis(33);
+    let mut last_draw = Instant::now() - draw_interval;
+    let mut ticker = tokio::time::interval(Duration::from_millis(150));
+    let mut prefix_pending = false;
+
+    let mut terminal = tui::init_terminal()?;
+    crate::spawn_input_listener(event_tx);
+    app.set_status_warning_for("attached (Ctrl-b d to detach)", Duration::from_secs(3));
+
+    let outcome = loop {
+        tokio::select! {
+            Some(event) = event_rx.recv() => {
+                let action = match event {
+                    Event::Key(key) => {
+                        if prefix_pending {
+                            prefix_pending = false;
+                            if key.code == KeyCode::Char('d') {
+                                break Outcome::Detached;
+                            }
+                            app.set_status_message("");
+                            AppAction::None
+                        } else if key.code == KeyCode::Char('b')
+                            && key.modifiers.contains(KeyModifiers::CONTROL)
+                        {
+                            prefix_pending = true;
+                            app.set_status_warning_persistent("Ctrl-b: press d to detach");
+                            AppAction::None
+                        } else {
+                            app.handle_key(key)
+                        }
+                    }
+                    Event::Mouse(mouse) => app.handle_mouse(mouse),
+                    Event::Resize { .. } => {
+                        let _ = terminal.autoresize();
+                        AppAction::None
+                    }
+                    _ => AppAction::None,
+                };
+                forward_action(action, &mut app, &requests);
+            }
+            line = lines.next_line() => {
+                let Ok(Some(line)) = line else {
+                    break Outcome::Ended;
+                };
+                match serde_json::from_str(&line) {
+                    Ok(SessionMessage::Output { id, lines }) => apply_lines(&mut app, id, &lines),
+                    Ok(SessionMessage::State { id, state }) => apply_state(&mut app, id, &state),
+                    _ => {}
+                }
+            }
+            Some(notice) = notice_rx.recv() => {
+                app.set_status_warning_for(notice, Duration::from_secs(3));
+            }
+            _ = ticker.tick() => {
+                if last_stats_at.elapsed() >= stats::SAMPLE_INTERVAL {
+                    last_stats_at = Instant::now();
+                    let entries = procfs::snapshot();
+                    let pids = app.running_pids();
+                    let samples = stats_sampler.sample(&pids, &entries);
+                    for (id, sample) in samples.into_iter().enumerate() {
+                        app.on_process_stats(id, sample);
+                    }
+                    tree_tracker.update(&pids, &entries);
+                    for (id, pid) in pids.iter().enumerate() {
+                        app.on_process_tree(id, tree_tracker.tree(id, *pid, &entries));
+                    }
+                }
+            }
+        }
+
+        if last_draw.elapsed() >= draw_interval {
+            if let Err(err) = tui::draw(&mut app, &mut terminal) {
+                tui::restore_terminal(terminal)?;
+                return Err(err.into());
+            }
+            last_draw = Instant::now();
+        }
+    };
+
+    tui::restore_terminal(terminal)?;
+    let message = match outcome {
+        Outcome::Detached => "detached; processes keep running (reattach with `piperack attach`, stop with `piperack ctl shutdown`)",
+        Outcome::Ended => "session ended",
+    };
+    eprintln!("{}", crate::format_tool_message(message, app.use_symbols));
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn run_attach(_path: &Path, _keys: KeyMap, _theme: Theme) -> Result<()> {
+    bail!("detached sessions are only supported on Unix")
+}
+
+/// Sends control requests one at a time (so keystrokes arrive in order) and reports
+/// failures and exported paths back as status notices.
+fn spawn_request_worker(
+    path: &Path,
+    notices: mpsc::Sender<String>,
+) -> mpsc::UnboundedSender<ControlRequest> {
+    let path = path.to_path_buf();
+    let (tx, mut rx) = mpsc::unbounded_channel::<ControlRequest>();
+    tokio::spawn(async move {
+        while let Some(request) = rx.recv().await {
+            let mut notice = None;
+            let result = control::send_request(&path, &request, |reply| {
+                if reply["ok"] == false {
+                    notice = reply["error"].as_str().map(str::to_string);
+                } else if let Some(exported) = reply["path"].as_str() {
+                    notice = Some(format!("Exported logs to {}", exported));
+                }
+                false
+            })
+            .await;
+            if let Err(err) = result {
+                notice = Some(format!("{:#}", err));
+            }
+            if let Some(notice) = notice {
+                let _ = notices.send(notice).await;
+            }
+        }
+    });
+    tx
+}
+
+/// Turns an action from the attached TUI into a control request for the supervisor.
+fn forward_action(
+    action: AppAction,
+    app: &mut App,
+    requests: &mpsc::UnboundedSender<ControlRequest>,
+) {
+    let name = |app: &App, id: usize| {
+        app.processes
+            .get(id)
+            .map(|process| process.spec.name.clone())
+    };
+    let request = match action {
+        AppAction::None | AppAction::KillPortOwner { .. } => None,
+        AppAction::CopySelection => {
+            crate::copy_selection(app);
+            None
+        }
+        AppAction::Quit => {
+            app.should_quit = false;
+            app.set_status_warning_persistent("stopping session");
+            Some(ControlRequest::Shutdown)
+        }
+        AppAction::Kill(id) => name(app, id).map(|name| ControlRequest::Kill { name }),
+        AppAction::Restart(id) => name(app, id).map(|name| ControlRequest::Restart { name }),
+        AppAction::RestartGroup(group) => Some(ControlRequest::RestartGroup { group }),
+        AppAction::KillGroup(group) => {
+            for process in &app.processes {
+                if group == "all" || process.spec.tags.contains(&group) {
+                    let name = process.spec.name.clone();
+                    let _ = requests.send(ControlRequest::Kill { name });
+                }
+            }
+            None
+        }
+        AppAction::Signal(id, signal) => {
+            name(app, id).map(|name| ControlRequest::Signal { name, signal })
+        }
+        AppAction::Export(id) => name(app, id).map(|name| ControlRequest::Export { name }),
+        AppAction::SendInputText(id, text) => {
+            name(app, id).map(|name| ControlRequest::Input { name, text })
+        }
+        AppAction::SendInputBytes(id, bytes) => {
+            name(app, id).map(|name| ControlRequest::InputBytes { name, bytes })
+        }
+    };
+    if let Some(request) = request {
+        let _ = requests.send(request);
+    }
+}
+
+/// Creates the supervisor log as a new file only the current user can read.
+///
+/// The socket may live in a world-writable directory such as `/tmp`, so an existing entry
+/// (a previous session's log, or a symlink planted by someone else) is never written through:
+/// it is removed first, and creation fails if another one appears in between.
+#[cfg(unix)]
+fn create_private_log(path: &Path) -> std::io::Result<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path)
+}
+
+/// Starts this invocation again as a headless supervisor in the background and waits
+/// until its control socket accepts connections.
+///
+/// The supervisor's own messages go to a log file next to the socket.
+#[cfg(unix)]
+pub fn spawn_detached(socket: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+        bail!(
+            "a piperack session is already running at {} (attach with `piperack attach`)",
+            socket.display()
+        );
+    }
+    let log_path = socket.with_extension("log");
+    let log = create_private_log(&log_path)
+        .with_context(|| format!("failed to create {}", log_path.display()))?;
+    let exe = std::env::current_exe().context("failed to locate the piperack executable")?;
+    let mut args: Vec<_> = std::env::args_os().skip(1).collect();
+    if let Some(pos) = args.iter().position(|arg| arg == "--detach") {
+        args.remove(pos);
+    }
+    let mut command = Command::new(exe);
+    command
+        .arg("--headless")
+        .args(args)
+        .env(control::SOCKET_ENV, socket)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    // SAFETY: setsid is async-signal-safe and only detaches the child from our terminal.
+    unsafe {
+        command.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+    let mut child = command.spawn().context("failed to start background session")?;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+            println!(
+                "started piperack session (pid {}); attach with `piperack attach`",
+                child.id()
+            );
+            return Ok(());
+        }
+        if let Some(status) = child.try_wait()? {
+            let output = std::fs::read_to_string(&log_path).unwrap_or_default();
+            bail!("background session exited ({}):\n{}", status, output.trim_end());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    bail!(
+        "background session did not open its control socket; see {}",
+        log_path.display()
+    )
+}
+
+#[cfg(not(unix))]
+pub fn spawn_detached(_socket: &Path) -> Result<()> {
+    bail!("detached sessions are only supported on Unix")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_spec(name: &str) -> ProcessSpec {
+        SpecSnapshot {
+            name: name.to_string(),
+            cmd: "echo".to_string(),
+            args: Vec::new(),
+            cwd: None,
+            color: None,
+            follow: true,
+            depends_on: Vec::new(),
+            ports: Vec::new(),
+            tags: vec!["web".to_string()],
+        }
+        .into_spec()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_log_never_writes_through_a_symlink() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("piperack-session-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("target.txt");
+        std::fs::write(&target, "keep me").unwrap();
+        let log_path = dir.join("session.log");
+        std::os::unix::fs::symlink(&target, &log_path).unwrap();
+
+        let mut log = create_private_log(&log_path).unwrap();
+        std::io::Write::write_all(&mut log, b"supervisor").unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "keep me");
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "supervisor");
+        let meta = std::fs::symlink_metadata(&log_path).unwrap();
+        assert!(meta.file_type().is_file());
+        assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn recv(rx: &mut mpsc::Receiver<String>) -> SessionMessage {
+        serde_json::from_str(&rx.try_recv().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn snapshot_rebuilds_app_state() {
+        let mut app = App::new(vec![make_spec("api"), make_spec("web")], 100, false, true);
+        app.on_process_output(0, "booting".to_string(), StreamKind::Stdout);
+        app.on_process_output(0, "oops".to_string(), StreamKind::Stderr);
+        app.on_process_started(0, 42);
+        app.on_process_ready(0);
+        app.processes[1].spec.success_codes = vec![0, 3];
+        app.on_process_exited(1, Some(3), None, true);
+
+        let mut attached = AttachedClients::new(100, false, true);
+        let (tx, mut rx) = mpsc::channel(8);
+        attached.attach(tx, &app);
+        let (mirror, supervisor_pid) = app_from_snapshot(recv(&mut rx)).unwrap();
+
+        assert_eq!(supervisor_pid, std::process::id());
+        assert_eq!(mirror.processes[0].spec.tags, vec!["web".to_string()]);
+        let logs: Vec<_> = mirror.processes[0].logs.iter().cloned().collect();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[1].stream, StreamKind::Stderr);
+        assert_eq!(mirror.processes[0].status, ProcessStatus::Running);
+        assert_eq!(mirror.processes[0].pid, Some(42));
+        assert!(mirror.processes[0].ready);
+        assert_eq!(
+            mirror.processes[1].status,
+            ProcessStatus::Exited { code: Some(3) }
+        );
+        assert!(mirror.processes[1].stopped);
+        assert!(mirror.processes[1].succeeded);
+    }
+
+    #[test]
+    fn sync_streams_only_changes() {
+        let mut app = App::new(vec![make_spec("api")], 100, false, true);
+        let mut attached = AttachedClients::new(100, false, true);
+        let (tx, mut rx) = mpsc::channel(8);
+        attached.attach(tx, &app);
+        let _ = recv(&mut rx);
+
+        attached.sync(&app);
+        assert!(rx.try_recv().is_err());
+
+        app.on_process_output(0, "one".to_string(), StreamKind::Stdout);
+        app.on_process_output(0, "two".to_string(), StreamKind::Stdout);
+        app.on_process_started(0, 7);
+        attached.sync(&app);
+        match recv(&mut rx) {
+            SessionMessage::Output { id, lines } => {
+                assert_eq!(id, 0);
+                assert_eq!(lines.len(), 2);
+                assert_eq!(lines[0]["line"], "one");
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+        match recv(&mut rx) {
+            SessionMessage::State { state, .. } => assert_eq!(state["pid"], 7),
+            other => panic!("unexpected message: {:?}", other),
+        }
+        attached.sync(&app);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn sync_drops_disconnected_clients() {
+        let mut app = App::new(vec![make_spec("api")], 100, false, true);
+        let mut attached = AttachedClients::new(100, false, true);
+        let (tx, rx) = mpsc::channel(8);
+        attached.attach(tx, &app);
+        drop(rx);
+        app.on_process_output(0, "one".to_string(), StreamKind::Stdout);
+        attached.sync(&app);
+        assert!(attached.clients.is_empty());
+    }
+}
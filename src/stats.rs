@@ -0,0 +1,237 @@
+//! Resource usage sampling for managed processes.
+//!
+//! Each managed process runs in its own process group (see `ProcessManager::start_process`),
+//! so the `StatsSampler` aggregates CPU, memory and thread counts over every member of that
+//! group found in a `/proc` snapshot. Samples are stored per process in `ProcessStats`, which also keeps a short history
+//! for the detail view sparklines.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::procfs::{self, ProcEntry};
+
+/// How often the main loop samples process statistics.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of samples kept for sparklines.
+const HISTORY_LEN: usize = 60;
+
+/// A single resource usage sample for one process group.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatsSample {
+    /// Resident memory of the whole group, in bytes.
+    pub rss_bytes: u64,
+    /// CPU usage since the previous sample (100% = one full core).
+    pub cpu_percent: f32,
+    /// Total number of threads in the group.
+    pub threads: u32,
+    /// Number of group members other than the leader.
+    pub children: u32,
+}
+
+/// Latest sample plus history and peaks for a single process.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessStats {
+    /// Most recent sample, cleared when the process stops.
+    pub latest: Option<StatsSample>,
+    /// Recent CPU usage (percent, rounded) for sparklines.
+    pub cpu_history: VecDeque<u64>,
+    /// Recent resident memory (bytes) for sparklines.
+    pub rss_history: VecDeque<u64>,
+    /// Highest resident memory observed.
+    pub peak_rss_bytes: u64,
+    /// Highest CPU usage observed.
+    pub peak_cpu_percent: f32,
+    /// Highest child count observed.
+    pub peak_children: u32,
+}
+
+impl ProcessStats {
+    /// Records a new sample, or clears the latest one if the process is gone.
+    pub fn record(&mut self, sample: Option<StatsSample>) {
+        self.latest = sample;
+        let Some(sample) = sample else {
+            return;
+        };
+        push_capped(&mut self.cpu_history, sample.cpu_percent.round() as u64);
+        push_capped(&mut self.rss_history, sample.rss_bytes);
+        self.peak_rss_bytes = self.peak_rss_bytes.max(sample.rss_bytes);
+        self.peak_cpu_percent = self.peak_cpu_percent.max(sample.cpu_percent);
+        self.peak_children = self.peak_children.max(sample.children);
+    }
+
+    /// Returns `true` once at least one sample has been recorded.
+    pub fn has_samples(&self) -> bool {
+        !self.cpu_history.is_empty()
+    }
+}
+
+fn push_capped(history: &mut VecDeque<u64>, value: u64) {
+    history.push_back(value);
+    while history.len() > HISTORY_LEN {
+        history.pop_front();
+    }
+}
+
+/// Computes per-group samples from successive `/proc` snapshots.
+#[derive(Debug)]
+pub struct StatsSampler {
+    // Previous CPU tick total per group leader, used to compute deltas.
+    previous: HashMap<u32, (u64, Instant)>,
+    clock_ticks: u64,
+    page_size: u64,
+}
+
+impl Default for StatsSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatsSampler {
+    pub fn new() -> Self {
+        Self {
+            previous: HashMap::new(),
+            clock_ticks: procfs::clock_ticks(),
+            page_size: procfs::page_size(),
+        }
+    }
+
+    /// Samples every running process group, returning one entry per input PID.
+    pub fn sample(&mut self, pids: &[Option<u32>], entries: &[ProcEntry]) -> Vec<Option<StatsSample>> {
+        let now = Instant::now();
+        let samples = pids
+            .iter()
+            .map(|pid| pid.and_then(|pid| self.sample_group(pid, entries, now)))
+            .collect();
+        self.previous.retain(|leader, _| pids.contains(&Some(*leader)));
+        samples
+    }
+
+    fn sample_group(&mut self, leader: u32, entries: &[ProcEntry], now: Instant) -> Option<StatsSample> {
+        let members: Vec<&ProcEntry> = entries.iter().filter(|e| e.pgrp == leader).collect();
+        if members.is_empty() {
+            self.previous.remove(&leader);
+            return None;
+        }
+        let ticks: u64 = members.iter().map(|e| e.cpu_ticks).sum();
+        let cpu_percent = match self.previous.insert(leader, (ticks, now)) {
+            Some((prev_ticks, prev_at)) => cpu_percent(
+                ticks.saturating_sub(prev_ticks),
+                now.duration_since(prev_at),
+                self.clock_ticks,
+            ),
+            None => 0.0,
+        };
+        Some(StatsSample {
+            rss_bytes: members.iter().map(|e| e.rss_pages).sum::<u64>() * self.page_size,
+            cpu_percent,
+            threads: members.iter().map(|e| e.threads).sum(),
+            children: members.len().saturating_sub(1) as u32,
+        })
+    }
+}
+
+fn cpu_percent(delta_ticks: u64, elapsed: Duration, clock_ticks: u64) -> f32 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 || clock_ticks == 0 {
+        return 0.0;
+    }
+    (delta_ticks as f64 / clock_ticks as f64 / secs * 100.0) as f32
+}
+
+/// Formats a byte count using binary units (e.g. `48M`).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 && unit > 0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+/// Short inline summary for the process list (e.g. `3% 48M +2`).
+pub fn format_compact(sample: &StatsSample, use_symbols: bool) -> String {
+    let mut out = format!(
+        "{:.0}% {}",
+        sample.cpu_percent,
+        format_bytes(sample.rss_bytes)
+    );
+    if sample.children > 0 {
+        let marker = if use_symbols { "↳" } else { "+" };
+        out.push_str(&format!(" {}{}", marker, sample.children));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, pgrp: u32, ticks: u64, rss_pages: u64) -> ProcEntry {
+        ProcEntry {
+            pid,
+            state: 'S',
+            ppid: 1,
+            pgrp,
+            comm: "sh".to_string(),
+            cpu_ticks: ticks,
+            threads: 2,
+            start_time: 0,
+            rss_pages,
+        }
+    }
+
+    #[test]
+    fn sample_group_aggregates_members() {
+        let mut sampler = StatsSampler {
+            previous: HashMap::new(),
+            clock_ticks: 100,
+            page_size: 4096,
+        };
+        let start = Instant::now();
+        let entries = vec![entry(10, 10, 100, 10), entry(11, 10, 50, 5), entry(12, 99, 500, 1)];
+        let first = sampler.sample_group(10, &entries, start).unwrap();
+        assert_eq!(first.children, 1);
+        assert_eq!(first.threads, 4);
+        assert_eq!(first.rss_bytes, 15 * 4096);
+        assert_eq!(first.cpu_percent, 0.0);
+
+        let entries = vec![entry(10, 10, 150, 10), entry(11, 10, 100, 5)];
+        let second = sampler
+            .sample_group(10, &entries, start + Duration::from_secs(1))
+            .unwrap();
+        assert!((second.cpu_percent - 100.0).abs() < 0.01);
+        assert!(sampler.sample_group(42, &entries, start).is_none());
+    }
+
+    #[test]
+    fn process_stats_tracks_peaks_and_clears_latest() {
+        let mut stats = ProcessStats::default();
+        stats.record(Some(StatsSample {
+            rss_bytes: 2048,
+            cpu_percent: 12.4,
+            threads: 3,
+            children: 2,
+        }));
+        stats.record(None);
+        assert!(stats.latest.is_none());
+        assert!(stats.has_samples());
+        assert_eq!(stats.peak_rss_bytes, 2048);
+        assert_eq!(stats.peak_children, 2);
+        assert_eq!(stats.cpu_history.back(), Some(&12));
+    }
+
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(512 * 1024), "512K");
+        assert_eq!(format_bytes(5 * 1024 * 1024 + 512 * 1024), "5.5M");
+        assert_eq!(format_bytes(48 * 1024 * 1024), "48M");
+    }
+}
@@ -0,0 +1,416 @@
+//! TUI colours.
+//!
+//! Every colour the TUI draws comes from a [`Theme`]: one of the built-in themes picked by
+//! name in the `[theme]` config table, with optional per-element overrides on top. When the
+//! `NO_COLOR` environment variable is set and the config does not name a theme, the
+//! monochrome theme is used, which relies on bold, dim and reversed text only.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::process::ProcessStatus;
+
+/// A built-in theme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    /// The default, for dark terminal backgrounds.
+    #[default]
+    Dark,
+    /// For light terminal backgrounds.
+    Light,
+    /// Bright colours and bold text.
+    HighContrast,
+    /// Blue and orange instead of green and red (Okabe-Ito palette).
+    #[serde(alias = "colorblind", alias = "colour_blind")]
+    ColorBlind,
+    /// No colours at all; picked by `NO_COLOR`.
+    Monochrome,
+}
+
+/// The `[theme]` config table: a built-in theme and styles overriding parts of it.
+///
+/// A style is a space-separated list of a foreground colour, `on <colour>` for the
+/// background, and modifiers (`bold`, `dim`, `italic`, `underlined`, `reversed`), e.g.
+/// `"black on yellow"` or `"bold #ff8700"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub name: Option<ThemeName>,
+    /// Panel borders.
+    pub border: Option<String>,
+    /// The focused pane's border, the selection marker and picker prompts.
+    pub accent: Option<String>,
+    /// The log border while input is sent to the process.
+    pub input: Option<String>,
+    /// Group headers, key hints, labels and other secondary text.
+    pub muted: Option<String>,
+    /// Unselected processes in the sidebar.
+    pub sidebar: Option<String>,
+    /// Warnings and confirmation prompts.
+    pub warning: Option<String>,
+    pub idle: Option<String>,
+    pub starting: Option<String>,
+    pub running: Option<String>,
+    /// Processes that exited successfully.
+    pub exited: Option<String>,
+    /// Processes that failed or exited with a non-zero code.
+    pub failed: Option<String>,
+    /// The `name ›` prefix of processes without a `color`.
+    pub prefix: Option<String>,
+    /// Selected log lines and the highlighted entry in pickers.
+    pub selection: Option<String>,
+    /// Search matches.
+    pub search: Option<String>,
+    /// Lines a process wrote to stderr.
+    pub stderr: Option<String>,
+    /// The help overlay.
+    pub popup: Option<String>,
+    /// The CPU sparkline in the details pane.
+    pub cpu: Option<String>,
+    /// The memory sparkline in the details pane.
+    pub rss: Option<String>,
+}
+
+/// The resolved styles the TUI draws with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub border: Style,
+    pub accent: Style,
+    pub input: Style,
+    pub muted: Style,
+    pub sidebar: Style,
+    pub warning: Style,
+    pub idle: Style,
+    pub starting: Style,
+    pub running: Style,
+    pub exited: Style,
+    pub failed: Style,
+    pub prefix: Style,
+    pub selection: Style,
+    pub search: Style,
+    pub stderr: Style,
+    pub popup: Style,
+    pub cpu: Style,
+    pub rss: Style,
+    /// Whether per-process `color` settings are shown.
+    pub process_colors: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin(ThemeName::Dark)
+    }
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+fn on(fg: Color, bg: Color) -> Style {
+    Style::default().fg(fg).bg(bg)
+}
+
+fn modifier(modifier: Modifier) -> Style {
+    Style::default().add_modifier(modifier)
+}
+
+impl Theme {
+    pub fn builtin(name: ThemeName) -> Self {
+        let dark = Self {
+            border: fg(Color::DarkGray),
+            accent: fg(Color::Cyan),
+            input: fg(Color::Green),
+            muted: fg(Color::DarkGray),
+            sidebar: fg(Color::Gray),
+            warning: fg(Color::Yellow),
+            idle: fg(Color::DarkGray),
+            starting: fg(Color::Yellow),
+            running: fg(Color::Green),
+            exited: fg(Color::Gray),
+            failed: fg(Color::Red),
+            prefix: fg(Color::White),
+            selection: Style::default().bg(Color::DarkGray),
+            search: on(Color::Black, Color::Yellow),
+            stderr: Style::default(),
+            popup: on(Color::White, Color::DarkGray),
+            cpu: fg(Color::Green),
+            rss: fg(Color::Magenta),
+            process_colors: true,
+        };
+        match name {
+            ThemeName::Dark => dark,
+            ThemeName::Light => Self {
+                accent: fg(Color::Blue),
+                sidebar: Style::default(),
+                warning: fg(Color::Magenta),
+                starting: fg(Color::Blue),
+                exited: fg(Color::DarkGray),
+                prefix: Style::default(),
+                selection: on(Color::Black, Color::Gray),
+                stderr: fg(Color::Red),
+                popup: on(Color::Black, Color::Gray),
+                cpu: fg(Color::Blue),
+                ..dark
+            },
+            ThemeName::HighContrast => Self {
+                border: fg(Color::White),
+                accent: fg(Color::LightCyan).add_modifier(Modifier::BOLD),
+                input: fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+                muted: fg(Color::White),
+                sidebar: fg(Color::White),
+                warning: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                idle: fg(Color::White),
+                starting: fg(Color::LightYellow),
+                running: fg(Color::LightGreen),
+                exited: fg(Color::White),
+                failed: fg(Color::LightRed).add_modifier(Modifier::BOLD),
+                prefix: fg(Color::White).add_modifier(Modifier::BOLD),
+                selection: modifier(Modifier::REVERSED),
+                search: on(Color::Black, Color::LightYellow).add_modifier(Modifier::BOLD),
+                stderr: fg(Color::LightRed),
+                popup: on(Color::Black, Color::White),
+                cpu: fg(Color::LightGreen),
+                rss: fg(Color::LightMagenta),
+                ..dark
+            },
+            ThemeName::ColorBlind => {
+                let sky_blue = Color::Indexed(74);
+                let orange = Color::Indexed(214);
+                let vermillion = Color::Indexed(166);
+                Self {
+                    accent: fg(sky_blue),
+                    input: fg(sky_blue),
+                    warning: fg(orange),
+                    starting: fg(orange),
+                    running: fg(sky_blue),
+                    failed: fg(vermillion).add_modifier(Modifier::BOLD),
+                    search: on(Color::Black, orange),
+                    stderr: fg(vermillion),
+                    cpu: fg(sky_blue),
+                    rss: fg(orange),
+                    ..dark
+                }
+            }
+            ThemeName::Monochrome => Self {
+                border: Style::default(),
+                accent: modifier(Modifier::BOLD),
+                input: modifier(Modifier::BOLD),
+                muted: modifier(Modifier::DIM),
+                sidebar: Style::default(),
+                warning: modifier(Modifier::BOLD),
+                idle: modifier(Modifier::DIM),
+                starting: Style::default(),
+                running: Style::default(),
+                exited: modifier(Modifier::DIM),
+                failed: modifier(Modifier::BOLD),
+                prefix: modifier(Modifier::BOLD),
+                selection: modifier(Modifier::REVERSED),
+                search: modifier(Modifier::REVERSED | Modifier::BOLD),
+                stderr: Style::default(),
+                popup: modifier(Modifier::REVERSED),
+                cpu: Style::default(),
+                rss: Style::default(),
+                process_colors: false,
+            },
+        }
+    }
+
+    /// The theme when there is no `[theme]` table: dark, or monochrome under `NO_COLOR`.
+    pub fn from_env() -> Self {
+        if no_color_requested() {
+            Self::builtin(ThemeName::Monochrome)
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Resolves the `[theme]` table. Without a theme name, `no_color` (the `NO_COLOR`
+    /// environment variable) picks the monochrome theme.
+    pub fn from_config(config: Option<&ThemeConfig>, no_color: bool) -> Result<Self> {
+        let name = match config.and_then(|config| config.name) {
+            Some(name) => name,
+            None if no_color => ThemeName::Monochrome,
+            None => ThemeName::Dark,
+        };
+        let mut theme = Self::builtin(name);
+        let Some(config) = config else {
+            return Ok(theme);
+        };
+        let overrides = [
+            ("border", &config.border, &mut theme.border),
+            ("accent", &config.accent, &mut theme.accent),
+            ("input", &config.input, &mut theme.input),
+            ("muted", &config.muted, &mut theme.muted),
+            ("sidebar", &config.sidebar, &mut theme.sidebar),
+            ("warning", &config.warning, &mut theme.warning),
+            ("idle", &config.idle, &mut theme.idle),
+            ("starting", &config.starting, &mut theme.starting),
+            ("running", &config.running, &mut theme.running),
+            ("exited", &config.exited, &mut theme.exited),
+            ("failed", &config.failed, &mut theme.failed),
+            ("prefix", &config.prefix, &mut theme.prefix),
+            ("selection", &config.selection, &mut theme.selection),
+            ("search", &config.search, &mut theme.search),
+            ("stderr", &config.stderr, &mut theme.stderr),
+            ("popup", &config.popup, &mut theme.popup),
+            ("cpu", &config.cpu, &mut theme.cpu),
+            ("rss", &config.rss, &mut theme.rss),
+        ];
+        for (element, text, style) in overrides {
+            if let Some(text) = text {
+                *style = parse_style(text).map_err(|err| anyhow!("theme.{}: {}", element, err))?;
+            }
+        }
+        Ok(theme)
+    }
+
+    /// The style of a process's status character; `succeeded` is whether its last exit
+    /// counted as success (`ProcessState::succeeded`).
+    pub fn status_style(&self, status: &ProcessStatus, succeeded: bool) -> Style {
+        match status {
+            ProcessStatus::Idle => self.idle,
+            ProcessStatus::Starting => self.starting,
+            ProcessStatus::Running => self.running,
+            ProcessStatus::Exited { .. } if succeeded => self.exited,
+            ProcessStatus::Exited { .. } => self.failed,
+            ProcessStatus::Failed { .. } => self.failed,
+        }
+    }
+
+    /// The style of a process's name and prefix, from its `color` setting.
+    pub fn process_style(&self, color: Option<&str>) -> Style {
+        match color.filter(|_| self.process_colors).and_then(color_from_name) {
+            Some(color) => fg(color),
+            None => self.prefix,
+        }
+    }
+}
+
+/// Whether `NO_COLOR` asks for output without colours (set and not empty).
+pub fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+fn color_from_name(name: &str) -> Option<Color> {
+    match name.to_lowercase().as_str() {
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "magenta" => Some(Color::Magenta),
+        "cyan" => Some(Color::Cyan),
+        "gray" | "grey" => Some(Color::Gray),
+        "white" => Some(Color::White),
+        _ => None,
+    }
+}
+
+/// Parses a style such as `"black on yellow"`, `"bold red"` or `"reversed"`.
+pub fn parse_style(text: &str) -> Result<Style> {
+    let mut style = Style::default();
+    let mut words = text.split_whitespace();
+    let mut empty = true;
+    while let Some(word) = words.next() {
+        empty = false;
+        let lower = word.to_lowercase();
+        let flag = match lower.as_str() {
+            "bold" => Some(Modifier::BOLD),
+            "dim" => Some(Modifier::DIM),
+            "italic" => Some(Modifier::ITALIC),
+            "underline" | "underlined" => Some(Modifier::UNDERLINED),
+            "reversed" | "reverse" => Some(Modifier::REVERSED),
+            _ => None,
+        };
+        if let Some(flag) = flag {
+            style = style.add_modifier(flag);
+        } else if lower == "on" {
+            let Some(color) = words.next() else {
+                bail!("'on' must be followed by a background colour");
+            };
+            style = style.bg(parse_color(color)?);
+        } else if lower != "default" {
+            style = style.fg(parse_color(word)?);
+        }
+    }
+    if empty {
+        bail!("empty style; use \"default\" for the terminal's colours");
+    }
+    Ok(style)
+}
+
+fn parse_color(text: &str) -> Result<Color> {
+    Color::from_str(text).map_err(|_| {
+        anyhow!(
+            "unknown colour or modifier '{}' (use a colour name, #rrggbb or 0-255)",
+            text
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_styles() {
+        assert_eq!(parse_style("black on yellow").unwrap(), on(Color::Black, Color::Yellow));
+        assert_eq!(
+            parse_style("bold #ff8700").unwrap(),
+            fg(Color::Rgb(0xff, 0x87, 0x00)).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(parse_style("on 236").unwrap(), Style::default().bg(Color::Indexed(236)));
+        assert_eq!(parse_style("light_red").unwrap(), fg(Color::LightRed));
+        assert_eq!(parse_style("default").unwrap(), Style::default());
+        assert!(parse_style("bold on").is_err());
+        assert!(parse_style("").is_err());
+        assert!(parse_style("blinking red").unwrap_err().to_string().contains("'blinking'"));
+    }
+
+    #[test]
+    fn status_and_process_styles_follow_the_theme() {
+        let theme = Theme::default();
+        assert_eq!(theme.status_style(&ProcessStatus::Idle, false).fg, Some(Color::DarkGray));
+        assert_eq!(theme.status_style(&ProcessStatus::Running, false).fg, Some(Color::Green));
+        assert_eq!(theme.status_style(&ProcessStatus::Starting, false).fg, Some(Color::Yellow));
+        assert_eq!(
+            theme.status_style(&ProcessStatus::Exited { code: Some(1) }, false).fg,
+            Some(Color::Red)
+        );
+        // A custom success code (e.g. 130 in `success_codes`) is not a failure.
+        assert_eq!(
+            theme.status_style(&ProcessStatus::Exited { code: Some(130) }, true),
+            theme.exited
+        );
+        assert_eq!(theme.process_style(Some("cyan")).fg, Some(Color::Cyan));
+        assert_eq!(theme.process_style(Some("nope")).fg, Some(Color::White));
+
+        let mono = Theme::builtin(ThemeName::Monochrome);
+        assert_eq!(mono.status_style(&ProcessStatus::Running, false).fg, None);
+        assert_eq!(mono.process_style(Some("cyan")).fg, None);
+    }
+
+    #[test]
+    fn no_color_applies_only_without_a_theme_name() {
+        assert_eq!(Theme::from_config(None, true).unwrap(), Theme::builtin(ThemeName::Monochrome));
+        let config: ThemeConfig = toml::from_str(
+            r#"
+name = "light"
+search = "reversed"
+"#,
+        )
+        .unwrap();
+        let theme = Theme::from_config(Some(&config), true).unwrap();
+        assert_eq!(theme.search, modifier(Modifier::REVERSED));
+        assert_eq!(theme.running, Theme::builtin(ThemeName::Light).running);
+
+        let config: ThemeConfig = toml::from_str("stderr = \"purple\"").unwrap();
+        let err = Theme::from_config(Some(&config), false).unwrap_err().to_string();
+        assert!(err.starts_with("theme.stderr: unknown colour"), "{}", err);
+        assert!(toml::from_str::<ThemeConfig>("sidebr = \"red\"").is_err());
+    }
+}
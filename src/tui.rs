@@ -12,15 +12,18 @@ use crossterm::terminal::{
 };
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Wrap};
-use ratatui::Terminal;
+use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Sparkline, Wrap};
+use ratatui::{Frame, Terminal};
 
 use crate::ansi::ansi_spans;
 use crate::app::{App, InputMode, LogViewport};
-use crate::output::sanitize_text;
-use crate::process::ProcessStatus;
+use crate::output::{sanitize_text, StreamKind};
+use crate::panes::{Pane, SplitDirection};
+use crate::process::{ProcessState, ProcessStatus};
+use crate::stats::{format_bytes, format_compact};
+use crate::theme::Theme;
 
 /// Type alias for the specific terminal backend used.
 pub type TuiTerminal = Terminal<CrosstermBackend<Stdout>>;
@@ -55,6 +58,7 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
     let title = window_title(app);
     execute!(terminal.backend_mut(), SetTitle(title))?;
     terminal.draw(|frame| {
+        let theme = app.theme.clone();
         let area = frame.size();
         let vertical = Layout::default()
             .direction(Direction::Vertical)
@@ -68,6 +72,8 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
         app.process_list_width = main[0].width;
 
         let mut list_items = Vec::new();
+        // Process id (None for group headers) and height of each list row.
+        let mut rows: Vec<(Option<usize>, usize)> = Vec::new();
         let mut ui_selected_index = 0;
         let mut current_ui_index = 0;
         let mut last_tag: Option<String> = None;
@@ -78,10 +84,11 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
             if last_tag.as_deref() != Some(tag) {
                 // Add header
                 let header = ListItem::new(Line::from(vec![
-                    Span::styled("▼ ", Style::default().fg(Color::DarkGray)),
-                    Span::styled(tag, Style::default().fg(Color::DarkGray)),
+                    Span::styled("▼ ", theme.muted),
+                    Span::styled(tag, theme.muted),
                 ]));
                 list_items.push(header);
+                rows.push((None, 1));
                 current_ui_index += 1;
                 last_tag = Some(tag.to_string());
             }
@@ -92,7 +99,7 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
                 ui_selected_index = current_ui_index;
             }
 
-            let status = status_char(&process.status, app.use_symbols);
+            let status = status_char(&process.status, process.succeeded, app.use_symbols);
             let preview = process
                 .logs
                 .iter()
@@ -112,20 +119,27 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
             let base_style = if is_selected {
                 Style::default()
             } else {
-                Style::default().fg(Color::Gray)
+                theme.sidebar
             };
 
             let name_style = if is_selected {
-                process_color(process.spec.color.as_deref()).add_modifier(name_mod)
+                theme.process_style(process.spec.color.as_deref()).add_modifier(name_mod)
             } else {
-                process_color(process.spec.color.as_deref())
+                theme.process_style(process.spec.color.as_deref())
             };
 
-            text.lines.push(Line::from(vec![
-                Span::styled(indent_str, if is_selected { Style::default().fg(Color::Cyan) } else { base_style }),
-                Span::styled(format!("[{}] ", status), if is_selected { status_style(&process.status) } else { status_style(&process.status).add_modifier(Modifier::DIM) }),
+            let mut name_line = vec![
+                Span::styled(indent_str, if is_selected { theme.accent } else { base_style }),
+                Span::styled(format!("[{}] ", status), if is_selected { theme.status_style(&process.status, process.succeeded) } else { theme.status_style(&process.status, process.succeeded).add_modifier(Modifier::DIM) }),
                 Span::styled(process.spec.name.clone(), name_style),
-            ]));
+            ];
+            if let Some(sample) = &process.stats.latest {
+                name_line.push(Span::styled(
+                    format!("  {}", format_compact(sample, app.use_symbols)),
+                    theme.muted,
+                ));
+            }
+            text.lines.push(Line::from(name_line));
             if !preview.is_empty() {
                 let available_width = (main[0].width as usize).saturating_sub(4 + indent_str.len());
                 let trimmed = truncate(&preview, available_width);
@@ -134,11 +148,12 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
                     Span::styled(trimmed, base_style)
                 ]));
             }
+            rows.push((Some(proc_idx), text.lines.len()));
             list_items.push(ListItem::new(text));
             current_ui_index += 1;
         }
 
-        let border_style = Style::default().fg(Color::DarkGray);
+        let border_style = theme.border;
         let input_active = app
             .selected_process()
             .map(|process| process.input_active)
@@ -153,57 +168,129 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
             )
             .highlight_style(Style::default().add_modifier(Modifier::BOLD));
 
-        frame.render_stateful_widget(list, main[0], &mut list_state(ui_selected_index, current_ui_index));
+        let mut state = list_state(ui_selected_index, current_ui_index);
+        frame.render_stateful_widget(list, main[0], &mut state);
+        app.visible_processes = visible_rows(&rows, state.offset(), main[0].height.saturating_sub(2));
+
+        let log_panel = if app.show_details && !app.timeline_view {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(DETAILS_HEIGHT)])
+                .split(main[1]);
+            if let Some(process) = app.selected_process() {
+                draw_details(frame, process, split[1], &theme);
+            }
+            split[0]
+        } else {
+            main[1]
+        };
 
-        let log_block = Block::default()
-            .title(log_title(app))
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(if input_active {
-                Style::default().fg(Color::Green)
-            } else {
-                border_style
+        // The timeline is a single view; panes only split the per-process logs.
+        let pane_areas = if app.panes.is_empty() || app.timeline_view {
+            vec![log_panel]
+        } else {
+            let count = app.panes.len();
+            Layout::default()
+                .direction(match app.split_direction {
+                    SplitDirection::Vertical => Direction::Horizontal,
+                    SplitDirection::Horizontal => Direction::Vertical,
+                })
+                .constraints(vec![Constraint::Ratio(1, count as u32); count])
+                .split(log_panel)
+                .to_vec()
+        };
+        let split = pane_areas.len() > 1;
+        let mut pane_viewports = Vec::new();
+        for (index, pane_area) in pane_areas.into_iter().enumerate() {
+            let focused = !split || index == app.focused_pane;
+            // The focused pane renders from the live selection state.
+            let pane = if focused { None } else { app.panes.get(index).cloned() };
+            let mut title = match &pane {
+                Some(pane) => app
+                    .processes
+                    .get(pane.process)
+                    .map(process_title)
+                    .unwrap_or_else(|| "Logs".to_string()),
+                None => log_title(app),
+            };
+            if split {
+                title = format!("{}: {}", index + 1, title);
+            }
+            let log_block = Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(if focused && input_active {
+                    theme.input
+                } else if focused && split {
+                    theme.accent
+                } else {
+                    border_style
+                });
+            let log_area = log_block.inner(pane_area);
+            let log_height = log_area.height as usize;
+            pane_viewports.push(LogViewport {
+                x: pane_area.x,
+                y: pane_area.y,
+                width: pane_area.width,
+                height: pane_area.height,
             });
-        let log_area = log_block.inner(main[1]);
-        let log_height = log_area.height as usize;
-        app.set_log_view_height(log_height);
-
-        let (log_lines, total, raw_lines) = render_log_lines(app, log_height, log_area.width as usize);
-        app.set_log_viewport(LogViewport {
-            x: log_area.x,
-            y: log_area.y,
-            width: log_area.width,
-            height: log_area.height,
-        });
-        app.set_visible_raw_lines(raw_lines);
-        let paragraph = Paragraph::new(log_lines).block(log_block).wrap(Wrap { trim: false });
-
-        frame.render_widget(paragraph, main[1]);
+            if focused {
+                app.set_log_view_height(log_height);
+            }
+
+            let (log_lines, total, raw_lines) =
+                render_log_lines(app, pane.as_ref(), log_height, log_area.width as usize);
+            if focused {
+                app.set_log_viewport(LogViewport {
+                    x: log_area.x,
+                    y: log_area.y,
+                    width: log_area.width,
+                    height: log_area.height,
+                });
+                app.set_visible_raw_lines(raw_lines);
+            }
+            let paragraph = Paragraph::new(log_lines).block(log_block).wrap(Wrap { trim: false });
+
+            frame.render_widget(paragraph, pane_area);
+            if total == 0 {
+                let empty = Paragraph::new("No output yet")
+                    .style(theme.muted)
+                    .block(Block::default());
+                frame.render_widget(empty, log_area);
+            }
+        }
+        app.set_pane_viewports(if split { pane_viewports } else { Vec::new() });
 
         let status_line = app.status_line();
-        let default_help = if app.use_symbols {
-            "↑/↓ select | Tab cycle | Enter input | f follow | t timeline | a ansi | / search | F filter | n/N next/prev | r restart | g group | R all | k kill | j json | e export | Ctrl+C copy | q quit | ? help"
-        } else {
-            "Up/Down select | Tab cycle | Enter input | f follow | t timeline | a ansi | / search | F filter | n/N next/prev | r restart | g group | R all | k kill | j json | e export | Ctrl+C copy | q quit | ? help"
-        };
+        let default_help = app.keys.hints(app.use_symbols);
         let (mut help_line, mut help_style) = if let Some((message, level)) = app.status_message() {
-            let color = match level {
-                crate::app::StatusLevel::Warning => Color::Yellow,
-                crate::app::StatusLevel::Info => Color::DarkGray,
+            let style = match level {
+                crate::app::StatusLevel::Warning => theme.warning,
+                crate::app::StatusLevel::Info => theme.muted,
             };
-            (message.to_string(), Style::default().fg(color))
+            (message.to_string(), style)
         } else {
-            (default_help.to_string(), Style::default().fg(Color::DarkGray))
+            (default_help, theme.muted)
         };
         if app.input_mode == InputMode::Search {
             help_line = format!("Search: {} (Esc to exit)", app.input);
-            help_style = Style::default().fg(Color::DarkGray);
+            help_style = theme.muted;
         } else if app.input_mode == InputMode::Filter {
             help_line = format!("Filter: {} (Esc to exit)", app.input);
-            help_style = Style::default().fg(Color::DarkGray);
+            help_style = theme.muted;
         } else if app.input_mode == InputMode::Group {
             help_line = format!("Restart Group: {}", app.input);
-            help_style = Style::default().fg(Color::DarkGray);
+            help_style = theme.muted;
+        } else if app.input_mode == InputMode::Palette {
+            help_line = "Commands: type to filter | Up/Down select | Enter run | Esc close".to_string();
+            help_style = theme.muted;
+        } else if app.input_mode == InputMode::GoTo {
+            help_line = "Go to: type a name or tag | Up/Down select | Enter jump | Esc close".to_string();
+            help_style = theme.muted;
+        } else if app.input_mode == InputMode::Confirm {
+            help_line = format!("{} [y/n]", app.confirm_prompt().unwrap_or_default());
+            help_style = theme.warning;
         } else if app.input_mode == InputMode::Input {
             let cursor = if app.use_symbols { "▌" } else { "|" };
             let divider = if app.use_symbols { " · " } else { " | " };
@@ -214,7 +301,7 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
                 divider,
                 divider
             );
-            help_style = Style::default().fg(Color::DarkGray);
+            help_style = theme.muted;
         }
         let status = Paragraph::new(Text::from(vec![
             Line::from(Span::raw(status_line)),
@@ -228,44 +315,11 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
         );
         frame.render_widget(status, vertical[1]);
 
-        if total == 0 {
-            let empty = Paragraph::new("No output yet")
-                .style(Style::default().fg(Color::DarkGray))
-                .block(Block::default());
-            frame.render_widget(empty, log_area);
-        }
-
         if app.show_help {
-            let popup_area = centered_rect(60, 60, area);
-            let help_text = vec![
-                "Navigation:",
-                "  Up/Down    Select process",
-                "  PageUp/Dn  Scroll logs",
-                "  Home/End   Scroll to top/bottom",
-                "  Tab        Cycle selection",
-                "",
-                "Actions:",
-                "  Enter      Send input to process",
-                "  f          Toggle auto-follow",
-                "  t          Toggle timeline view",
-                "  a          Toggle ANSI stripping",
-                "  j          Toggle JSON formatting",
-                "  r          Restart selected",
-                "  k          Kill selected",
-                "  R          Restart ALL",
-                "  g          Restart Group (by tag)",
-                "  e          Export logs to file",
-                "",
-                "Search & Filter:",
-                "  /          Search (jump to match)",
-                "  n/N        Next/Prev match",
-                "  F          Filter (hide non-matching)",
-                "",
-                "General:",
-                "  ?          Toggle this help",
-                "  q          Quit",
-            ]
-            .join("\n");
+            let help_text = app.keys.help_text();
+            // Tall enough for every binding when the terminal allows it.
+            let needed = (help_text.lines().count() as u16 + 2) * 100 / area.height.max(1);
+            let popup_area = centered_rect(60, needed.clamp(60, 100), area);
 
             let help_block = Paragraph::new(help_text)
                 .block(
@@ -274,7 +328,7 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
                         .borders(Borders::ALL)
                         .border_type(BorderType::Rounded),
                 )
-                .style(Style::default().bg(Color::DarkGray).fg(Color::White));
+                .style(theme.popup);
             
             // Clear the area first to ensure readability? 
             // Ratatui renders back-to-front, so just drawing on top is fine.
@@ -284,10 +338,235 @@ pub fn draw(app: &mut App, terminal: &mut TuiTerminal) -> io::Result<()> {
             frame.render_widget(ratatui::widgets::Clear, popup_area);
             frame.render_widget(help_block, popup_area);
         }
+
+        if app.input_mode == InputMode::Palette {
+            draw_palette(frame, app, area);
+        } else if app.input_mode == InputMode::GoTo {
+            draw_goto(frame, app, area);
+        }
     })?;
     Ok(())
 }
 
+const DETAILS_HEIGHT: u16 = 9;
+
+/// Draws an empty picker popup with the query prompt; returns the area for its list.
+fn draw_picker(
+    frame: &mut Frame,
+    app: &App,
+    area: ratatui::layout::Rect,
+    title: &str,
+) -> ratatui::layout::Rect {
+    let popup_area = centered_rect(60, 60, area);
+    let block = Block::default()
+        .title(title.to_string())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(app.theme.accent);
+    let inner = block.inner(popup_area);
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(block, popup_area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(inner);
+
+    let cursor = if app.use_symbols { "▌" } else { "|" };
+    let prompt = Line::from(vec![
+        Span::styled("> ", app.theme.accent),
+        Span::raw(app.input.clone()),
+        Span::raw(cursor),
+    ]);
+    frame.render_widget(Paragraph::new(prompt), rows[0]);
+    rows[1]
+}
+
+fn draw_goto(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let list_area = draw_picker(frame, app, area, "Go to process");
+    let matches = app.goto_matches();
+    if matches.is_empty() {
+        let empty = Paragraph::new("No matching processes").style(app.theme.muted);
+        frame.render_widget(empty, list_area);
+        return;
+    }
+    let items: Vec<ListItem> = matches
+        .iter()
+        .filter_map(|id| app.processes.get(*id))
+        .map(|process| {
+            let status = status_char(&process.status, process.succeeded, app.use_symbols);
+            let mut spans = vec![
+                Span::styled(format!("[{}] ", status), app.theme.status_style(&process.status, process.succeeded)),
+                Span::styled(
+                    process.spec.name.clone(),
+                    app.theme.process_style(process.spec.color.as_deref()),
+                ),
+            ];
+            if !process.spec.tags.is_empty() {
+                spans.push(Span::styled(
+                    format!("  {}", process.spec.tags.join(", ")),
+                    app.theme.muted,
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(app.theme.selection.add_modifier(Modifier::BOLD));
+    frame.render_stateful_widget(list, list_area, &mut list_state(app.goto_index, matches.len()));
+}
+
+fn draw_palette(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let list_area = draw_picker(frame, app, area, "Commands");
+    let matches = app.palette_matches();
+    if matches.is_empty() {
+        let empty = Paragraph::new("No matching commands").style(app.theme.muted);
+        frame.render_widget(empty, list_area);
+        return;
+    }
+    let width = list_area.width as usize;
+    let items: Vec<ListItem> = matches
+        .iter()
+        .map(|entry| {
+            let shortcut_len = entry.shortcut.chars().count();
+            let label = truncate(&entry.label, width.saturating_sub(shortcut_len + 2));
+            let padding = width.saturating_sub(label.chars().count() + shortcut_len);
+            ListItem::new(Line::from(vec![
+                Span::raw(label),
+                Span::raw(" ".repeat(padding)),
+                Span::styled(entry.shortcut.clone(), app.theme.muted),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(app.theme.selection.add_modifier(Modifier::BOLD));
+    frame.render_stateful_widget(list, list_area, &mut list_state(app.palette_index, matches.len()));
+}
+
+fn draw_details(frame: &mut Frame, process: &ProcessState, area: ratatui::layout::Rect, theme: &Theme) {
+    let block = Block::default()
+        .title(format!("Details - {}", process.spec.name))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(inner);
+    let inner = columns[0];
+    frame.render_widget(Paragraph::new(tree_lines(process, inner.height as usize, theme)), columns[1]);
+
+    let stats = &process.stats;
+    let label = theme.muted;
+    let pid = process
+        .pid
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| "-".into());
+    let summary = match &stats.latest {
+        Some(sample) => Line::from(vec![
+            Span::styled("pid ", label),
+            Span::raw(pid),
+            Span::styled("  cpu ", label),
+            Span::raw(format!("{:.1}%", sample.cpu_percent)),
+            Span::styled("  rss ", label),
+            Span::raw(format_bytes(sample.rss_bytes)),
+            Span::styled("  threads ", label),
+            Span::raw(sample.threads.to_string()),
+            Span::styled("  children ", label),
+            Span::raw(sample.children.to_string()),
+        ]),
+        None if cfg!(target_os = "linux") => {
+            Line::from(Span::styled("not running", label))
+        }
+        None => Line::from(Span::styled("resource stats are only available on Linux", label)),
+    };
+    let peaks = Line::from(vec![
+        Span::styled("peak cpu ", label),
+        Span::raw(format!("{:.1}%", stats.peak_cpu_percent)),
+        Span::styled("  peak rss ", label),
+        Span::raw(format_bytes(stats.peak_rss_bytes)),
+        Span::styled("  peak children ", label),
+        Span::raw(stats.peak_children.to_string()),
+    ]);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+    frame.render_widget(Paragraph::new(Text::from(vec![summary, peaks])), rows[0]);
+    let width = inner.width.saturating_sub(6) as usize;
+    render_sparkline(frame, ("cpu", theme.muted), &stats.cpu_history, theme.cpu, rows[1], width);
+    render_sparkline(frame, ("rss", theme.muted), &stats.rss_history, theme.rss, rows[2], width);
+}
+
+fn tree_lines(process: &ProcessState, height: usize, theme: &Theme) -> Text<'static> {
+    let label = theme.muted;
+    let mut lines = vec![Line::from(Span::styled("tree", label))];
+    if process.tree.is_empty() {
+        lines.push(Line::from(Span::styled("-", label)));
+        return Text::from(lines);
+    }
+    let visible = height.saturating_sub(1);
+    for (idx, node) in process.tree.iter().enumerate() {
+        if idx + 1 == visible && process.tree.len() > visible {
+            let more = process.tree.len() - idx;
+            lines.push(Line::from(Span::styled(format!("… {} more", more), label)));
+            break;
+        }
+        let indent = if node.depth == 0 {
+            String::new()
+        } else {
+            format!("{}└ ", "  ".repeat(node.depth - 1))
+        };
+        let mut spans = vec![
+            Span::styled(indent, label),
+            Span::raw(format!("{} ", node.pid)),
+            Span::styled(node.comm.clone(), theme.accent),
+        ];
+        if node.orphaned {
+            spans.push(Span::styled(" (orphaned)", theme.warning));
+        }
+        lines.push(Line::from(spans));
+    }
+    Text::from(lines)
+}
+
+fn render_sparkline(
+    frame: &mut Frame,
+    (label, label_style): (&str, Style),
+    history: &std::collections::VecDeque<u64>,
+    style: Style,
+    area: ratatui::layout::Rect,
+    width: usize,
+) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(5), Constraint::Min(1)])
+        .split(area);
+    frame.render_widget(
+        Paragraph::new(Span::styled(label.to_string(), label_style)),
+        columns[0],
+    );
+    // Keep the most recent samples so the sparkline scrolls from the right.
+    let data: Vec<u64> = history
+        .iter()
+        .skip(history.len().saturating_sub(width))
+        .copied()
+        .collect();
+    frame.render_widget(
+        Sparkline::default()
+            .data(&data)
+            .style(style),
+        columns[1],
+    );
+}
+
 fn centered_rect(
     percent_x: u16,
     percent_y: u16,
@@ -327,28 +606,40 @@ fn log_title(app: &App) -> String {
     if app.timeline_view {
         return "Timeline".to_string();
     }
-    if let Some(process) = app.selected_process() {
-        match &process.status {
-            ProcessStatus::Running => format!("Logs - {} (running)", process.spec.name),
-            ProcessStatus::Exited { code } => {
-                format!("Logs - {} (exited {:?})", process.spec.name, code)
-            }
-            ProcessStatus::Failed { .. } => format!("Logs - {} (failed)", process.spec.name),
-            ProcessStatus::Starting => format!("Logs - {} (starting)", process.spec.name),
-            ProcessStatus::Idle => format!("Logs - {} (idle)", process.spec.name),
+    app.selected_process()
+        .map(process_title)
+        .unwrap_or_else(|| "Logs".to_string())
+}
+
+fn process_title(process: &ProcessState) -> String {
+    match &process.status {
+        ProcessStatus::Running => format!("Logs - {} (running)", process.spec.name),
+        ProcessStatus::Exited { code } => {
+            format!("Logs - {} (exited {:?})", process.spec.name, code)
         }
-    } else {
-        "Logs".to_string()
+        ProcessStatus::Failed { .. } => format!("Logs - {} (failed)", process.spec.name),
+        ProcessStatus::Starting => format!("Logs - {} (starting)", process.spec.name),
+        ProcessStatus::Idle => format!("Logs - {} (idle)", process.spec.name),
     }
 }
 
-fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, usize, Vec<String>) {
+/// Renders the visible log lines of an unfocused `pane`, or of the focused view when `None`.
+fn render_log_lines(
+    app: &App,
+    pane: Option<&Pane>,
+    height: usize,
+    width: usize,
+) -> (Text<'static>, usize, Vec<String>) {
     if height == 0 {
         return (Text::default(), 0, Vec::new());
     }
+    let search_query = match pane {
+        Some(pane) => pane.search_query.as_ref(),
+        None => app.search_query.as_ref(),
+    };
 
     // Helper to process a single log line
-    let process_line = |text: &str, name: &str, color: Option<&str>| -> (Vec<Line<'static>>, Vec<String>) {
+    let process_line = |text: &str, name: &str, color: Option<&str>, stream: StreamKind| -> (Vec<Line<'static>>, Vec<String>) {
         let plain = strip_carriage(&sanitize_text(text, true));
         if let Some(query) = &app.filter_query {
             if !plain.contains(query) {
@@ -363,11 +654,15 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
         };
         let raw_lines: Vec<String> = plain.lines().map(|line| line.to_string()).collect();
 
-        let name_style = process_color(color);
+        let name_style = app.theme.process_style(color);
+        let content_style = match stream {
+            StreamKind::Stderr => app.theme.stderr,
+            StreamKind::Stdout => Style::default(),
+        };
         let prefix = format!("{} \u{203a} ", name);
         let prefix_len = prefix.chars().count();
         let indent = " ".repeat(prefix_len);
-        let use_ansi = !app.strip_ansi && !app.json_formatting && app.search_query.is_none();
+        let use_ansi = !app.strip_ansi && !app.json_formatting && search_query.is_none();
 
         if use_ansi {
             let lines = text
@@ -377,7 +672,10 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
                     let current_prefix = if i == 0 { &prefix } else { &indent };
                     let mut spans = Vec::new();
                     spans.push(Span::styled(current_prefix.to_string(), name_style));
-                    spans.extend(ansi_spans(line));
+                    spans.extend(ansi_spans(line).into_iter().map(|span| {
+                        let style = content_style.patch(span.style);
+                        span.style(style)
+                    }));
                     let trimmed = truncate_spans(spans, width.saturating_sub(1));
                     Line::from(trimmed)
                 })
@@ -394,13 +692,13 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
                 let trimmed = truncate(&combined, width.saturating_sub(1));
 
                 // Highlighting logic
-                if let Some(query) = &app.search_query {
-                    if !query.is_empty() && trimmed.contains(query) {
+                if let Some(query) = search_query {
+                    if !query.is_empty() && trimmed.contains(query.as_str()) {
                         let mut spans = Vec::new();
-                        let highlight_style = Style::default().fg(Color::Black).bg(Color::Yellow);
+                        let highlight_style = app.theme.search;
                         let mut last_idx = 0;
 
-                        for (idx, match_str) in trimmed.match_indices(query) {
+                        for (idx, match_str) in trimmed.match_indices(query.as_str()) {
                             if idx > last_idx {
                                 let pre_match = &trimmed[last_idx..idx];
                                 // Apply prefix style if this part overlaps with prefix
@@ -461,13 +759,13 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
                                     if end_pos <= prefix_width {
                                         styled_spans.push(Span::styled(content, name_style));
                                     } else if current_pos >= prefix_width {
-                                        styled_spans.push(Span::raw(content));
+                                        styled_spans.push(Span::styled(content, content_style));
                                     } else {
                                         // Overlaps boundary. Use raw to avoid complexity.
-                                        styled_spans.push(Span::raw(content));
+                                        styled_spans.push(Span::styled(content, content_style));
                                     }
                                 } else {
-                                    styled_spans.push(Span::raw(content));
+                                    styled_spans.push(Span::styled(content, content_style));
                                 }
                             }
                             current_pos += len;
@@ -483,10 +781,10 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
                         .to_string();
                     Line::from(vec![
                         Span::styled(current_prefix.to_string(), name_style),
-                        Span::raw(rest),
+                        Span::styled(rest, content_style),
                     ])
                 } else {
-                    Line::from(Span::raw(trimmed))
+                    Line::from(Span::styled(trimmed, content_style))
                 }
             })
             .collect();
@@ -517,7 +815,7 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
                 .get(entry.process_id)
                 .map(|p| (p.spec.name.as_str(), p.spec.color.as_deref()))
                 .unwrap_or(("process", None));
-            let (rendered, raw) = process_line(&entry.text, name, color);
+            let (rendered, raw) = process_line(&entry.text, name, color, entry.stream);
             all_lines.extend(rendered);
             raw_lines.extend(raw);
         }
@@ -532,7 +830,10 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
         let end = (start + height).min(total_filtered);
         lines = all_lines[start..end].to_vec();
         raw_lines = raw_lines[start..end].to_vec();
-    } else if let Some(process) = app.selected_process() {
+    } else if let Some((process, scroll, follow)) = match pane {
+        Some(pane) => app.processes.get(pane.process).map(|p| (p, pane.scroll, pane.follow)),
+        None => app.selected_process().map(|p| (p, p.scroll, p.follow)),
+    } {
         let mut all_lines = Vec::new();
         let mut all_raw = Vec::new();
         let name = process.spec.name.as_str();
@@ -542,24 +843,27 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
             // Strip existing prefix if present in raw log to avoid double prefixing?
             // The original logic stripped it.
             let text = strip_existing_prefix(name, &entry.text);
-            let (rendered, raw) = process_line(&text, name, color);
+            let (rendered, raw) = process_line(&text, name, color, entry.stream);
             all_lines.extend(rendered);
             all_raw.extend(raw);
         }
         total_filtered = all_lines.len();
 
-        let start = if process.follow {
+        let start = if follow {
             total_filtered.saturating_sub(height)
         } else {
-            process.scroll.min(total_filtered.saturating_sub(height))
+            scroll.min(total_filtered.saturating_sub(height))
         };
         let end = (start + height).min(total_filtered);
         lines = all_lines[start..end].to_vec();
         raw_lines = all_raw[start..end].to_vec();
     }
 
-    if let Some((start, end)) = app.selection_range_for(raw_lines.len()) {
-        let selection_style = Style::default().bg(Color::DarkGray);
+    if let Some((start, end)) = app
+        .selection_range_for(raw_lines.len())
+        .filter(|_| pane.is_none())
+    {
+        let selection_style = app.theme.selection;
         for (idx, line) in lines.iter_mut().enumerate() {
             if idx >= start && idx <= end {
                 line.style = line.style.patch(selection_style);
@@ -570,6 +874,19 @@ fn render_log_lines(app: &App, height: usize, width: usize) -> (Text<'static>, u
     (Text::from(lines), total_filtered, raw_lines)
 }
 
+/// Processes whose row fits in `height` lines of a list scrolled to `offset`.
+fn visible_rows(rows: &[(Option<usize>, usize)], offset: usize, height: u16) -> Vec<usize> {
+    let mut used = 0;
+    rows.iter()
+        .skip(offset)
+        .take_while(|(_, lines)| {
+            used += lines;
+            used <= height as usize
+        })
+        .filter_map(|(id, _)| *id)
+        .collect()
+}
+
 fn list_state(selected: usize, len: usize) -> ratatui::widgets::ListState {
     let mut state = ratatui::widgets::ListState::default();
     if len > 0 {
@@ -578,29 +895,7 @@ fn list_state(selected: usize, len: usize) -> ratatui::widgets::ListState {
     state
 }
 
-fn process_color(name: Option<&str>) -> Style {
-    if let Some(color) = name {
-        return Style::default().fg(color_from_name(color).unwrap_or(Color::White));
-    }
-    Style::default().fg(Color::White)
-}
-
-fn color_from_name(name: &str) -> Option<Color> {
-    match name.to_lowercase().as_str() {
-        "black" => Some(Color::Black),
-        "red" => Some(Color::Red),
-        "green" => Some(Color::Green),
-        "yellow" => Some(Color::Yellow),
-        "blue" => Some(Color::Blue),
-        "magenta" => Some(Color::Magenta),
-        "cyan" => Some(Color::Cyan),
-        "gray" | "grey" => Some(Color::Gray),
-        "white" => Some(Color::White),
-        _ => None,
-    }
-}
-
-fn status_char(status: &ProcessStatus, use_symbols: bool) -> char {
+fn status_char(status: &ProcessStatus, succeeded: bool, use_symbols: bool) -> char {
     if use_symbols {
         return match status {
             ProcessStatus::Idle => '·',
@@ -614,13 +909,8 @@ fn status_char(status: &ProcessStatus, use_symbols: bool) -> char {
         ProcessStatus::Idle => '.',
         ProcessStatus::Starting => 'S',
         ProcessStatus::Running => 'R',
-        ProcessStatus::Exited { code } => {
-            if code.unwrap_or(1) == 0 {
-                'E'
-            } else {
-                'X'
-            }
-        }
+        ProcessStatus::Exited { .. } if succeeded => 'E',
+        ProcessStatus::Exited { .. } => 'X',
         ProcessStatus::Failed { .. } => 'F',
     }
 }
@@ -641,22 +931,6 @@ fn strip_existing_prefix(name: &str, text: &str) -> String {
     text.to_string()
 }
 
-fn status_style(status: &ProcessStatus) -> Style {
-    match status {
-        ProcessStatus::Idle => Style::default().fg(Color::DarkGray),
-        ProcessStatus::Starting => Style::default().fg(Color::Yellow),
-        ProcessStatus::Running => Style::default().fg(Color::Green),
-        ProcessStatus::Exited { code } => {
-            if code.unwrap_or(1) == 0 {
-                Style::default().fg(Color::Gray)
-            } else {
-                Style::default().fg(Color::Red)
-            }
-        }
-        ProcessStatus::Failed { .. } => Style::default().fg(Color::Red),
-    }
-}
-
 fn truncate(text: &str, max: usize) -> String {
     if max == 0 {
         return String::new();
@@ -722,6 +996,14 @@ mod tests {
         assert_eq!(strip_existing_prefix("api", "nope"), "nope");
     }
 
+    #[test]
+    fn visible_rows_skips_headers_and_scrolled_out_rows() {
+        let rows = [(None, 1), (Some(0), 2), (Some(1), 2), (None, 1), (Some(2), 1), (Some(3), 2)];
+        assert_eq!(visible_rows(&rows, 0, 5), vec![0, 1]);
+        assert_eq!(visible_rows(&rows, 2, 5), vec![1, 2]);
+        assert_eq!(visible_rows(&rows, 2, 10), vec![1, 2, 3]);
+    }
+
     #[test]
     fn truncate_shortens_and_marks() {
         assert_eq!(truncate("abcdef", 4), "abc~");
@@ -748,17 +1030,6 @@ mod tests {
         assert!(truncated.is_empty());
     }
 
-    #[test]
-    fn status_style_reflects_state() {
-        assert_eq!(status_style(&ProcessStatus::Idle).fg, Some(Color::DarkGray));
-        assert_eq!(status_style(&ProcessStatus::Running).fg, Some(Color::Green));
-        assert_eq!(status_style(&ProcessStatus::Starting).fg, Some(Color::Yellow));
-        assert_eq!(
-            status_style(&ProcessStatus::Exited { code: Some(1) }).fg,
-            Some(Color::Red)
-        );
-    }
-
     #[test]
     fn strip_carriage_keeps_last_segment() {
         assert_eq!(strip_carriage("abc\rdef"), "def");
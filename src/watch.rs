@@ -66,11 +66,7 @@ fn watch_process(
     }
 
     let debounce = Duration::from_millis(spec.watch_debounce_ms.max(50));
-    loop {
-        let event = match raw_rx.recv() {
-            Ok(res) => res,
-            Err(_) => break,
-        };
+    while let Ok(event) = raw_rx.recv() {
         if !is_relevant(&event, &matcher) {
             continue;
         }
@@ -235,7 +231,7 @@ mod tests {
     #[test]
     fn ignore_matcher_respects_globs() {
         let base = Path::new("/tmp/piperack-tests");
-        let matcher = IgnoreMatcher::new(base, &vec!["target".to_string()], true).unwrap();
+        let matcher = IgnoreMatcher::new(base, &["target".to_string()], true).unwrap();
         assert!(matcher.is_ignored(&base.join("target")));
         assert!(matcher.is_ignored(&PathBuf::from("target")));
         assert!(!matcher.is_ignored(&base.join("src")));